@@ -3,10 +3,9 @@
 //
 // Field tags match the upstream pre-BigInt schema verbatim, so workers
 // built against an old SDK decode these bytes unchanged. Tags absent
-// from these structs (script, certificates, withdrawals, mint,
-// reference_inputs, validity, collateral, auxiliary, proposals,
-// successful, ...) are deliberately dropped — workers that need them
-// must re-add them here and update the converter.
+// from these structs (script, withdrawals, reference_inputs, validity,
+// collateral, successful, ...) are deliberately dropped — workers that
+// need them must re-add them here and update the converter.
 
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -50,6 +49,8 @@ pub struct Asset {
     pub name: ::prost::bytes::Bytes,
     #[prost(uint64, tag = "2")]
     pub output_coin: u64,
+    #[prost(int64, tag = "3")]
+    pub mint_coin: i64,
 }
 
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -79,12 +80,85 @@ pub struct Tx {
     pub inputs: ::prost::alloc::vec::Vec<TxInput>,
     #[prost(message, repeated, tag = "2")]
     pub outputs: ::prost::alloc::vec::Vec<TxOutput>,
+    #[prost(message, repeated, tag = "3")]
+    pub certificates: ::prost::alloc::vec::Vec<Certificate>,
+    #[prost(message, repeated, tag = "5")]
+    pub mint: ::prost::alloc::vec::Vec<Multiasset>,
     #[prost(message, optional, tag = "7")]
     pub witnesses: ::core::option::Option<WitnessSet>,
     #[prost(uint64, tag = "9")]
     pub fee: u64,
+    #[prost(message, optional, tag = "12")]
+    pub auxiliary: ::core::option::Option<AuxData>,
     #[prost(bytes = "bytes", tag = "13")]
     pub hash: ::prost::bytes::Bytes,
+    #[prost(message, repeated, tag = "14")]
+    pub proposals: ::prost::alloc::vec::Vec<GovernanceActionProposal>,
+}
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AuxData {
+    #[prost(message, repeated, tag = "1")]
+    pub metadata: ::prost::alloc::vec::Vec<Metadata>,
+    #[prost(message, repeated, tag = "2")]
+    pub scripts: ::prost::alloc::vec::Vec<Script>,
+}
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Metadata {
+    #[prost(uint64, tag = "1")]
+    pub label: u64,
+    #[prost(message, optional, tag = "2")]
+    pub value: ::core::option::Option<Metadatum>,
+}
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Metadatum {
+    #[prost(oneof = "metadatum::Metadatum", tags = "1, 2, 3, 4, 5")]
+    pub metadatum: ::core::option::Option<metadatum::Metadatum>,
+}
+
+pub mod metadatum {
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Metadatum {
+        #[prost(int64, tag = "1")]
+        Int(i64),
+        #[prost(bytes, tag = "2")]
+        Bytes(::prost::bytes::Bytes),
+        #[prost(string, tag = "3")]
+        Text(::prost::alloc::string::String),
+        #[prost(message, tag = "4")]
+        Array(super::MetadatumArray),
+        #[prost(message, tag = "5")]
+        Map(super::MetadatumMap),
+    }
+}
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MetadatumArray {
+    #[prost(message, repeated, tag = "1")]
+    pub items: ::prost::alloc::vec::Vec<Metadatum>,
+}
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MetadatumMap {
+    #[prost(message, repeated, tag = "1")]
+    pub pairs: ::prost::alloc::vec::Vec<MetadatumPair>,
+}
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MetadatumPair {
+    #[prost(message, optional, tag = "1")]
+    pub key: ::core::option::Option<Metadatum>,
+    #[prost(message, optional, tag = "2")]
+    pub value: ::core::option::Option<Metadatum>,
 }
 
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -238,6 +312,463 @@ pub mod script {
     }
 }
 
+// ────────────────────────────────────────────────────────────────────────
+// Certificates
+//
+// Mirrors utxorpc-spec 0.17.0 certificates, minus the deprecated genesis
+// delegation / MIR kinds (tags 6 and 7) and the Plutus redeemer (tag
+// 100). A certificate of a dropped kind decodes with an empty oneof.
+// ────────────────────────────────────────────────────────────────────────
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct StakeCredential {
+    #[prost(oneof = "stake_credential::StakeCredential", tags = "1, 2")]
+    pub stake_credential: ::core::option::Option<stake_credential::StakeCredential>,
+}
+
+pub mod stake_credential {
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum StakeCredential {
+        #[prost(bytes, tag = "1")]
+        AddrKeyHash(::prost::bytes::Bytes),
+        #[prost(bytes, tag = "2")]
+        ScriptHash(::prost::bytes::Bytes),
+    }
+}
+
+/// Wire-compatible with upstream `RationalNumber`. The name is taken by
+/// the JSON flavor used in PParams.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Ratio {
+    #[prost(int32, tag = "1")]
+    pub numerator: i32,
+    #[prost(uint32, tag = "2")]
+    pub denominator: u32,
+}
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Relay {
+    #[prost(bytes = "bytes", tag = "1")]
+    pub ip_v4: ::prost::bytes::Bytes,
+    #[prost(bytes = "bytes", tag = "2")]
+    pub ip_v6: ::prost::bytes::Bytes,
+    #[prost(string, tag = "3")]
+    pub dns_name: ::prost::alloc::string::String,
+    #[prost(uint32, tag = "4")]
+    pub port: u32,
+}
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PoolMetadata {
+    #[prost(string, tag = "1")]
+    pub url: ::prost::alloc::string::String,
+    #[prost(bytes = "bytes", tag = "2")]
+    pub hash: ::prost::bytes::Bytes,
+}
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Anchor {
+    #[prost(string, tag = "1")]
+    pub url: ::prost::alloc::string::String,
+    #[prost(bytes = "bytes", tag = "2")]
+    pub content_hash: ::prost::bytes::Bytes,
+}
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DRep {
+    #[prost(oneof = "d_rep::Drep", tags = "1, 2, 3, 4")]
+    pub drep: ::core::option::Option<d_rep::Drep>,
+}
+
+pub mod d_rep {
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Drep {
+        #[prost(bytes, tag = "1")]
+        AddrKeyHash(::prost::bytes::Bytes),
+        #[prost(bytes, tag = "2")]
+        ScriptHash(::prost::bytes::Bytes),
+        #[prost(bool, tag = "3")]
+        Abstain(bool),
+        #[prost(bool, tag = "4")]
+        NoConfidence(bool),
+    }
+}
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Certificate {
+    #[prost(
+        oneof = "certificate::Certificate",
+        tags = "1, 2, 3, 4, 5, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19"
+    )]
+    pub certificate: ::core::option::Option<certificate::Certificate>,
+}
+
+pub mod certificate {
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Certificate {
+        #[prost(message, tag = "1")]
+        StakeRegistration(super::StakeCredential),
+        #[prost(message, tag = "2")]
+        StakeDeregistration(super::StakeCredential),
+        #[prost(message, tag = "3")]
+        StakeDelegation(super::StakeDelegationCert),
+        #[prost(message, tag = "4")]
+        PoolRegistration(super::PoolRegistrationCert),
+        #[prost(message, tag = "5")]
+        PoolRetirement(super::PoolRetirementCert),
+        #[prost(message, tag = "8")]
+        RegCert(super::RegCert),
+        #[prost(message, tag = "9")]
+        UnregCert(super::UnRegCert),
+        #[prost(message, tag = "10")]
+        VoteDelegCert(super::VoteDelegCert),
+        #[prost(message, tag = "11")]
+        StakeVoteDelegCert(super::StakeVoteDelegCert),
+        #[prost(message, tag = "12")]
+        StakeRegDelegCert(super::StakeRegDelegCert),
+        #[prost(message, tag = "13")]
+        VoteRegDelegCert(super::VoteRegDelegCert),
+        #[prost(message, tag = "14")]
+        StakeVoteRegDelegCert(super::StakeVoteRegDelegCert),
+        #[prost(message, tag = "15")]
+        AuthCommitteeHotCert(super::AuthCommitteeHotCert),
+        #[prost(message, tag = "16")]
+        ResignCommitteeColdCert(super::ResignCommitteeColdCert),
+        #[prost(message, tag = "17")]
+        RegDrepCert(super::RegDRepCert),
+        #[prost(message, tag = "18")]
+        UnregDrepCert(super::UnRegDRepCert),
+        #[prost(message, tag = "19")]
+        UpdateDrepCert(super::UpdateDRepCert),
+    }
+}
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct StakeDelegationCert {
+    #[prost(message, optional, tag = "1")]
+    pub stake_credential: ::core::option::Option<StakeCredential>,
+    #[prost(bytes = "bytes", tag = "2")]
+    pub pool_keyhash: ::prost::bytes::Bytes,
+}
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PoolRegistrationCert {
+    #[prost(bytes = "bytes", tag = "1")]
+    pub operator: ::prost::bytes::Bytes,
+    #[prost(bytes = "bytes", tag = "2")]
+    pub vrf_keyhash: ::prost::bytes::Bytes,
+    #[prost(uint64, tag = "3")]
+    pub pledge: u64,
+    #[prost(uint64, tag = "4")]
+    pub cost: u64,
+    #[prost(message, optional, tag = "5")]
+    pub margin: ::core::option::Option<Ratio>,
+    #[prost(bytes = "bytes", tag = "6")]
+    pub reward_account: ::prost::bytes::Bytes,
+    #[prost(bytes = "bytes", repeated, tag = "7")]
+    pub pool_owners: ::prost::alloc::vec::Vec<::prost::bytes::Bytes>,
+    #[prost(message, repeated, tag = "8")]
+    pub relays: ::prost::alloc::vec::Vec<Relay>,
+    #[prost(message, optional, tag = "9")]
+    pub pool_metadata: ::core::option::Option<PoolMetadata>,
+}
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PoolRetirementCert {
+    #[prost(bytes = "bytes", tag = "1")]
+    pub pool_keyhash: ::prost::bytes::Bytes,
+    #[prost(uint64, tag = "2")]
+    pub epoch: u64,
+}
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RegCert {
+    #[prost(message, optional, tag = "1")]
+    pub stake_credential: ::core::option::Option<StakeCredential>,
+    #[prost(uint64, tag = "2")]
+    pub coin: u64,
+}
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UnRegCert {
+    #[prost(message, optional, tag = "1")]
+    pub stake_credential: ::core::option::Option<StakeCredential>,
+    #[prost(uint64, tag = "2")]
+    pub coin: u64,
+}
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct VoteDelegCert {
+    #[prost(message, optional, tag = "1")]
+    pub stake_credential: ::core::option::Option<StakeCredential>,
+    #[prost(message, optional, tag = "2")]
+    pub drep: ::core::option::Option<DRep>,
+}
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct StakeVoteDelegCert {
+    #[prost(message, optional, tag = "1")]
+    pub stake_credential: ::core::option::Option<StakeCredential>,
+    #[prost(bytes = "bytes", tag = "2")]
+    pub pool_keyhash: ::prost::bytes::Bytes,
+    #[prost(message, optional, tag = "3")]
+    pub drep: ::core::option::Option<DRep>,
+}
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct StakeRegDelegCert {
+    #[prost(message, optional, tag = "1")]
+    pub stake_credential: ::core::option::Option<StakeCredential>,
+    #[prost(bytes = "bytes", tag = "2")]
+    pub pool_keyhash: ::prost::bytes::Bytes,
+    #[prost(uint64, tag = "3")]
+    pub coin: u64,
+}
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct VoteRegDelegCert {
+    #[prost(message, optional, tag = "1")]
+    pub stake_credential: ::core::option::Option<StakeCredential>,
+    #[prost(message, optional, tag = "2")]
+    pub drep: ::core::option::Option<DRep>,
+    #[prost(uint64, tag = "3")]
+    pub coin: u64,
+}
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct StakeVoteRegDelegCert {
+    #[prost(message, optional, tag = "1")]
+    pub stake_credential: ::core::option::Option<StakeCredential>,
+    #[prost(bytes = "bytes", tag = "2")]
+    pub pool_keyhash: ::prost::bytes::Bytes,
+    #[prost(message, optional, tag = "3")]
+    pub drep: ::core::option::Option<DRep>,
+    #[prost(uint64, tag = "4")]
+    pub coin: u64,
+}
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AuthCommitteeHotCert {
+    #[prost(message, optional, tag = "1")]
+    pub committee_cold_credential: ::core::option::Option<StakeCredential>,
+    #[prost(message, optional, tag = "2")]
+    pub committee_hot_credential: ::core::option::Option<StakeCredential>,
+}
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ResignCommitteeColdCert {
+    #[prost(message, optional, tag = "1")]
+    pub committee_cold_credential: ::core::option::Option<StakeCredential>,
+    #[prost(message, optional, tag = "2")]
+    pub anchor: ::core::option::Option<Anchor>,
+}
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RegDRepCert {
+    #[prost(message, optional, tag = "1")]
+    pub drep_credential: ::core::option::Option<StakeCredential>,
+    #[prost(uint64, tag = "2")]
+    pub coin: u64,
+    #[prost(message, optional, tag = "3")]
+    pub anchor: ::core::option::Option<Anchor>,
+}
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UnRegDRepCert {
+    #[prost(message, optional, tag = "1")]
+    pub drep_credential: ::core::option::Option<StakeCredential>,
+    #[prost(uint64, tag = "2")]
+    pub coin: u64,
+}
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdateDRepCert {
+    #[prost(message, optional, tag = "1")]
+    pub drep_credential: ::core::option::Option<StakeCredential>,
+    #[prost(message, optional, tag = "2")]
+    pub anchor: ::core::option::Option<Anchor>,
+}
+
+// ────────────────────────────────────────────────────────────────────────
+// Governance proposals
+//
+// Mirrors utxorpc-spec 0.17.0 governance action proposals, minus the
+// protocol parameter update of a parameter change action (tag 2):
+// `PParams` only crosses the WIT boundary as JSON, see below.
+// ────────────────────────────────────────────────────────────────────────
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GovernanceActionProposal {
+    #[prost(uint64, tag = "1")]
+    pub deposit: u64,
+    #[prost(bytes = "bytes", tag = "2")]
+    pub reward_account: ::prost::bytes::Bytes,
+    #[prost(message, optional, tag = "3")]
+    pub gov_action: ::core::option::Option<GovernanceAction>,
+    #[prost(message, optional, tag = "4")]
+    pub anchor: ::core::option::Option<Anchor>,
+}
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GovernanceAction {
+    #[prost(
+        oneof = "governance_action::GovernanceAction",
+        tags = "1, 2, 3, 4, 5, 6, 7"
+    )]
+    pub governance_action: ::core::option::Option<governance_action::GovernanceAction>,
+}
+
+pub mod governance_action {
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum GovernanceAction {
+        #[prost(message, tag = "1")]
+        ParameterChangeAction(super::ParameterChangeAction),
+        #[prost(message, tag = "2")]
+        HardForkInitiationAction(super::HardForkInitiationAction),
+        #[prost(message, tag = "3")]
+        TreasuryWithdrawalsAction(super::TreasuryWithdrawalsAction),
+        #[prost(message, tag = "4")]
+        NoConfidenceAction(super::NoConfidenceAction),
+        #[prost(message, tag = "5")]
+        UpdateCommitteeAction(super::UpdateCommitteeAction),
+        #[prost(message, tag = "6")]
+        NewConstitutionAction(super::NewConstitutionAction),
+        #[prost(uint32, tag = "7")]
+        InfoAction(u32),
+    }
+}
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GovernanceActionId {
+    #[prost(bytes = "bytes", tag = "1")]
+    pub transaction_id: ::prost::bytes::Bytes,
+    #[prost(uint32, tag = "2")]
+    pub governance_action_index: u32,
+}
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ParameterChangeAction {
+    #[prost(message, optional, tag = "1")]
+    pub gov_action_id: ::core::option::Option<GovernanceActionId>,
+    #[prost(bytes = "bytes", tag = "3")]
+    pub policy_hash: ::prost::bytes::Bytes,
+}
+
+/// Wire-compatible with upstream `ProtocolVersion`. The name is taken by
+/// the JSON flavor used in PParams.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Version {
+    #[prost(uint32, tag = "1")]
+    pub major: u32,
+    #[prost(uint32, tag = "2")]
+    pub minor: u32,
+}
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct HardForkInitiationAction {
+    #[prost(message, optional, tag = "1")]
+    pub gov_action_id: ::core::option::Option<GovernanceActionId>,
+    #[prost(message, optional, tag = "2")]
+    pub protocol_version: ::core::option::Option<Version>,
+}
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TreasuryWithdrawalsAction {
+    #[prost(message, repeated, tag = "1")]
+    pub withdrawals: ::prost::alloc::vec::Vec<WithdrawalAmount>,
+    #[prost(bytes = "bytes", tag = "2")]
+    pub policy_hash: ::prost::bytes::Bytes,
+}
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WithdrawalAmount {
+    #[prost(bytes = "bytes", tag = "1")]
+    pub reward_account: ::prost::bytes::Bytes,
+    #[prost(uint64, tag = "2")]
+    pub coin: u64,
+}
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct NoConfidenceAction {
+    #[prost(message, optional, tag = "1")]
+    pub gov_action_id: ::core::option::Option<GovernanceActionId>,
+}
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdateCommitteeAction {
+    #[prost(message, optional, tag = "1")]
+    pub gov_action_id: ::core::option::Option<GovernanceActionId>,
+    #[prost(message, repeated, tag = "2")]
+    pub remove_committee_credentials: ::prost::alloc::vec::Vec<StakeCredential>,
+    #[prost(message, repeated, tag = "3")]
+    pub new_committee_credentials: ::prost::alloc::vec::Vec<NewCommitteeCredentials>,
+    #[prost(message, optional, tag = "4")]
+    pub new_committee_threshold: ::core::option::Option<Ratio>,
+}
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct NewCommitteeCredentials {
+    #[prost(message, optional, tag = "1")]
+    pub committee_cold_credential: ::core::option::Option<StakeCredential>,
+    #[prost(uint32, tag = "2")]
+    pub expires_epoch: u32,
+}
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct NewConstitutionAction {
+    #[prost(message, optional, tag = "1")]
+    pub gov_action_id: ::core::option::Option<GovernanceActionId>,
+    #[prost(message, optional, tag = "2")]
+    pub constitution: ::core::option::Option<Constitution>,
+}
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Constitution {
+    #[prost(message, optional, tag = "1")]
+    pub anchor: ::core::option::Option<Anchor>,
+    #[prost(bytes = "bytes", tag = "2")]
+    pub hash: ::prost::bytes::Bytes,
+}
+
 // ────────────────────────────────────────────────────────────────────────
 // PParams family
 //
@@ -24,6 +24,7 @@ fn wire_compat_tx_output_roundtrips_via_017() {
             assets: vec![legacy::Asset {
                 name: b"hello".to_vec().into(),
                 output_coin: 5,
+                mint_coin: 0,
             }],
         }],
         datum: Some(legacy::Datum {
@@ -68,6 +69,22 @@ fn wire_compat_tx_roundtrips_via_017() {
             as_output: None,
         }],
         outputs: vec![],
+        certificates: vec![legacy::Certificate {
+            certificate: Some(legacy::certificate::Certificate::PoolRetirement(
+                legacy::PoolRetirementCert {
+                    pool_keyhash: vec![0x33; 28].into(),
+                    epoch: 500,
+                },
+            )),
+        }],
+        mint: vec![legacy::Multiasset {
+            policy_id: vec![0xBB; 28].into(),
+            assets: vec![legacy::Asset {
+                name: b"burnt".to_vec().into(),
+                output_coin: 0,
+                mint_coin: -3,
+            }],
+        }],
         witnesses: Some(legacy::WitnessSet {
             vkeywitness: vec![legacy::VKeyWitness {
                 vkey: vec![0x11; 32].into(),
@@ -77,7 +94,26 @@ fn wire_compat_tx_roundtrips_via_017() {
             plutus_datums: vec![],
         }),
         fee: 1234,
+        auxiliary: None,
         hash: vec![0xCC; 32].into(),
+        proposals: vec![legacy::GovernanceActionProposal {
+            deposit: 100_000_000_000,
+            reward_account: vec![0xE1; 29].into(),
+            gov_action: Some(legacy::GovernanceAction {
+                governance_action: Some(
+                    legacy::governance_action::GovernanceAction::TreasuryWithdrawalsAction(
+                        legacy::TreasuryWithdrawalsAction {
+                            withdrawals: vec![legacy::WithdrawalAmount {
+                                reward_account: vec![0xE1; 29].into(),
+                                coin: 42,
+                            }],
+                            policy_hash: vec![].into(),
+                        },
+                    ),
+                ),
+            }),
+            anchor: None,
+        }],
     };
     let bytes = bal.encode_to_vec();
     let decoded = v17::Tx::decode(bytes.as_slice()).expect("decode 0.17.0");
@@ -85,14 +121,32 @@ fn wire_compat_tx_roundtrips_via_017() {
     assert_eq!(decoded.inputs[0].output_index, 1);
     assert_eq!(decoded.fee, 1234);
     assert_eq!(decoded.hash.to_vec(), vec![0xCC; 32]);
+    assert_eq!(decoded.mint[0].policy_id.to_vec(), vec![0xBB; 28]);
+    assert_eq!(decoded.mint[0].assets[0].mint_coin, -3);
+    match decoded.certificates[0].certificate.as_ref() {
+        Some(v17::certificate::Certificate::PoolRetirement(cert)) => {
+            assert_eq!(cert.pool_keyhash.to_vec(), vec![0x33; 28]);
+            assert_eq!(cert.epoch, 500);
+        }
+        other => panic!("unexpected certificate: {other:?}"),
+    }
     let witnesses = decoded.witnesses.as_ref().expect("witnesses present");
     assert_eq!(witnesses.vkeywitness.len(), 1);
     assert_eq!(witnesses.vkeywitness[0].vkey.to_vec(), vec![0x11; 32]);
     assert_eq!(witnesses.vkeywitness[0].signature.to_vec(), vec![0x22; 64]);
+    assert_eq!(decoded.proposals[0].deposit, 100_000_000_000);
+    match decoded.proposals[0]
+        .gov_action
+        .as_ref()
+        .and_then(|x| x.governance_action.as_ref())
+    {
+        Some(v17::governance_action::GovernanceAction::TreasuryWithdrawalsAction(action)) => {
+            assert_eq!(action.withdrawals[0].coin, 42);
+        }
+        other => panic!("unexpected governance action: {other:?}"),
+    }
     // Dropped tags decode as defaults:
-    assert!(decoded.certificates.is_empty());
     assert!(decoded.withdrawals.is_empty());
-    assert!(decoded.proposals.is_empty());
     assert!(!decoded.successful);
 }
 
@@ -0,0 +1,163 @@
+//! In-process pub/sub bus backing the `broadcast` interface.
+//!
+//! Messages published by a worker are queued in the bus and delivered by the
+//! broadcast driver (see `drivers::broadcast`) as `message` events to every
+//! worker subscribed to the topic. Delivery is deferred so that a worker can
+//! publish to a topic it is itself subscribed to.
+//!
+//! The bus can optionally be bridged to an external broker by providing a
+//! [`Bridge`] implementation. Messages published by workers are forwarded to
+//! the bridge, while messages coming from the broker are injected through
+//! [`Broadcast::publish`] (or `Runtime::publish_message`).
+
+use std::sync::Arc;
+
+use tokio::sync::{mpsc, Mutex};
+
+use crate::{metrics::Metrics, wit::balius::app::broadcast as wit, Error, WorkerId};
+
+/// Error code returned to the worker when the bus is no longer running.
+const BUS_CLOSED: wit::BroadcastError = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    /// The worker that published the message, `None` for external messages.
+    pub source: Option<WorkerId>,
+    pub topic: String,
+    pub payload: Vec<u8>,
+}
+
+#[async_trait::async_trait]
+pub trait Bridge {
+    /// Forward a message published by a worker to the external broker.
+    async fn forward(&mut self, message: &Message) -> Result<(), Error>;
+}
+
+#[derive(Clone)]
+pub struct Broadcast {
+    sender: mpsc::UnboundedSender<Message>,
+    receiver: Arc<Mutex<mpsc::UnboundedReceiver<Message>>>,
+    bridge: Option<Arc<Mutex<dyn Bridge + Send + Sync>>>,
+}
+
+impl Default for Broadcast {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Broadcast {
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+
+        Self {
+            sender,
+            receiver: Arc::new(Mutex::new(receiver)),
+            bridge: None,
+        }
+    }
+
+    pub fn with_bridge(mut self, bridge: Arc<Mutex<dyn Bridge + Send + Sync>>) -> Self {
+        self.bridge = Some(bridge);
+        self
+    }
+
+    /// Queue a message for delivery to the subscribed workers.
+    pub fn publish(&self, message: Message) -> Result<(), Error> {
+        self.sender
+            .send(message)
+            .map_err(|_| Error::Driver("broadcast bus is closed".to_string()))
+    }
+
+    /// Wait for the next queued message.
+    pub(crate) async fn next(&self) -> Option<Message> {
+        self.receiver.lock().await.recv().await
+    }
+
+    /// Forward a worker message to the external broker, if any. Messages
+    /// coming from the broker itself are never sent back.
+    pub(crate) async fn forward(&self, message: &Message) -> Result<(), Error> {
+        match (&self.bridge, &message.source) {
+            (Some(bridge), Some(_)) => bridge.lock().await.forward(message).await,
+            _ => Ok(()),
+        }
+    }
+}
+
+pub struct BroadcastHost {
+    worker_id: String,
+    bus: Broadcast,
+    metrics: Arc<Metrics>,
+}
+impl BroadcastHost {
+    pub fn new(worker_id: &str, bus: &Broadcast, metrics: &Arc<Metrics>) -> Self {
+        Self {
+            worker_id: worker_id.to_string(),
+            bus: bus.clone(),
+            metrics: metrics.clone(),
+        }
+    }
+}
+
+impl wit::Host for BroadcastHost {
+    async fn publish_msg(
+        &mut self,
+        topic: String,
+        payload: wit::Msg,
+    ) -> Result<(), wit::BroadcastError> {
+        self.metrics.broadcast_publish(&self.worker_id, &topic);
+
+        self.bus
+            .publish(Message {
+                source: Some(self.worker_id.clone()),
+                topic,
+                payload,
+            })
+            .map_err(|_| BUS_CLOSED)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct RecordingBridge {
+        forwarded: Vec<Message>,
+    }
+
+    #[async_trait::async_trait]
+    impl Bridge for RecordingBridge {
+        async fn forward(&mut self, message: &Message) -> Result<(), Error> {
+            self.forwarded.push(message.clone());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn forwards_only_worker_messages() {
+        let bridge = Arc::new(Mutex::new(RecordingBridge::default()));
+        let bus = Broadcast::new().with_bridge(bridge.clone());
+
+        let from_worker = Message {
+            source: Some("a".to_string()),
+            topic: "prices".to_string(),
+            payload: b"{}".to_vec(),
+        };
+        let from_broker = Message {
+            source: None,
+            ..from_worker.clone()
+        };
+
+        bus.publish(from_worker.clone()).unwrap();
+        bus.publish(from_broker.clone()).unwrap();
+
+        for expected in [&from_worker, &from_broker] {
+            let message = bus.next().await.unwrap();
+            assert_eq!(&message, expected);
+            bus.forward(&message).await.unwrap();
+        }
+
+        assert_eq!(bridge.lock().await.forwarded, vec![from_worker]);
+    }
+}
@@ -0,0 +1,144 @@
+//! On-disk cache of compiled worker components.
+//!
+//! Compiling a component is by far the slowest part of registering a worker.
+//! The cache keeps the output of `Component::serialize` keyed by the hash of
+//! the wasm bytes and the compatibility hash of the engine, which covers the
+//! wasmtime version and the compilation settings. Entries built by any other
+//! engine are never picked up and get replaced the next time the same wasm is
+//! compiled.
+
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    path::{Path, PathBuf},
+};
+
+use pallas::crypto::hash::Hasher as Blake2b;
+use tracing::{debug, warn};
+use wasmtime::component::Component;
+
+use crate::Error;
+
+const EXTENSION: &str = "cwasm";
+
+#[derive(Clone)]
+pub(crate) struct ComponentCache {
+    dir: PathBuf,
+}
+
+impl ComponentCache {
+    pub fn new(dir: impl AsRef<Path>) -> Result<Self, Error> {
+        std::fs::create_dir_all(dir.as_ref())?;
+
+        Ok(Self {
+            dir: dir.as_ref().to_owned(),
+        })
+    }
+
+    fn entry_name(engine: &wasmtime::Engine, wasm: &[u8]) -> (String, String) {
+        let wasm_hash = Blake2b::<256>::hash(wasm).to_string();
+
+        let mut hasher = DefaultHasher::new();
+        engine.precompile_compatibility_hash().hash(&mut hasher);
+        let engine_hash = format!("{:016x}", hasher.finish());
+
+        (wasm_hash, engine_hash)
+    }
+
+    /// Return the compiled component for `wasm`, compiling and storing it if
+    /// there's no usable entry yet.
+    pub fn load(&self, engine: &wasmtime::Engine, wasm: &[u8]) -> Result<Component, Error> {
+        let (wasm_hash, engine_hash) = Self::entry_name(engine, wasm);
+        let path = self
+            .dir
+            .join(format!("{wasm_hash}-{engine_hash}.{EXTENSION}"));
+
+        if path.exists() {
+            // SAFETY: entries are only written by this cache, from the output
+            // of `Component::serialize`, and wasmtime rejects artifacts built
+            // by an incompatible engine.
+            match unsafe { Component::deserialize_file(engine, &path) } {
+                Ok(component) => {
+                    debug!(path = %path.display(), "loaded component from cache");
+                    return Ok(component);
+                }
+                Err(err) => {
+                    warn!(path = %path.display(), %err, "discarding unusable cached component");
+                }
+            }
+        }
+
+        let component = Component::new(engine, wasm)?;
+
+        if let Err(err) = self.store(&wasm_hash, &path, &component) {
+            warn!(path = %path.display(), %err, "failed to cache compiled component");
+        }
+
+        Ok(component)
+    }
+
+    fn store(&self, wasm_hash: &str, path: &Path, component: &Component) -> Result<(), Error> {
+        let bytes = component.serialize()?;
+
+        // write to a temp file first so a crash never leaves a truncated
+        // entry. The name is unique so concurrent writers of the same entry,
+        // from this process or another, don't clobber each other.
+        let tmp = path.with_extension(format!(
+            "{}-{:08x}.tmp",
+            std::process::id(),
+            rand::random::<u32>()
+        ));
+        std::fs::write(&tmp, bytes)?;
+        std::fs::rename(&tmp, path)?;
+
+        // entries of the same wasm built by other engines are stale
+        for entry in std::fs::read_dir(&self.dir)? {
+            let other = entry?.path();
+            let stale = other != path
+                && other.extension().is_some_and(|x| x == EXTENSION)
+                && other
+                    .file_name()
+                    .and_then(|x| x.to_str())
+                    .is_some_and(|x| x.starts_with(wasm_hash));
+
+            if stale {
+                std::fs::remove_file(other)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reuses_compiled_components() {
+        let dir = std::env::temp_dir().join(format!("balius-cache-{}", std::process::id()));
+        let cache = ComponentCache::new(&dir).unwrap();
+
+        let engine = wasmtime::Engine::default();
+        let wasm = wat::parse_str("(component)").unwrap();
+
+        let entries = || std::fs::read_dir(&dir).unwrap().count();
+
+        cache.load(&engine, &wasm).unwrap();
+        assert_eq!(entries(), 1);
+
+        // a stale entry from another engine gets replaced
+        let mut config = wasmtime::Config::new();
+        config.consume_fuel(true);
+        let other = wasmtime::Engine::new(&config).unwrap();
+        cache.load(&other, &wasm).unwrap();
+        assert_eq!(entries(), 1);
+
+        // a corrupted entry is recompiled
+        let entry = std::fs::read_dir(&dir).unwrap().next().unwrap().unwrap();
+        std::fs::write(entry.path(), b"garbage").unwrap();
+        cache.load(&other, &wasm).unwrap();
+        assert_eq!(entries(), 1);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
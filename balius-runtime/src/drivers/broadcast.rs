@@ -0,0 +1,38 @@
+//! Driver to deliver broadcast messages.
+//!
+//! This driver drains the Runtime's pub/sub bus and funnels each message
+//! into every worker subscribed to its topic. Messages published by workers
+//! are also forwarded to the external broker bridge, if one is configured.
+
+use tokio::select;
+use tokio_util::sync::CancellationToken;
+use tracing::warn;
+
+use crate::{Error, Runtime};
+
+pub async fn run(runtime: Runtime, cancel: CancellationToken) -> Result<(), Error> {
+    let bus = runtime
+        .broadcast
+        .clone()
+        .ok_or(Error::Config("broadcast is not enabled".to_string()))?;
+
+    loop {
+        select! {
+            _ = cancel.cancelled() => {
+                warn!("broadcast driver cancelled");
+                break Ok(())
+            },
+            message = bus.next() => {
+                let Some(message) = message else {
+                    break Ok(())
+                };
+
+                runtime.handle_message(&message).await;
+
+                if let Err(err) = bus.forward(&message).await {
+                    warn!(topic = message.topic, %err, "failed to forward message to bridge");
+                }
+            }
+        }
+    }
+}
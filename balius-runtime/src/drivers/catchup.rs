@@ -0,0 +1,73 @@
+//! Driver to bring lagging workers up to date.
+//!
+//! A worker whose cursor is behind the head of the store log (eg: a worker
+//! added to an already synced daemon) doesn't receive live chain events.
+//! This driver replays the logged blocks into each of them, in batches, until
+//! they reach the head and join the live feed. Up-to-date workers keep
+//! following the tip in the meantime.
+//!
+//! The log only goes back to where the daemon started syncing. Chain events
+//! before that aren't fetched again, a worker starting earlier gets a
+//! warning and is caught up from the first logged block.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::select;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+use crate::{Error, Runtime};
+
+const DEFAULT_BATCH_SIZE: usize = 100;
+const DEFAULT_POLL_INTERVAL_SECS: u64 = 1;
+
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+pub struct Config {
+    /// Max number of log entries replayed into a worker in one go.
+    pub batch_size: Option<usize>,
+
+    /// How often (in seconds) to look for lagging workers.
+    pub poll_interval: Option<u64>,
+}
+
+pub async fn run(config: Config, runtime: Runtime, cancel: CancellationToken) -> Result<(), Error> {
+    let batch_size = config.batch_size.unwrap_or(DEFAULT_BATCH_SIZE);
+    let poll_interval =
+        Duration::from_secs(config.poll_interval.unwrap_or(DEFAULT_POLL_INTERVAL_SECS));
+
+    loop {
+        let lagging = match runtime.lagging_workers().await {
+            Ok(x) => x,
+            Err(err) => {
+                warn!(%err, "failed to look for lagging workers");
+                vec![]
+            }
+        };
+
+        let mut pending = false;
+
+        for worker in lagging {
+            match runtime.catch_up_worker(&worker, batch_size).await {
+                Ok(true) => info!(worker, "worker caught up with the chain"),
+                Ok(false) => pending = true,
+                Err(err) => warn!(worker, %err, "failed to catch up worker"),
+            }
+        }
+
+        // keep going right away while there's work left
+        let wait = if pending {
+            Duration::ZERO
+        } else {
+            poll_interval
+        };
+
+        select! {
+            _ = cancel.cancelled() => {
+                warn!("catch-up driver cancelled");
+                break Ok(())
+            },
+            _ = tokio::time::sleep(wait) => {}
+        }
+    }
+}
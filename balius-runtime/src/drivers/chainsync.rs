@@ -65,6 +65,28 @@ async fn gather_blocks(
     }
 }
 
+/// Resolve a point without hash, as given by [`Runtime::chain_cursor`] when
+/// starting from the start slot of the workers, to the first block at or
+/// after its slot.
+async fn resolve_start(
+    sync: &mut CardanoSyncClient,
+    point: utxorpc::spec::sync::BlockRef,
+) -> Result<Block, Error> {
+    let slot = point.slot;
+
+    let page = sync
+        .dump_history(Some(point), 1)
+        .await
+        .map_err(|e| Error::Driver(e.to_string()))?;
+
+    page.items
+        .into_iter()
+        .next()
+        .and_then(|x| x.parsed)
+        .map(Block::Cardano)
+        .ok_or_else(|| Error::Driver(format!("no block found at or after slot {slot}")))
+}
+
 pub async fn run(
     config: Config,
     mut runtime: Runtime,
@@ -84,12 +106,22 @@ pub async fn run(
 
     let mut sync = builder.build::<CardanoSyncClient>().await;
 
-    let cursor = runtime
-        .chain_cursor()
-        .await?
-        .map(Into::into)
-        .into_iter()
-        .collect();
+    let mut start = None;
+
+    let cursor: Option<utxorpc::spec::sync::BlockRef> =
+        runtime.chain_cursor().await?.map(Into::into);
+
+    let cursor = match cursor {
+        Some(point) if point.hash.is_empty() => {
+            // intersecting at the first block leaves it out of the tip, so
+            // it's handled right before following it
+            let block = resolve_start(&mut sync, point).await?;
+            let point = block.chain_point().into();
+            start = Some(block);
+            vec![point]
+        }
+        point => point.into_iter().collect(),
+    };
 
     info!(cursor = ?cursor, "found runtime cursor");
 
@@ -111,6 +143,11 @@ pub async fn run(
         _ => return Err(Error::Driver("unexpected event".to_string())),
     }
 
+    if let Some(block) = start {
+        info!(slot = block.slot(), "handling start block");
+        runtime.handle_chain(&vec![], &block).await?;
+    }
+
     info!("starting follow-tip loop");
 
     loop {
@@ -7,6 +7,13 @@
 //! for the worker. JSON-RPC params are mapped directly into Balius request
 //! params.
 //!
+//! Requests to the reserved `_admin` path are served by the runtime itself:
+//!
+//! - `dead_letters.list`, with an optional `worker` param, lists the dead
+//!   letters kept in the store, oldest first.
+//! - `dead_letters.replay`, with an `id` param, dispatches a dead letter to
+//!   its worker again, removing it once handled.
+//!
 //! The JSON-RPC server is implemented as a Warp application and adheres to
 //! the JSON-RPC 2.0 spec.
 
@@ -17,7 +24,10 @@ use tokio_util::sync::CancellationToken;
 use tracing::{debug, error};
 use warp::Filter as _;
 
-use crate::{wit, Error, Runtime};
+use crate::{wit, DeadLetterId, Error, Runtime};
+
+/// Path of the requests served by the runtime instead of a worker.
+const ADMIN_PATH: &str = "_admin";
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Config {
@@ -90,18 +100,109 @@ pub async fn handle_request(
     }
 }
 
+#[derive(Deserialize)]
+struct ListDeadLettersParams {
+    worker: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ReplayDeadLetterParams {
+    id: DeadLetterId,
+}
+
+async fn handle_admin_method(
+    runtime: &Runtime,
+    method: &str,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, String> {
+    match method {
+        "dead_letters.list" => {
+            let params: ListDeadLettersParams =
+                serde_json::from_value(params).map_err(|x| x.to_string())?;
+
+            let letters = runtime
+                .dead_letters(params.worker.as_deref())
+                .await
+                .map_err(|x| x.to_string())?;
+
+            let letters = letters
+                .into_iter()
+                .map(|(id, letter)| {
+                    json!({
+                        "id": id,
+                        "worker": letter.worker,
+                        "channel": letter.channel,
+                        "code": letter.code,
+                        "message": letter.message,
+                        "slot": letter.block.map(|x| x.slot),
+                    })
+                })
+                .collect();
+
+            Ok(serde_json::Value::Array(letters))
+        }
+        "dead_letters.replay" => {
+            let params: ReplayDeadLetterParams =
+                serde_json::from_value(params).map_err(|x| x.to_string())?;
+
+            runtime
+                .replay_dead_letter(params.id)
+                .await
+                .map_err(|x| x.to_string())?;
+
+            Ok(json!({}))
+        }
+        _ => Err(format!("unknown admin method '{method}'")),
+    }
+}
+
+pub async fn handle_admin_request(runtime: Runtime, body: serde_json::Value) -> warp::reply::Json {
+    let request = match parse_request(body) {
+        Ok(x) => x,
+        Err(err) => return warp::reply::json(&err),
+    };
+
+    debug!(
+        id = request.id,
+        method = request.method,
+        "handling admin request"
+    );
+
+    match handle_admin_method(&runtime, &request.method, request.params).await {
+        Ok(x) => warp::reply::json(&x),
+        Err(error) => {
+            error!(
+                id = request.id,
+                method = request.method,
+                "admin request failed"
+            );
+            warp::reply::json(&ErrorResponse { error })
+        }
+    }
+}
+
 pub async fn serve(
     config: Config,
     runtime: Runtime,
     cancel: CancellationToken,
 ) -> Result<(), Error> {
-    let filter = warp::any()
+    let admin_runtime = runtime.clone();
+    let admin = warp::path(ADMIN_PATH)
+        .and(warp::path::end())
+        .map(move || admin_runtime.clone())
+        .and(warp::post())
+        .and(warp::body::json())
+        .then(handle_admin_request);
+
+    let worker = warp::any()
         .map(move || runtime.clone())
         .and(warp::path::param())
         .and(warp::post())
         .and(warp::body::json())
         .then(handle_request);
 
+    let filter = admin.or(worker);
+
     let address: SocketAddr = config
         .listen_address
         .parse()
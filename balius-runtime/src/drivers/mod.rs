@@ -7,5 +7,8 @@
 //! Each of these drivers has a way to trigger a forever-loop that should be
 //! spawn as an independent tokio task running on the background.
 
+pub mod broadcast;
+pub mod catchup;
 pub mod chainsync;
 pub mod jsonrpc;
+pub mod timer;
@@ -0,0 +1,437 @@
+//! Driver to trigger timer events.
+//!
+//! This driver keeps a schedule of every timer channel registered by the
+//! workers loaded in the Runtime and funnels a `timer` event into the
+//! corresponding channel each time an interval elapses. Intervals are
+//! defined by the worker either as a cron expression (eg: `0 15 * * *`) or
+//! as a simple duration (eg: `30s`, `5m`, `1h 30m`).
+//!
+//! The schedule is refreshed periodically from the Runtime, so workers that
+//! are registered or removed while the driver is running are picked up
+//! without restarting it.
+
+use std::{collections::HashMap, str::FromStr, time::Duration};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::select;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, warn};
+
+use crate::{Error, Runtime, WorkerId};
+
+const DEFAULT_REFRESH_INTERVAL_SECS: u64 = 5;
+
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+pub struct Config {
+    /// How often (in seconds) the schedule is refreshed from the Runtime.
+    pub refresh_interval: Option<u64>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Interval {
+    Cron(Box<cron::Schedule>),
+    Every(Duration),
+}
+
+impl Interval {
+    /// Compute the next time the interval fires, strictly after `after`.
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Self::Cron(schedule) => schedule.after(&after).next(),
+            Self::Every(duration) => {
+                let duration = chrono::Duration::from_std(*duration).ok()?;
+                after.checked_add_signed(duration)
+            }
+        }
+    }
+
+    /// Compute the tick following `last` that is strictly after `now`,
+    /// skipping any tick missed in between. Durations are counted from
+    /// `last` so that late ticks don't make the schedule drift.
+    pub fn next_tick(&self, last: DateTime<Utc>, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Self::Cron(_) => self.next_after(now.max(last)),
+            Self::Every(duration) => {
+                let step = chrono::Duration::from_std(*duration).ok()?;
+                let step_ms = step.num_milliseconds().max(1);
+                let late_ms = now.signed_duration_since(last).num_milliseconds().max(0);
+                let steps = i32::try_from(late_ms / step_ms + 1).ok()?;
+                last.checked_add_signed(step.checked_mul(steps)?)
+            }
+        }
+    }
+}
+
+impl FromStr for Interval {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+
+        if let Ok(duration) = humantime::parse_duration(s) {
+            if duration.is_zero() {
+                return Err(Error::Config(format!("timer interval '{s}' is zero")));
+            }
+
+            return Ok(Self::Every(duration));
+        }
+
+        // the cron crate expects a leading seconds field, we also accept the
+        // classic 5-field syntax by assuming the start of the minute.
+        let expr = match s.split_whitespace().count() {
+            5 => format!("0 {s}"),
+            _ => s.to_owned(),
+        };
+
+        cron::Schedule::from_str(&expr)
+            .map(|x| Self::Cron(Box::new(x)))
+            .map_err(|e| Error::Config(format!("invalid timer interval '{s}': {e}")))
+    }
+}
+
+type ScheduleKey = (WorkerId, u32);
+
+struct Entry {
+    raw: String,
+    interval: Interval,
+    next: DateTime<Utc>,
+}
+
+#[derive(Default)]
+struct Schedule {
+    entries: HashMap<ScheduleKey, Entry>,
+}
+
+impl Schedule {
+    /// Sync the schedule with the timer channels currently registered in
+    /// the runtime, keeping the next fire time of entries that didn't
+    /// change.
+    async fn refresh<T>(&mut self, runtime: &T, now: DateTime<Utc>)
+    where
+        T: TimerTarget + Sync,
+    {
+        let channels = runtime.timer_channels().await;
+
+        let mut entries = HashMap::with_capacity(channels.len());
+
+        for (worker, channel, raw) in channels {
+            let key = (worker, channel);
+
+            if let Some(existing) = self.entries.remove(&key) {
+                if existing.raw == raw {
+                    entries.insert(key, existing);
+                    continue;
+                }
+            }
+
+            let interval = match raw.parse::<Interval>() {
+                Ok(x) => x,
+                Err(err) => {
+                    warn!(worker = key.0, channel = key.1, %err, "skipping timer channel");
+                    continue;
+                }
+            };
+
+            let Some(next) = interval.next_after(now) else {
+                warn!(
+                    worker = key.0,
+                    channel = key.1,
+                    "timer interval never fires"
+                );
+                continue;
+            };
+
+            debug!(worker = key.0, channel = key.1, %next, "scheduled timer");
+            entries.insert(
+                key,
+                Entry {
+                    raw,
+                    interval,
+                    next,
+                },
+            );
+        }
+
+        self.entries = entries;
+    }
+
+    fn next_due(&self) -> Option<DateTime<Utc>> {
+        self.entries.values().map(|x| x.next).min()
+    }
+
+    /// How long to sleep before the next iteration. Overdue entries yield
+    /// no wait at all, while an empty schedule waits for the next refresh.
+    fn wait_time(&self, now: DateTime<Utc>, refresh_interval: Duration) -> Duration {
+        match self.next_due() {
+            Some(next) => next
+                .signed_duration_since(now)
+                .to_std()
+                .unwrap_or_default()
+                .min(refresh_interval),
+            None => refresh_interval,
+        }
+    }
+
+    /// Take all entries due at `now`, rescheduling them for their next
+    /// occurrence.
+    fn take_due(&mut self, now: DateTime<Utc>) -> Vec<(ScheduleKey, DateTime<Utc>)> {
+        let mut due = vec![];
+
+        self.entries.retain(|key, entry| {
+            if entry.next > now {
+                return true;
+            }
+
+            due.push((key.clone(), entry.next));
+
+            match entry.interval.next_tick(entry.next, now) {
+                Some(next) => {
+                    entry.next = next;
+                    true
+                }
+                None => false,
+            }
+        });
+
+        due
+    }
+}
+
+/// The runtime-facing side of the driver, kept as a trait so that the
+/// dispatch loop can be exercised without loading actual workers.
+#[async_trait::async_trait]
+trait TimerTarget {
+    async fn timer_channels(&self) -> Vec<(WorkerId, u32, String)>;
+    async fn handle_timer(
+        &self,
+        worker_id: &str,
+        channel: u32,
+        timestamp: u64,
+    ) -> Result<(), Error>;
+}
+
+#[async_trait::async_trait]
+impl TimerTarget for Runtime {
+    async fn timer_channels(&self) -> Vec<(WorkerId, u32, String)> {
+        Runtime::timer_channels(self).await
+    }
+
+    async fn handle_timer(
+        &self,
+        worker_id: &str,
+        channel: u32,
+        timestamp: u64,
+    ) -> Result<(), Error> {
+        Runtime::handle_timer(self, worker_id, channel, timestamp).await
+    }
+}
+
+pub async fn run(config: Config, runtime: Runtime, cancel: CancellationToken) -> Result<(), Error> {
+    drive(config, &runtime, cancel).await
+}
+
+async fn drive<T>(config: Config, runtime: &T, cancel: CancellationToken) -> Result<(), Error>
+where
+    T: TimerTarget + Sync,
+{
+    let refresh_interval = Duration::from_secs(
+        config
+            .refresh_interval
+            .unwrap_or(DEFAULT_REFRESH_INTERVAL_SECS),
+    );
+
+    let mut schedule = Schedule::default();
+    let mut last_refresh = None;
+
+    loop {
+        let now = Utc::now();
+
+        let should_refresh = match last_refresh {
+            Some(x) => {
+                now.signed_duration_since(x).to_std().unwrap_or_default() >= refresh_interval
+            }
+            None => true,
+        };
+
+        if should_refresh {
+            schedule.refresh(runtime, now).await;
+            last_refresh = Some(now);
+        }
+
+        for ((worker, channel), fired_at) in schedule.take_due(now) {
+            let timestamp = fired_at.timestamp_millis() as u64;
+
+            if let Err(err) = runtime.handle_timer(&worker, channel, timestamp).await {
+                warn!(worker, channel, %err, "timer event failed");
+            }
+        }
+
+        let wait = schedule.wait_time(Utc::now(), refresh_interval);
+
+        select! {
+            _ = cancel.cancelled() => {
+                warn!("timer driver cancelled");
+                break Ok(())
+            },
+            _ = tokio::time::sleep(wait) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    fn at(rfc3339: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(rfc3339)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn parses_simple_durations() {
+        let interval: Interval = "5m".parse().unwrap();
+        assert!(matches!(interval, Interval::Every(x) if x == Duration::from_secs(300)));
+
+        let interval: Interval = "1h 30m".parse().unwrap();
+        assert!(matches!(interval, Interval::Every(x) if x == Duration::from_secs(5400)));
+    }
+
+    #[test]
+    fn parses_cron_expressions() {
+        let start = at("2024-01-01T10:00:00Z");
+
+        let interval: Interval = "0 15 * * *".parse().unwrap();
+        let next = interval.next_after(start).unwrap();
+        assert_eq!(next.to_rfc3339(), "2024-01-01T15:00:00+00:00");
+
+        let interval: Interval = "30 */5 * * * *".parse().unwrap();
+        let next = interval.next_after(start).unwrap();
+        assert_eq!(next.to_rfc3339(), "2024-01-01T10:00:30+00:00");
+    }
+
+    #[test]
+    fn rejects_invalid_intervals() {
+        assert!("0s".parse::<Interval>().is_err());
+        assert!("every now and then".parse::<Interval>().is_err());
+    }
+
+    #[test]
+    fn reschedules_due_entries() {
+        let start = Utc::now();
+
+        let mut schedule = Schedule::default();
+        schedule.entries.insert(
+            ("a".to_owned(), 0),
+            Entry {
+                raw: "10s".to_owned(),
+                interval: "10s".parse().unwrap(),
+                next: start,
+            },
+        );
+
+        let due = schedule.take_due(start);
+        assert_eq!(due, vec![(("a".to_owned(), 0), start)]);
+        assert!(schedule.take_due(start).is_empty());
+        assert_eq!(
+            schedule.next_due(),
+            Some(start + chrono::Duration::seconds(10))
+        );
+    }
+
+    #[test]
+    fn reschedules_without_drift() {
+        let start = at("2024-01-01T10:00:00Z");
+
+        let mut schedule = Schedule::default();
+        schedule.entries.insert(
+            ("a".to_owned(), 0),
+            Entry {
+                raw: "5m".to_owned(),
+                interval: "5m".parse().unwrap(),
+                next: start,
+            },
+        );
+
+        // the loop woke up late, the next tick still keeps the cadence
+        let due = schedule.take_due(start + chrono::Duration::seconds(3));
+        assert_eq!(due.len(), 1);
+        assert_eq!(schedule.next_due(), Some(at("2024-01-01T10:05:00Z")));
+
+        // ticks missed entirely are skipped, not fired in a burst
+        let due = schedule.take_due(at("2024-01-01T10:17:00Z"));
+        assert_eq!(due, vec![(("a".to_owned(), 0), at("2024-01-01T10:05:00Z"))]);
+        assert_eq!(schedule.next_due(), Some(at("2024-01-01T10:20:00Z")));
+    }
+
+    #[test]
+    fn empty_schedule_waits_for_refresh() {
+        let refresh = Duration::from_secs(5);
+        let now = Utc::now();
+
+        let mut schedule = Schedule::default();
+        assert_eq!(schedule.wait_time(now, refresh), refresh);
+
+        schedule.entries.insert(
+            ("a".to_owned(), 0),
+            Entry {
+                raw: "1h".to_owned(),
+                interval: "1h".parse().unwrap(),
+                next: now - chrono::Duration::seconds(1),
+            },
+        );
+        assert_eq!(schedule.wait_time(now, refresh), Duration::ZERO);
+
+        schedule.entries.values_mut().for_each(|x| {
+            x.next = now + chrono::Duration::seconds(2);
+        });
+        assert_eq!(schedule.wait_time(now, refresh), Duration::from_secs(2));
+    }
+
+    #[derive(Default)]
+    struct MockTarget {
+        fired: Mutex<Vec<(String, u32, u64)>>,
+    }
+
+    #[async_trait::async_trait]
+    impl TimerTarget for MockTarget {
+        async fn timer_channels(&self) -> Vec<(WorkerId, u32, String)> {
+            vec![("keeper".to_owned(), 3, "100ms".to_owned())]
+        }
+
+        async fn handle_timer(
+            &self,
+            worker_id: &str,
+            channel: u32,
+            timestamp: u64,
+        ) -> Result<(), Error> {
+            self.fired
+                .lock()
+                .unwrap()
+                .push((worker_id.to_owned(), channel, timestamp));
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn dispatches_due_timers() {
+        let target = MockTarget::default();
+        let cancel = CancellationToken::new();
+
+        let stop = cancel.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(450)).await;
+            stop.cancel();
+        });
+
+        drive(Config::default(), &target, cancel).await.unwrap();
+
+        let fired = target.fired.into_inner().unwrap();
+        assert!(fired.len() >= 3, "expected at least 3 ticks, got {fired:?}");
+        assert!(fired.iter().all(|(w, c, _)| w == "keeper" && *c == 3));
+        assert!(fired.windows(2).all(|x| x[1].2 - x[0].2 == 100));
+    }
+}
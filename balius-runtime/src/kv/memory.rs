@@ -3,7 +3,7 @@ use std::collections::BTreeMap;
 
 use crate::wit::balius::app::kv as wit;
 use std::ops::Bound;
-use wit::{KvError, Payload};
+use wit::{KvEntry, KvError, KvPage, Payload};
 
 use super::KvProvider;
 
@@ -41,6 +41,13 @@ impl KvProvider for MemoryKv {
         Ok(())
     }
 
+    async fn delete_value(&mut self, worker_id: &str, key: String) -> Result<(), KvError> {
+        if let Some(map) = self.map.get_mut(worker_id) {
+            map.remove(&key);
+        }
+        Ok(())
+    }
+
     async fn list_values(
         &mut self,
         worker_id: &str,
@@ -62,4 +69,121 @@ impl KvProvider for MemoryKv {
         }
         Ok(result)
     }
+
+    async fn compare_and_swap(
+        &mut self,
+        worker_id: &str,
+        key: String,
+        expected: Option<Payload>,
+        value: Payload,
+    ) -> Result<bool, KvError> {
+        let map = self.map.entry(worker_id.to_string()).or_default();
+
+        if map.get(&key) != expected.as_ref() {
+            return Ok(false);
+        }
+
+        map.insert(key, value);
+        Ok(true)
+    }
+
+    async fn get_many(
+        &mut self,
+        worker_id: &str,
+        keys: Vec<String>,
+    ) -> Result<Vec<Option<Payload>>, KvError> {
+        let map = self.map.entry(worker_id.to_string()).or_default();
+        Ok(keys.iter().map(|key| map.get(key).cloned()).collect())
+    }
+
+    async fn set_many(&mut self, worker_id: &str, entries: Vec<KvEntry>) -> Result<(), KvError> {
+        let map = self.map.entry(worker_id.to_string()).or_default();
+
+        for entry in entries {
+            map.insert(entry.key, entry.value);
+        }
+
+        Ok(())
+    }
+
+    async fn list_entries(
+        &mut self,
+        worker_id: &str,
+        prefix: String,
+        after: Option<String>,
+        limit: u32,
+    ) -> Result<KvPage, KvError> {
+        let start = match after {
+            Some(after) if after >= prefix => Bound::Excluded(after),
+            _ => Bound::Included(prefix.clone()),
+        };
+
+        let mut range = self
+            .map
+            .entry(worker_id.to_string())
+            .or_default()
+            .range((start, Bound::Unbounded))
+            .take_while(|(k, _)| k.starts_with(&prefix));
+
+        let entries = range
+            .by_ref()
+            .take(limit as usize)
+            .map(|(key, value)| KvEntry {
+                key: key.clone(),
+                value: value.clone(),
+            })
+            .collect::<Vec<_>>();
+
+        let next = match range.next() {
+            Some(_) => entries.last().map(|x| x.key.clone()),
+            None => None,
+        };
+
+        Ok(KvPage { entries, next })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn pages_through_entries() {
+        let mut kv = MemoryKv::default();
+
+        for key in ["a1", "a2", "a3", "b1"] {
+            kv.set_value("w", key.into(), key.as_bytes().to_vec())
+                .await
+                .unwrap();
+        }
+
+        let page = kv.list_entries("w", "a".into(), None, 2).await.unwrap();
+        assert_eq!(page.entries.len(), 2);
+        assert_eq!(page.next.as_deref(), Some("a2"));
+
+        let page = kv
+            .list_entries("w", "a".into(), page.next, 2)
+            .await
+            .unwrap();
+        assert_eq!(page.entries.len(), 1);
+        assert_eq!(page.entries[0].key, "a3");
+        assert_eq!(page.next, None);
+
+        assert!(!kv
+            .compare_and_swap("w", "a1".into(), None, vec![0])
+            .await
+            .unwrap());
+        assert!(kv
+            .compare_and_swap("w", "a1".into(), Some(b"a1".to_vec()), vec![0])
+            .await
+            .unwrap());
+
+        kv.delete_value("w", "a1".into()).await.unwrap();
+        assert_eq!(
+            kv.get_many("w", vec!["a1".into(), "b1".into()])
+                .await
+                .unwrap(),
+            vec![None, Some(b"b1".to_vec())]
+        );
+    }
 }
@@ -0,0 +1,252 @@
+//! Ephemeral KV for tests and daemons without a configured KV.
+//!
+//! Values live in memory like [`MemoryKv`], and the operations that reach
+//! the provider can optionally be recorded so tests can assert on them. Keep
+//! in mind that writes made while handling chain events only reach the
+//! provider once the cursor of the worker is committed.
+
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+
+use crate::wit::balius::app::kv as wit;
+use wit::{KvEntry, KvError, KvPage, Payload};
+
+use super::{memory::MemoryKv, KvProvider};
+
+/// An operation received by a [`MockKv`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KvOp {
+    Get {
+        worker: String,
+        key: String,
+    },
+    Set {
+        worker: String,
+        key: String,
+        value: Payload,
+    },
+    Delete {
+        worker: String,
+        key: String,
+    },
+    List {
+        worker: String,
+        prefix: String,
+    },
+    CompareAndSwap {
+        worker: String,
+        key: String,
+        expected: Option<Payload>,
+        value: Payload,
+    },
+    GetMany {
+        worker: String,
+        keys: Vec<String>,
+    },
+    SetMany {
+        worker: String,
+        entries: Vec<(String, Payload)>,
+    },
+    ListEntries {
+        worker: String,
+        prefix: String,
+        after: Option<String>,
+        limit: u32,
+    },
+}
+
+/// Clones share the same values and recorded operations.
+#[derive(Default, Clone)]
+pub struct MockKv {
+    kv: Arc<Mutex<MemoryKv>>,
+    ops: Option<Arc<std::sync::Mutex<Vec<KvOp>>>>,
+}
+
+impl MockKv {
+    /// A mock that records every operation it receives.
+    pub fn recording() -> Self {
+        Self {
+            kv: Default::default(),
+            ops: Some(Default::default()),
+        }
+    }
+
+    /// The operations received so far, oldest first. Always empty if the
+    /// mock isn't recording.
+    pub fn ops(&self) -> Vec<KvOp> {
+        match &self.ops {
+            Some(ops) => ops.lock().unwrap().clone(),
+            None => vec![],
+        }
+    }
+
+    pub fn clear_ops(&self) {
+        if let Some(ops) = &self.ops {
+            ops.lock().unwrap().clear();
+        }
+    }
+
+    fn record(&self, op: impl FnOnce() -> KvOp) {
+        if let Some(ops) = &self.ops {
+            ops.lock().unwrap().push(op());
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl KvProvider for MockKv {
+    async fn get_value(&mut self, worker_id: &str, key: String) -> Result<Payload, KvError> {
+        self.record(|| KvOp::Get {
+            worker: worker_id.to_string(),
+            key: key.clone(),
+        });
+
+        self.kv.lock().await.get_value(worker_id, key).await
+    }
+
+    async fn set_value(
+        &mut self,
+        worker_id: &str,
+        key: String,
+        value: Payload,
+    ) -> Result<(), KvError> {
+        self.record(|| KvOp::Set {
+            worker: worker_id.to_string(),
+            key: key.clone(),
+            value: value.clone(),
+        });
+
+        self.kv.lock().await.set_value(worker_id, key, value).await
+    }
+
+    async fn delete_value(&mut self, worker_id: &str, key: String) -> Result<(), KvError> {
+        self.record(|| KvOp::Delete {
+            worker: worker_id.to_string(),
+            key: key.clone(),
+        });
+
+        self.kv.lock().await.delete_value(worker_id, key).await
+    }
+
+    async fn list_values(
+        &mut self,
+        worker_id: &str,
+        prefix: String,
+    ) -> Result<Vec<String>, KvError> {
+        self.record(|| KvOp::List {
+            worker: worker_id.to_string(),
+            prefix: prefix.clone(),
+        });
+
+        self.kv.lock().await.list_values(worker_id, prefix).await
+    }
+
+    async fn compare_and_swap(
+        &mut self,
+        worker_id: &str,
+        key: String,
+        expected: Option<Payload>,
+        value: Payload,
+    ) -> Result<bool, KvError> {
+        self.record(|| KvOp::CompareAndSwap {
+            worker: worker_id.to_string(),
+            key: key.clone(),
+            expected: expected.clone(),
+            value: value.clone(),
+        });
+
+        self.kv
+            .lock()
+            .await
+            .compare_and_swap(worker_id, key, expected, value)
+            .await
+    }
+
+    async fn get_many(
+        &mut self,
+        worker_id: &str,
+        keys: Vec<String>,
+    ) -> Result<Vec<Option<Payload>>, KvError> {
+        self.record(|| KvOp::GetMany {
+            worker: worker_id.to_string(),
+            keys: keys.clone(),
+        });
+
+        self.kv.lock().await.get_many(worker_id, keys).await
+    }
+
+    async fn set_many(&mut self, worker_id: &str, entries: Vec<KvEntry>) -> Result<(), KvError> {
+        self.record(|| KvOp::SetMany {
+            worker: worker_id.to_string(),
+            entries: entries
+                .iter()
+                .map(|x| (x.key.clone(), x.value.clone()))
+                .collect(),
+        });
+
+        self.kv.lock().await.set_many(worker_id, entries).await
+    }
+
+    async fn list_entries(
+        &mut self,
+        worker_id: &str,
+        prefix: String,
+        after: Option<String>,
+        limit: u32,
+    ) -> Result<KvPage, KvError> {
+        self.record(|| KvOp::ListEntries {
+            worker: worker_id.to_string(),
+            prefix: prefix.clone(),
+            after: after.clone(),
+            limit,
+        });
+
+        self.kv
+            .lock()
+            .await
+            .list_entries(worker_id, prefix, after, limit)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn records_operations() {
+        let mock = MockKv::recording();
+        let mut kv = mock.clone();
+
+        kv.set_value("w", "a".into(), vec![1]).await.unwrap();
+        assert_eq!(kv.get_value("w", "a".into()).await.unwrap(), vec![1]);
+        assert!(kv.get_value("w", "b".into()).await.is_err());
+
+        assert_eq!(
+            mock.ops(),
+            vec![
+                KvOp::Set {
+                    worker: "w".into(),
+                    key: "a".into(),
+                    value: vec![1],
+                },
+                KvOp::Get {
+                    worker: "w".into(),
+                    key: "a".into(),
+                },
+                KvOp::Get {
+                    worker: "w".into(),
+                    key: "b".into(),
+                },
+            ]
+        );
+
+        mock.clear_ops();
+        assert!(mock.ops().is_empty());
+
+        // values survive clearing the ops
+        assert_eq!(kv.get_value("w", "a".into()).await.unwrap(), vec![1]);
+        assert!(MockKv::default().ops().is_empty());
+    }
+}
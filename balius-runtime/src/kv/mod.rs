@@ -1,37 +1,230 @@
 use std::sync::Arc;
 use tokio::sync::{Mutex, RwLock};
 
-use crate::{metrics::Metrics, wit::balius::app::kv as wit, Error};
+use crate::{
+    metrics::Metrics,
+    store::{KvBatch, KvWrite},
+    wit::balius::app::kv as wit,
+    Error,
+};
 
-pub use wit::{Host as CustomKv, KvError, Payload};
+pub use wit::{Host as CustomKv, KvEntry, KvError, KvPage, Payload};
+
+/// Max number of entries in a page of `list-entries`.
+const MAX_PAGE_SIZE: u32 = 1000;
 
 #[derive(Clone)]
 pub enum Kv {
-    Mock,
+    Mock(mock::MockKv),
     Memory(Arc<RwLock<memory::MemoryKv>>),
     Redb(Arc<RwLock<redb::RedbKv>>),
+    Sqlite(Arc<RwLock<sqlite::SqliteKv>>),
     Custom(Arc<Mutex<dyn KvProvider + Send + Sync>>),
 }
 
+impl From<mock::MockKv> for Kv {
+    fn from(kv: mock::MockKv) -> Self {
+        Kv::Mock(kv)
+    }
+}
+
 impl Kv {
     pub async fn into_ephemeral(self) -> Result<Self, Error> {
         match self {
-            Kv::Mock => Ok(self),
+            Kv::Mock(_) => Ok(self),
             Kv::Memory(x) => Ok(Kv::Memory(x)),
             Kv::Redb(x) => Ok(Kv::Redb(Arc::new(RwLock::new(
                 x.write().await.into_ephemeral()?,
             )))),
+            Kv::Sqlite(x) => Ok(Kv::Sqlite(Arc::new(RwLock::new(
+                x.write().await.into_ephemeral()?,
+            )))),
             Kv::Custom(_) => Err(Error::KvError(
                 "Cannot convert custom kv into ephemeral".to_string(),
             )),
         }
     }
+
+    async fn get_value(&self, worker_id: &str, key: String) -> Result<Payload, KvError> {
+        match self {
+            Kv::Mock(kv) => kv.clone().get_value(worker_id, key).await,
+            Kv::Memory(kv) => kv.read().await.clone().get_value(worker_id, key).await,
+            Kv::Redb(kv) => kv.read().await.clone().get_value(worker_id, key).await,
+            Kv::Sqlite(kv) => kv.read().await.clone().get_value(worker_id, key).await,
+            Kv::Custom(kv) => kv.lock().await.get_value(worker_id, key).await,
+        }
+    }
+
+    async fn set_value(&self, worker_id: &str, key: String, value: Payload) -> Result<(), KvError> {
+        match self {
+            Kv::Mock(kv) => kv.clone().set_value(worker_id, key, value).await,
+            Kv::Memory(kv) => kv.write().await.set_value(worker_id, key, value).await,
+            Kv::Redb(kv) => {
+                kv.read()
+                    .await
+                    .clone()
+                    .set_value(worker_id, key, value)
+                    .await
+            }
+            Kv::Sqlite(kv) => {
+                kv.read()
+                    .await
+                    .clone()
+                    .set_value(worker_id, key, value)
+                    .await
+            }
+            Kv::Custom(kv) => kv.lock().await.set_value(worker_id, key, value).await,
+        }
+    }
+
+    async fn delete_value(&self, worker_id: &str, key: String) -> Result<(), KvError> {
+        match self {
+            Kv::Mock(kv) => kv.clone().delete_value(worker_id, key).await,
+            Kv::Memory(kv) => kv.write().await.delete_value(worker_id, key).await,
+            Kv::Redb(kv) => kv.read().await.clone().delete_value(worker_id, key).await,
+            Kv::Sqlite(kv) => kv.read().await.clone().delete_value(worker_id, key).await,
+            Kv::Custom(kv) => kv.lock().await.delete_value(worker_id, key).await,
+        }
+    }
+
+    async fn compare_and_swap(
+        &self,
+        worker_id: &str,
+        key: String,
+        expected: Option<Payload>,
+        value: Payload,
+    ) -> Result<bool, KvError> {
+        match self {
+            Kv::Mock(kv) => {
+                kv.clone()
+                    .compare_and_swap(worker_id, key, expected, value)
+                    .await
+            }
+            Kv::Memory(kv) => {
+                kv.write()
+                    .await
+                    .compare_and_swap(worker_id, key, expected, value)
+                    .await
+            }
+            Kv::Redb(kv) => {
+                kv.read()
+                    .await
+                    .clone()
+                    .compare_and_swap(worker_id, key, expected, value)
+                    .await
+            }
+            Kv::Sqlite(kv) => {
+                kv.read()
+                    .await
+                    .clone()
+                    .compare_and_swap(worker_id, key, expected, value)
+                    .await
+            }
+            Kv::Custom(kv) => {
+                kv.lock()
+                    .await
+                    .compare_and_swap(worker_id, key, expected, value)
+                    .await
+            }
+        }
+    }
+
+    async fn get_many(
+        &self,
+        worker_id: &str,
+        keys: Vec<String>,
+    ) -> Result<Vec<Option<Payload>>, KvError> {
+        match self {
+            Kv::Mock(kv) => kv.clone().get_many(worker_id, keys).await,
+            Kv::Memory(kv) => kv.read().await.clone().get_many(worker_id, keys).await,
+            Kv::Redb(kv) => kv.read().await.clone().get_many(worker_id, keys).await,
+            Kv::Sqlite(kv) => kv.read().await.clone().get_many(worker_id, keys).await,
+            Kv::Custom(kv) => kv.lock().await.get_many(worker_id, keys).await,
+        }
+    }
+
+    async fn set_many(&self, worker_id: &str, entries: Vec<KvEntry>) -> Result<(), KvError> {
+        match self {
+            Kv::Mock(kv) => kv.clone().set_many(worker_id, entries).await,
+            Kv::Memory(kv) => kv.write().await.set_many(worker_id, entries).await,
+            Kv::Redb(kv) => kv.read().await.clone().set_many(worker_id, entries).await,
+            Kv::Sqlite(kv) => kv.read().await.clone().set_many(worker_id, entries).await,
+            Kv::Custom(kv) => kv.lock().await.set_many(worker_id, entries).await,
+        }
+    }
+
+    async fn list_entries(
+        &self,
+        worker_id: &str,
+        prefix: String,
+        after: Option<String>,
+        limit: u32,
+    ) -> Result<KvPage, KvError> {
+        match self {
+            Kv::Mock(kv) => {
+                kv.clone()
+                    .list_entries(worker_id, prefix, after, limit)
+                    .await
+            }
+            Kv::Memory(kv) => {
+                kv.read()
+                    .await
+                    .clone()
+                    .list_entries(worker_id, prefix, after, limit)
+                    .await
+            }
+            Kv::Redb(kv) => {
+                kv.read()
+                    .await
+                    .clone()
+                    .list_entries(worker_id, prefix, after, limit)
+                    .await
+            }
+            Kv::Sqlite(kv) => {
+                kv.read()
+                    .await
+                    .clone()
+                    .list_entries(worker_id, prefix, after, limit)
+                    .await
+            }
+            Kv::Custom(kv) => {
+                kv.lock()
+                    .await
+                    .list_entries(worker_id, prefix, after, limit)
+                    .await
+            }
+        }
+    }
+
+    /// Write a batch of a worker straight into the provider.
+    pub(crate) async fn apply(&self, worker_id: &str, batch: &KvBatch) -> Result<(), KvError> {
+        for write in &batch.writes {
+            match write.value.clone() {
+                Some(value) => self.set_value(worker_id, write.key.clone(), value).await?,
+                None => self.delete_value(worker_id, write.key.clone()).await?,
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn list_values(&self, worker_id: &str, prefix: String) -> Result<Vec<String>, KvError> {
+        match self {
+            Kv::Mock(kv) => kv.clone().list_values(worker_id, prefix).await,
+            Kv::Memory(kv) => kv.read().await.clone().list_values(worker_id, prefix).await,
+            Kv::Redb(kv) => kv.read().await.clone().list_values(worker_id, prefix).await,
+            Kv::Sqlite(kv) => kv.read().await.clone().list_values(worker_id, prefix).await,
+            Kv::Custom(kv) => kv.lock().await.list_values(worker_id, prefix).await,
+        }
+    }
 }
 
 pub struct KvHost {
     worker_id: String,
     provider: Kv,
     metrics: Arc<Metrics>,
+    overlay: Arc<std::sync::Mutex<overlay::Overlay>>,
+    read_only: bool,
 }
 impl KvHost {
     pub fn new(worker_id: &str, provider: &Kv, metrics: &Arc<Metrics>) -> Self {
@@ -39,12 +232,90 @@ impl KvHost {
             worker_id: worker_id.to_string(),
             provider: provider.clone(),
             metrics: metrics.clone(),
+            overlay: Default::default(),
+            read_only: false,
+        }
+    }
+
+    /// Reject every write, eg: for pooled request instances.
+    pub(crate) fn set_read_only(&mut self) {
+        self.read_only = true;
+    }
+
+    fn check_writable(&self) -> Result<(), KvError> {
+        if self.read_only {
+            return Err(KvError::Internal(
+                "kv is read-only in pooled request instances".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// The overlay buffering the writes made while handling chain events.
+    pub(crate) fn overlay(&self) -> Arc<std::sync::Mutex<overlay::Overlay>> {
+        self.overlay.clone()
+    }
+
+    /// Share the overlay of a previous instance of the same worker.
+    pub(crate) fn set_overlay(&mut self, overlay: Arc<std::sync::Mutex<overlay::Overlay>>) {
+        self.overlay = overlay;
+    }
+
+    async fn read(&self, key: String) -> Result<Option<Payload>, KvError> {
+        if let Some(value) = self.overlay.lock().unwrap().get(&key) {
+            return Ok(value);
+        }
+
+        match self.provider.get_value(&self.worker_id, key).await {
+            Ok(value) => Ok(Some(value)),
+            Err(KvError::NotFound(_)) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    async fn write(&self, key: String, value: Option<Payload>) -> Result<(), KvError> {
+        if !self.overlay.lock().unwrap().is_active() {
+            return self
+                .provider
+                .apply(
+                    &self.worker_id,
+                    &KvBatch {
+                        writes: vec![KvWrite { key, value }],
+                    },
+                )
+                .await;
+        }
+
+        let needs_previous = self.overlay.lock().unwrap().needs_previous(&key);
+        if needs_previous {
+            let previous = self.read(key.clone()).await?;
+            self.overlay
+                .lock()
+                .unwrap()
+                .record_previous(key.clone(), previous);
+        }
+
+        self.overlay.lock().unwrap().write(key, value);
+
+        Ok(())
+    }
+
+    /// Revert the writes of a block, through the overlay.
+    pub(crate) async fn revert(&self, undo: &KvBatch) -> Result<(), KvError> {
+        for write in undo.writes.iter() {
+            self.write(write.key.clone(), write.value.clone()).await?;
         }
+
+        Ok(())
     }
 }
 
 pub mod memory;
+pub mod mock;
+pub(crate) mod overlay;
 pub mod redb;
+pub mod sqlite;
 
 #[async_trait::async_trait]
 pub trait KvProvider {
@@ -55,87 +326,220 @@ pub trait KvProvider {
         key: String,
         value: Payload,
     ) -> Result<(), KvError>;
+    /// Remove a key. Removing a missing key isn't an error.
+    async fn delete_value(&mut self, worker_id: &str, key: String) -> Result<(), KvError>;
     async fn list_values(
         &mut self,
         worker_id: &str,
         prefix: String,
     ) -> Result<Vec<String>, KvError>;
+
+    /// Set the key only if its current value is `expected`, `None` meaning
+    /// the key is missing. Returns whether the value was set.
+    async fn compare_and_swap(
+        &mut self,
+        worker_id: &str,
+        key: String,
+        expected: Option<Payload>,
+        value: Payload,
+    ) -> Result<bool, KvError> {
+        let current = match self.get_value(worker_id, key.clone()).await {
+            Ok(x) => Some(x),
+            Err(KvError::NotFound(_)) => None,
+            Err(err) => return Err(err),
+        };
+
+        if current != expected {
+            return Ok(false);
+        }
+
+        self.set_value(worker_id, key, value).await?;
+        Ok(true)
+    }
+
+    async fn get_many(
+        &mut self,
+        worker_id: &str,
+        keys: Vec<String>,
+    ) -> Result<Vec<Option<Payload>>, KvError> {
+        let mut values = Vec::with_capacity(keys.len());
+
+        for key in keys {
+            match self.get_value(worker_id, key).await {
+                Ok(x) => values.push(Some(x)),
+                Err(KvError::NotFound(_)) => values.push(None),
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(values)
+    }
+
+    async fn set_many(&mut self, worker_id: &str, entries: Vec<KvEntry>) -> Result<(), KvError> {
+        for entry in entries {
+            self.set_value(worker_id, entry.key, entry.value).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Up to `limit` entries under `prefix` in key order, starting after the
+    /// `after` key.
+    async fn list_entries(
+        &mut self,
+        worker_id: &str,
+        prefix: String,
+        after: Option<String>,
+        limit: u32,
+    ) -> Result<KvPage, KvError> {
+        let mut keys = self
+            .list_values(worker_id, prefix)
+            .await?
+            .into_iter()
+            .filter(|x| after.as_ref().is_none_or(|after| x > after))
+            .collect::<Vec<_>>();
+        keys.sort();
+
+        let more = keys.len() > limit as usize;
+        keys.truncate(limit as usize);
+
+        let values = self.get_many(worker_id, keys.clone()).await?;
+
+        let entries = keys
+            .into_iter()
+            .zip(values)
+            .filter_map(|(key, value)| value.map(|value| KvEntry { key, value }))
+            .collect::<Vec<_>>();
+
+        let next = match more {
+            true => entries.last().map(|x| x.key.clone()),
+            false => None,
+        };
+
+        Ok(KvPage { entries, next })
+    }
 }
 
 impl wit::Host for KvHost {
     async fn get_value(&mut self, key: String) -> Result<Payload, KvError> {
         self.metrics.kv_get(&self.worker_id);
-        match &mut self.provider {
-            Kv::Mock => todo!(),
-            Kv::Memory(kv) => {
-                kv.read()
-                    .await
-                    .clone()
-                    .get_value(&self.worker_id, key)
-                    .await
-            }
-            Kv::Redb(kv) => {
-                kv.read()
-                    .await
-                    .clone()
-                    .get_value(&self.worker_id, key)
-                    .await
-            }
-            Kv::Custom(kv) => {
-                let mut lock = kv.lock().await;
-                lock.get_value(&self.worker_id, key).await
-            }
-        }
+        self.read(key.clone()).await?.ok_or(KvError::NotFound(key))
     }
 
     async fn set_value(&mut self, key: String, value: Payload) -> Result<(), KvError> {
         self.metrics.kv_set(&self.worker_id);
-        match &mut self.provider {
-            Kv::Mock => todo!(),
-            Kv::Memory(kv) => {
-                kv.write()
-                    .await
-                    .set_value(&self.worker_id, key, value)
-                    .await
-            }
-            Kv::Redb(kv) => {
-                kv.read()
-                    .await
-                    .clone()
-                    .set_value(&self.worker_id, key, value)
-                    .await
-            }
-
-            Kv::Custom(kv) => {
-                let mut lock = kv.lock().await;
-                lock.set_value(&self.worker_id, key, value).await
-            }
-        }
+        self.check_writable()?;
+        self.write(key, Some(value)).await
     }
 
     async fn list_values(&mut self, prefix: String) -> Result<Vec<String>, KvError> {
         self.metrics.kv_list(&self.worker_id);
-        match &mut self.provider {
-            Kv::Mock => todo!(),
-            Kv::Memory(kv) => {
-                kv.read()
-                    .await
-                    .clone()
-                    .list_values(&self.worker_id, prefix)
-                    .await
-            }
-            Kv::Redb(kv) => {
-                kv.read()
-                    .await
-                    .clone()
-                    .list_values(&self.worker_id, prefix)
-                    .await
+        let keys = self
+            .provider
+            .list_values(&self.worker_id, prefix.clone())
+            .await?;
+
+        Ok(self.overlay.lock().unwrap().merge_keys(&prefix, keys))
+    }
+
+    async fn delete_value(&mut self, key: String) -> Result<(), KvError> {
+        self.metrics.kv_delete(&self.worker_id);
+        self.check_writable()?;
+        self.write(key, None).await
+    }
+
+    async fn compare_and_swap(
+        &mut self,
+        key: String,
+        expected: Option<Payload>,
+        value: Payload,
+    ) -> Result<bool, KvError> {
+        self.metrics.kv_compare_and_swap(&self.worker_id);
+        self.check_writable()?;
+
+        if !self.overlay.lock().unwrap().is_active() {
+            return self
+                .provider
+                .compare_and_swap(&self.worker_id, key, expected, value)
+                .await;
+        }
+
+        if self.read(key.clone()).await? != expected {
+            return Ok(false);
+        }
+
+        self.write(key, Some(value)).await?;
+        Ok(true)
+    }
+
+    async fn get_many(&mut self, keys: Vec<String>) -> Result<Vec<Option<Payload>>, KvError> {
+        self.metrics.kv_get(&self.worker_id);
+
+        let mut values = vec![None; keys.len()];
+        let mut missing = vec![];
+
+        {
+            let overlay = self.overlay.lock().unwrap();
+
+            for (i, key) in keys.iter().enumerate() {
+                match overlay.get(key) {
+                    Some(value) => values[i] = value,
+                    None => missing.push(i),
+                }
             }
+        }
 
-            Kv::Custom(kv) => {
-                let mut lock = kv.lock().await;
-                lock.list_values(&self.worker_id, prefix).await
+        if !missing.is_empty() {
+            let fetched = self
+                .provider
+                .get_many(
+                    &self.worker_id,
+                    missing.iter().map(|i| keys[*i].clone()).collect(),
+                )
+                .await?;
+
+            for (i, value) in missing.into_iter().zip(fetched) {
+                values[i] = value;
             }
         }
+
+        Ok(values)
+    }
+
+    async fn set_many(&mut self, entries: Vec<KvEntry>) -> Result<(), KvError> {
+        self.metrics.kv_set(&self.worker_id);
+        self.check_writable()?;
+
+        if !self.overlay.lock().unwrap().is_active() {
+            return self.provider.set_many(&self.worker_id, entries).await;
+        }
+
+        for entry in entries {
+            self.write(entry.key, Some(entry.value)).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn list_entries(
+        &mut self,
+        prefix: String,
+        after: Option<String>,
+        limit: u32,
+    ) -> Result<KvPage, KvError> {
+        self.metrics.kv_list(&self.worker_id);
+
+        let limit = limit.clamp(1, MAX_PAGE_SIZE);
+
+        let page = self
+            .provider
+            .list_entries(&self.worker_id, prefix.clone(), after.clone(), limit)
+            .await?;
+
+        Ok(self
+            .overlay
+            .lock()
+            .unwrap()
+            .merge_page(&prefix, after.as_deref(), limit, page))
     }
 }
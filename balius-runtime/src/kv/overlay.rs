@@ -0,0 +1,208 @@
+//! KV writes of a worker held back until its chain cursor is persisted.
+//!
+//! While a worker handles chain events, its writes land in the overlay
+//! instead of the provider, and reads see them on top of the provider state.
+//! Once the cursor of the worker is committed to the store along with the
+//! writes (see `Runtime::commit_chain_progress`), they are applied to the
+//! provider. Each block also records the previous value of the keys it
+//! wrote, which is what reverts the block when it's rolled back.
+
+use std::collections::BTreeMap;
+
+use crate::store::{KvBatch, KvWrite};
+
+use super::{KvEntry, KvPage, Payload};
+
+#[derive(Default)]
+pub struct Overlay {
+    // set while handling a log entry, writes go to the provider otherwise
+    active: bool,
+    // writes of the log entries handled so far
+    base: BTreeMap<String, Option<Payload>>,
+    // writes of the log entry being handled
+    entry: BTreeMap<String, Option<Payload>>,
+    // value before the current block of every key it wrote
+    block: Option<BTreeMap<String, Option<Payload>>>,
+}
+
+impl Overlay {
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Start buffering the writes of a log entry.
+    pub fn begin_entry(&mut self) {
+        self.active = true;
+        self.entry.clear();
+    }
+
+    /// Stop buffering, keeping the writes of the entry only if it was
+    /// handled successfully.
+    pub fn end_entry(&mut self, keep: bool) {
+        let entry = std::mem::take(&mut self.entry);
+
+        if keep {
+            self.base.extend(entry);
+        }
+
+        self.active = false;
+        self.block = None;
+    }
+
+    /// Start recording the previous value of the keys written.
+    pub fn begin_block(&mut self) {
+        self.block = Some(BTreeMap::new());
+    }
+
+    /// The writes that revert the current block.
+    pub fn end_block(&mut self) -> KvBatch {
+        let previous = self.block.take().unwrap_or_default();
+        to_batch(previous)
+    }
+
+    /// `None` if the key isn't in the overlay, `Some(None)` if it was deleted.
+    pub fn get(&self, key: &str) -> Option<Option<Payload>> {
+        self.entry.get(key).or_else(|| self.base.get(key)).cloned()
+    }
+
+    /// Whether the previous value of the key is needed before writing it.
+    pub fn needs_previous(&self, key: &str) -> bool {
+        self.block.as_ref().is_some_and(|x| !x.contains_key(key))
+    }
+
+    pub fn record_previous(&mut self, key: String, value: Option<Payload>) {
+        if let Some(block) = self.block.as_mut() {
+            block.entry(key).or_insert(value);
+        }
+    }
+
+    pub fn write(&mut self, key: String, value: Option<Payload>) {
+        self.entry.insert(key, value);
+    }
+
+    /// Apply the overlay on top of the keys the provider has under `prefix`.
+    pub fn merge_keys(&self, prefix: &str, keys: Vec<String>) -> Vec<String> {
+        let mut keys = keys
+            .into_iter()
+            .map(|x| (x, ()))
+            .collect::<BTreeMap<_, _>>();
+
+        for (key, value) in self.base.iter().chain(self.entry.iter()) {
+            if !key.starts_with(prefix) {
+                continue;
+            }
+
+            match value {
+                Some(_) => keys.insert(key.clone(), ()),
+                None => keys.remove(key),
+            };
+        }
+
+        keys.into_keys().collect()
+    }
+
+    /// Apply the overlay on top of a page of entries read from the provider.
+    pub fn merge_page(
+        &self,
+        prefix: &str,
+        after: Option<&str>,
+        limit: u32,
+        page: KvPage,
+    ) -> KvPage {
+        // keys past the end of a partial page belong to the next ones
+        let until = page.next;
+
+        let mut entries = page
+            .entries
+            .into_iter()
+            .map(|x| (x.key, x.value))
+            .collect::<BTreeMap<_, _>>();
+
+        for (key, value) in self.base.iter().chain(self.entry.iter()) {
+            let in_page = key.starts_with(prefix)
+                && after.is_none_or(|x| key.as_str() > x)
+                && until.as_deref().is_none_or(|x| key.as_str() <= x);
+
+            if !in_page {
+                continue;
+            }
+
+            match value {
+                Some(value) => entries.insert(key.clone(), value.clone()),
+                None => entries.remove(key),
+            };
+        }
+
+        let limit = limit as usize;
+
+        let next = match entries.len() > limit {
+            true => entries.keys().nth(limit - 1).cloned(),
+            false => until,
+        };
+
+        KvPage {
+            entries: entries
+                .into_iter()
+                .take(limit)
+                .map(|(key, value)| KvEntry { key, value })
+                .collect(),
+            next,
+        }
+    }
+
+    /// Take the writes buffered so far, to be persisted and then applied to
+    /// the provider.
+    pub fn take_writes(&mut self) -> KvBatch {
+        to_batch(std::mem::take(&mut self.base))
+    }
+}
+
+fn to_batch(values: BTreeMap<String, Option<Payload>>) -> KvBatch {
+    KvBatch {
+        writes: values
+            .into_iter()
+            .map(|(key, value)| KvWrite { key, value })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_writes_of_handled_entries() {
+        let mut overlay = Overlay::default();
+
+        overlay.begin_entry();
+        overlay.begin_block();
+        assert!(overlay.needs_previous("a"));
+        overlay.record_previous("a".into(), None);
+        overlay.write("a".into(), Some(vec![1]));
+        assert!(!overlay.needs_previous("a"));
+        overlay.write("a".into(), Some(vec![2]));
+
+        let undo = overlay.end_block();
+        assert_eq!(undo.writes.len(), 1);
+        assert_eq!(undo.writes[0].value, None);
+        overlay.end_entry(true);
+
+        // a failed entry leaves no trace
+        overlay.begin_entry();
+        overlay.write("a".into(), None);
+        overlay.write("b".into(), Some(vec![3]));
+        assert_eq!(overlay.get("a"), Some(None));
+        overlay.end_entry(false);
+
+        assert_eq!(overlay.get("a"), Some(Some(vec![2])));
+        assert_eq!(overlay.get("b"), None);
+        assert_eq!(
+            overlay.merge_keys("", vec!["c".into()]),
+            vec!["a".to_string(), "c".to_string()]
+        );
+
+        let writes = overlay.take_writes();
+        assert_eq!(writes.writes.len(), 1);
+        assert_eq!(overlay.get("a"), None);
+    }
+}
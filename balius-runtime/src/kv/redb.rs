@@ -4,7 +4,7 @@ use std::{path::Path, sync::Arc};
 use crate::wit::balius::app::kv as wit;
 use redb::{Database, Durability, ReadableTable, TableDefinition};
 use tracing::warn;
-use wit::{KvError, Payload};
+use wit::{KvEntry, KvError, KvPage, Payload};
 
 use super::KvProvider;
 use crate::Error;
@@ -118,6 +118,28 @@ impl KvProvider for RedbKv {
         Ok(())
     }
 
+    async fn delete_value(&mut self, worker_id: &str, key: String) -> Result<(), KvError> {
+        let wx = self
+            .db
+            .begin_write()
+            .map_err(|err| KvError::Internal(err.to_string()))?;
+
+        {
+            let mut table = wx
+                .open_table(Self::DEF)
+                .map_err(|err| KvError::Internal(err.to_string()))?;
+
+            table
+                .remove(Self::key_for_worker(worker_id, &key))
+                .map_err(|err| KvError::Internal(err.to_string()))?;
+        }
+
+        wx.commit()
+            .map_err(|err| KvError::Internal(err.to_string()))?;
+
+        Ok(())
+    }
+
     async fn list_values(
         &mut self,
         worker_id: &str,
@@ -137,16 +159,202 @@ impl KvProvider for RedbKv {
             .range(Self::key_for_worker(worker_id, &prefix)..)
             .map_err(|err| KvError::Internal(err.to_string()))?;
 
+        // keys are listed without the worker prefix, as the worker wrote them
+        let worker_prefix = Self::key_for_worker(worker_id, "");
+
         for item in range {
             let (k, _) = item.unwrap();
             if k.value()
                 .starts_with(&Self::key_for_worker(worker_id, &prefix))
             {
-                result.push(k.value());
+                result.push(k.value()[worker_prefix.len()..].to_string());
             } else {
                 break;
             }
         }
         Ok(result)
     }
+
+    async fn compare_and_swap(
+        &mut self,
+        worker_id: &str,
+        key: String,
+        expected: Option<Payload>,
+        value: Payload,
+    ) -> Result<bool, KvError> {
+        let wx = self
+            .db
+            .begin_write()
+            .map_err(|err| KvError::Internal(err.to_string()))?;
+
+        let swapped = {
+            let mut table = wx
+                .open_table(Self::DEF)
+                .map_err(|err| KvError::Internal(err.to_string()))?;
+
+            let full_key = Self::key_for_worker(worker_id, &key);
+
+            let current = table
+                .get(full_key.clone())
+                .map_err(|err| KvError::Internal(err.to_string()))?
+                .map(|x| x.value());
+
+            if current == expected {
+                table
+                    .insert(full_key, value)
+                    .map_err(|err| KvError::Internal(err.to_string()))?;
+            }
+
+            current == expected
+        };
+
+        wx.commit()
+            .map_err(|err| KvError::Internal(err.to_string()))?;
+
+        Ok(swapped)
+    }
+
+    async fn get_many(
+        &mut self,
+        worker_id: &str,
+        keys: Vec<String>,
+    ) -> Result<Vec<Option<Payload>>, KvError> {
+        let rx = self
+            .db
+            .begin_read()
+            .map_err(|err| KvError::Internal(err.to_string()))?;
+
+        let table = rx
+            .open_table(Self::DEF)
+            .map_err(|err| KvError::Internal(err.to_string()))?;
+
+        keys.iter()
+            .map(|key| {
+                let value = table
+                    .get(Self::key_for_worker(worker_id, key))
+                    .map_err(|err| KvError::Internal(err.to_string()))?;
+
+                Ok(value.map(|x| x.value()))
+            })
+            .collect()
+    }
+
+    async fn set_many(&mut self, worker_id: &str, entries: Vec<KvEntry>) -> Result<(), KvError> {
+        let wx = self
+            .db
+            .begin_write()
+            .map_err(|err| KvError::Internal(err.to_string()))?;
+
+        {
+            let mut table = wx
+                .open_table(Self::DEF)
+                .map_err(|err| KvError::Internal(err.to_string()))?;
+
+            for entry in entries {
+                table
+                    .insert(Self::key_for_worker(worker_id, &entry.key), entry.value)
+                    .map_err(|err| KvError::Internal(err.to_string()))?;
+            }
+        }
+
+        wx.commit()
+            .map_err(|err| KvError::Internal(err.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn list_entries(
+        &mut self,
+        worker_id: &str,
+        prefix: String,
+        after: Option<String>,
+        limit: u32,
+    ) -> Result<KvPage, KvError> {
+        let rx = self
+            .db
+            .begin_read()
+            .map_err(|err| KvError::Internal(err.to_string()))?;
+
+        let table = rx
+            .open_table(Self::DEF)
+            .map_err(|err| KvError::Internal(err.to_string()))?;
+
+        let worker_prefix = Self::key_for_worker(worker_id, "");
+        let full_prefix = Self::key_for_worker(worker_id, &prefix);
+        let after = after
+            .filter(|x| *x >= prefix)
+            .map(|x| Self::key_for_worker(worker_id, &x));
+
+        let start = after.clone().unwrap_or(full_prefix.clone());
+        let range = table
+            .range(start..)
+            .map_err(|err| KvError::Internal(err.to_string()))?;
+
+        let mut entries = vec![];
+        let mut more = false;
+
+        for item in range {
+            let (k, v) = item.map_err(|err| KvError::Internal(err.to_string()))?;
+            let key = k.value();
+
+            if after.as_ref() == Some(&key) {
+                continue;
+            }
+
+            if !key.starts_with(&full_prefix) {
+                break;
+            }
+
+            if entries.len() == limit as usize {
+                more = true;
+                break;
+            }
+
+            entries.push(KvEntry {
+                key: key[worker_prefix.len()..].to_string(),
+                value: v.value(),
+            });
+        }
+
+        let next = match more {
+            true => entries.last().map(|x| x.key.clone()),
+            false => None,
+        };
+
+        Ok(KvPage { entries, next })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn in_memory() -> RedbKv {
+        let db = Database::builder()
+            .create_with_backend(redb::backends::InMemoryBackend::new())
+            .unwrap();
+
+        RedbKv { db: Arc::new(db) }
+    }
+
+    #[tokio::test]
+    async fn lists_keys_as_written_by_the_worker() {
+        let mut kv = in_memory();
+
+        for key in ["a1", "a2", "b1"] {
+            kv.set_value("w", key.into(), vec![]).await.unwrap();
+        }
+
+        // other workers don't show up, even sharing a prefix
+        kv.set_value("w-x", "a0".into(), vec![]).await.unwrap();
+
+        assert_eq!(
+            kv.list_values("w", "a".into()).await.unwrap(),
+            vec!["a1", "a2"]
+        );
+
+        kv.delete_value("w", "a1".into()).await.unwrap();
+        kv.delete_value("w", "missing".into()).await.unwrap();
+        assert_eq!(kv.list_values("w", "a".into()).await.unwrap(), vec!["a2"]);
+    }
 }
@@ -0,0 +1,318 @@
+//! SQLite implementation of KV, with a row per worker and key.
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use crate::wit::balius::app::kv as wit;
+use rusqlite::{params, Connection, OptionalExtension as _, TransactionBehavior};
+use wit::{KvEntry, KvError, KvPage, Payload};
+
+use super::KvProvider;
+use crate::{
+    store::sqlite::{copy_to_memory, migrate},
+    Error,
+};
+
+const MIGRATIONS: &[&str] = &[r#"
+    CREATE TABLE kv (
+        worker TEXT NOT NULL,
+        key TEXT NOT NULL,
+        value BLOB NOT NULL,
+        PRIMARY KEY (worker, key)
+    ) WITHOUT ROWID;
+"#];
+
+fn internal(err: rusqlite::Error) -> KvError {
+    KvError::Internal(err.to_string())
+}
+
+#[derive(Clone)]
+pub struct SqliteKv {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SqliteKv {
+    pub fn try_new(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let conn = Connection::open(path).map_err(|err| Error::KvError(err.to_string()))?;
+
+        conn.pragma_update(None, "journal_mode", "WAL")
+            .map_err(|err| Error::KvError(err.to_string()))?;
+
+        Self::from_connection(conn)
+    }
+
+    pub fn in_memory() -> Result<Self, Error> {
+        let conn = Connection::open_in_memory().map_err(|err| Error::KvError(err.to_string()))?;
+
+        Self::from_connection(conn)
+    }
+
+    fn from_connection(mut conn: Connection) -> Result<Self, Error> {
+        migrate(&mut conn, MIGRATIONS).map_err(Error::KvError)?;
+
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+        })
+    }
+
+    pub fn into_ephemeral(&mut self) -> Result<Self, Error> {
+        let conn = copy_to_memory(&self.conn.lock().unwrap())
+            .map_err(|err| Error::KvError(err.to_string()))?;
+
+        Self::from_connection(conn)
+    }
+}
+
+#[async_trait::async_trait]
+impl KvProvider for SqliteKv {
+    async fn get_value(&mut self, worker_id: &str, key: String) -> Result<Payload, KvError> {
+        let conn = self.conn.lock().unwrap();
+
+        let value = conn
+            .query_row(
+                "SELECT value FROM kv WHERE worker = ?1 AND key = ?2",
+                params![worker_id, key],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(internal)?;
+
+        value.ok_or(KvError::NotFound(key))
+    }
+
+    async fn set_value(
+        &mut self,
+        worker_id: &str,
+        key: String,
+        value: Payload,
+    ) -> Result<(), KvError> {
+        self.conn
+            .lock()
+            .unwrap()
+            .execute(
+                "INSERT OR REPLACE INTO kv (worker, key, value) VALUES (?1, ?2, ?3)",
+                params![worker_id, key, value],
+            )
+            .map_err(internal)?;
+
+        Ok(())
+    }
+
+    async fn delete_value(&mut self, worker_id: &str, key: String) -> Result<(), KvError> {
+        self.conn
+            .lock()
+            .unwrap()
+            .execute(
+                "DELETE FROM kv WHERE worker = ?1 AND key = ?2",
+                params![worker_id, key],
+            )
+            .map_err(internal)?;
+
+        Ok(())
+    }
+
+    async fn list_values(
+        &mut self,
+        worker_id: &str,
+        prefix: String,
+    ) -> Result<Vec<String>, KvError> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare("SELECT key FROM kv WHERE worker = ?1 AND key >= ?2 ORDER BY key")
+            .map_err(internal)?;
+
+        let mut rows = stmt.query(params![worker_id, prefix]).map_err(internal)?;
+
+        let mut result = vec![];
+        while let Some(row) = rows.next().map_err(internal)? {
+            let key: String = row.get(0).map_err(internal)?;
+
+            // Sorted, if prefix doesn't match then we break
+            if !key.starts_with(&prefix) {
+                break;
+            }
+
+            result.push(key);
+        }
+
+        Ok(result)
+    }
+
+    async fn compare_and_swap(
+        &mut self,
+        worker_id: &str,
+        key: String,
+        expected: Option<Payload>,
+        value: Payload,
+    ) -> Result<bool, KvError> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn
+            .transaction_with_behavior(TransactionBehavior::Immediate)
+            .map_err(internal)?;
+
+        let current: Option<Payload> = tx
+            .query_row(
+                "SELECT value FROM kv WHERE worker = ?1 AND key = ?2",
+                params![worker_id, key],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(internal)?;
+
+        if current != expected {
+            return Ok(false);
+        }
+
+        tx.execute(
+            "INSERT OR REPLACE INTO kv (worker, key, value) VALUES (?1, ?2, ?3)",
+            params![worker_id, key, value],
+        )
+        .map_err(internal)?;
+
+        tx.commit().map_err(internal)?;
+
+        Ok(true)
+    }
+
+    async fn get_many(
+        &mut self,
+        worker_id: &str,
+        keys: Vec<String>,
+    ) -> Result<Vec<Option<Payload>>, KvError> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare_cached("SELECT value FROM kv WHERE worker = ?1 AND key = ?2")
+            .map_err(internal)?;
+
+        keys.iter()
+            .map(|key| {
+                stmt.query_row(params![worker_id, key], |row| row.get(0))
+                    .optional()
+                    .map_err(internal)
+            })
+            .collect()
+    }
+
+    async fn set_many(&mut self, worker_id: &str, entries: Vec<KvEntry>) -> Result<(), KvError> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction().map_err(internal)?;
+
+        for entry in entries {
+            tx.execute(
+                "INSERT OR REPLACE INTO kv (worker, key, value) VALUES (?1, ?2, ?3)",
+                params![worker_id, entry.key, entry.value],
+            )
+            .map_err(internal)?;
+        }
+
+        tx.commit().map_err(internal)
+    }
+
+    async fn list_entries(
+        &mut self,
+        worker_id: &str,
+        prefix: String,
+        after: Option<String>,
+        limit: u32,
+    ) -> Result<KvPage, KvError> {
+        let (start, inclusive) = match after {
+            Some(after) if after >= prefix => (after, false),
+            _ => (prefix.clone(), true),
+        };
+
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare(
+                "SELECT key, value FROM kv
+                WHERE worker = ?1 AND (key > ?2 OR (?3 AND key = ?2))
+                ORDER BY key",
+            )
+            .map_err(internal)?;
+
+        let mut range = stmt
+            .query_map(params![worker_id, start, inclusive], |row| {
+                Ok(KvEntry {
+                    key: row.get(0)?,
+                    value: row.get(1)?,
+                })
+            })
+            .map_err(internal)?
+            .take_while(|x| x.as_ref().map_or(true, |x| x.key.starts_with(&prefix)));
+
+        let entries = range
+            .by_ref()
+            .take(limit as usize)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(internal)?;
+
+        let next = match range.next().transpose().map_err(internal)? {
+            Some(_) => entries.last().map(|x| x.key.clone()),
+            None => None,
+        };
+
+        Ok(KvPage { entries, next })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn pages_through_entries() {
+        let mut kv = SqliteKv::in_memory().unwrap();
+
+        for key in ["a1", "a2", "a3", "b1"] {
+            kv.set_value("w", key.into(), key.as_bytes().to_vec())
+                .await
+                .unwrap();
+        }
+
+        // other workers don't show up
+        kv.set_value("v", "a0".into(), vec![]).await.unwrap();
+
+        assert_eq!(
+            kv.list_values("w", "a".into()).await.unwrap(),
+            vec!["a1", "a2", "a3"]
+        );
+
+        let page = kv.list_entries("w", "a".into(), None, 2).await.unwrap();
+        assert_eq!(page.entries.len(), 2);
+        assert_eq!(page.entries[0].key, "a1");
+        assert_eq!(page.next.as_deref(), Some("a2"));
+
+        let page = kv
+            .list_entries("w", "a".into(), page.next, 2)
+            .await
+            .unwrap();
+        assert_eq!(page.entries.len(), 1);
+        assert_eq!(page.entries[0].key, "a3");
+        assert_eq!(page.next, None);
+
+        assert!(!kv
+            .compare_and_swap("w", "a1".into(), None, vec![0])
+            .await
+            .unwrap());
+        assert!(kv
+            .compare_and_swap("w", "a1".into(), Some(b"a1".to_vec()), vec![0])
+            .await
+            .unwrap());
+        assert_eq!(kv.get_value("w", "a1".into()).await.unwrap(), vec![0]);
+
+        kv.delete_value("w", "a1".into()).await.unwrap();
+        assert_eq!(
+            kv.get_many("w", vec!["a1".into(), "b1".into()])
+                .await
+                .unwrap(),
+            vec![None, Some(b"b1".to_vec())]
+        );
+
+        let mut copy = kv.into_ephemeral().unwrap();
+        assert!(matches!(
+            copy.get_value("w", "a1".into()).await,
+            Err(KvError::NotFound(_))
+        ));
+        assert_eq!(copy.get_value("w", "b1".into()).await.unwrap(), b"b1");
+    }
+}
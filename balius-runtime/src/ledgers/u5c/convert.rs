@@ -46,6 +46,17 @@ impl From<prost::DecodeError> for ConvertError {
     }
 }
 
+fn unwrap_i64(b: Option<&upstream::BigInt>) -> Result<i64, ConvertError> {
+    match b.and_then(|x| x.big_int.as_ref()) {
+        None => Ok(0),
+        Some(upstream::big_int::BigInt::Int(v)) => Ok(*v),
+        Some(upstream::big_int::BigInt::BigUInt(bytes)) => {
+            i64::try_from(big_uint_to_u64(bytes)?).map_err(|_| ConvertError::Overflow)
+        }
+        _ => Err(ConvertError::Overflow),
+    }
+}
+
 fn unwrap_u64(b: Option<&upstream::BigInt>) -> Result<u64, ConvertError> {
     match b.and_then(|x| x.big_int.as_ref()) {
         None => Ok(0),
@@ -85,8 +96,8 @@ where
 
 /// Re-encodes a wire-stable type via prost roundtrip into its legacy
 /// counterpart. Only safe when the upstream and legacy types share an
-/// identical wire format — used here for `Datum` and `WitnessSet`,
-/// which didn't change between 0.17 and 0.18.
+/// identical wire format — used here for `Datum`, `WitnessSet` and
+/// `AuxData`, which didn't change between 0.17 and 0.18.
 fn roundtrip<U, L>(u: &U) -> Result<L, ConvertError>
 where
     U: prost::Message,
@@ -105,13 +116,15 @@ where
 }
 
 pub fn convert_asset(a: upstream::Asset) -> Result<legacy::Asset, ConvertError> {
-    let output_coin = match a.quantity {
-        Some(upstream::asset::Quantity::OutputCoin(b)) => unwrap_u64(Some(&b))?,
-        _ => 0,
+    let (output_coin, mint_coin) = match a.quantity {
+        Some(upstream::asset::Quantity::OutputCoin(b)) => (unwrap_u64(Some(&b))?, 0),
+        Some(upstream::asset::Quantity::MintCoin(b)) => (0, unwrap_i64(Some(&b))?),
+        None => (0, 0),
     };
     Ok(legacy::Asset {
         name: a.name,
         output_coin,
+        mint_coin,
     })
 }
 
@@ -139,13 +152,124 @@ pub fn convert_tx_input(i: upstream::TxInput) -> Result<legacy::TxInput, Convert
     })
 }
 
+/// Certificates only differ from 0.17 in their coin fields, every nested
+/// credential / anchor / relay type is wire-stable and goes through
+/// `roundtrip`. Deprecated kinds (genesis delegation, MIR) are dropped,
+/// leaving an empty certificate so indices still match the Tx.
+pub fn convert_certificate(c: upstream::Certificate) -> Result<legacy::Certificate, ConvertError> {
+    use legacy::certificate::Certificate as L;
+    use upstream::certificate::Certificate as U;
+
+    let certificate = match c.certificate {
+        Some(U::StakeRegistration(x)) => Some(L::StakeRegistration(roundtrip(&x)?)),
+        Some(U::StakeDeregistration(x)) => Some(L::StakeDeregistration(roundtrip(&x)?)),
+        Some(U::StakeDelegation(x)) => Some(L::StakeDelegation(roundtrip(&x)?)),
+        Some(U::PoolRegistration(x)) => Some(L::PoolRegistration(legacy::PoolRegistrationCert {
+            operator: x.operator,
+            vrf_keyhash: x.vrf_keyhash,
+            pledge: unwrap_u64(x.pledge.as_ref())?,
+            cost: unwrap_u64(x.cost.as_ref())?,
+            margin: roundtrip_opt(&x.margin)?,
+            reward_account: x.reward_account,
+            pool_owners: x.pool_owners,
+            relays: try_map(x.relays, |r| roundtrip(&r))?,
+            pool_metadata: roundtrip_opt(&x.pool_metadata)?,
+        })),
+        Some(U::PoolRetirement(x)) => Some(L::PoolRetirement(roundtrip(&x)?)),
+        Some(U::GenesisKeyDelegation(_)) | Some(U::MirCert(_)) => None,
+        Some(U::RegCert(x)) => Some(L::RegCert(legacy::RegCert {
+            stake_credential: roundtrip_opt(&x.stake_credential)?,
+            coin: unwrap_u64(x.coin.as_ref())?,
+        })),
+        Some(U::UnregCert(x)) => Some(L::UnregCert(legacy::UnRegCert {
+            stake_credential: roundtrip_opt(&x.stake_credential)?,
+            coin: unwrap_u64(x.coin.as_ref())?,
+        })),
+        Some(U::VoteDelegCert(x)) => Some(L::VoteDelegCert(roundtrip(&x)?)),
+        Some(U::StakeVoteDelegCert(x)) => Some(L::StakeVoteDelegCert(roundtrip(&x)?)),
+        Some(U::StakeRegDelegCert(x)) => Some(L::StakeRegDelegCert(legacy::StakeRegDelegCert {
+            stake_credential: roundtrip_opt(&x.stake_credential)?,
+            pool_keyhash: x.pool_keyhash,
+            coin: unwrap_u64(x.coin.as_ref())?,
+        })),
+        Some(U::VoteRegDelegCert(x)) => Some(L::VoteRegDelegCert(legacy::VoteRegDelegCert {
+            stake_credential: roundtrip_opt(&x.stake_credential)?,
+            drep: roundtrip_opt(&x.drep)?,
+            coin: unwrap_u64(x.coin.as_ref())?,
+        })),
+        Some(U::StakeVoteRegDelegCert(x)) => {
+            Some(L::StakeVoteRegDelegCert(legacy::StakeVoteRegDelegCert {
+                stake_credential: roundtrip_opt(&x.stake_credential)?,
+                pool_keyhash: x.pool_keyhash,
+                drep: roundtrip_opt(&x.drep)?,
+                coin: unwrap_u64(x.coin.as_ref())?,
+            }))
+        }
+        Some(U::AuthCommitteeHotCert(x)) => Some(L::AuthCommitteeHotCert(roundtrip(&x)?)),
+        Some(U::ResignCommitteeColdCert(x)) => Some(L::ResignCommitteeColdCert(roundtrip(&x)?)),
+        Some(U::RegDrepCert(x)) => Some(L::RegDrepCert(legacy::RegDRepCert {
+            drep_credential: roundtrip_opt(&x.drep_credential)?,
+            coin: unwrap_u64(x.coin.as_ref())?,
+            anchor: roundtrip_opt(&x.anchor)?,
+        })),
+        Some(U::UnregDrepCert(x)) => Some(L::UnregDrepCert(legacy::UnRegDRepCert {
+            drep_credential: roundtrip_opt(&x.drep_credential)?,
+            coin: unwrap_u64(x.coin.as_ref())?,
+        })),
+        Some(U::UpdateDrepCert(x)) => Some(L::UpdateDrepCert(roundtrip(&x)?)),
+        None => None,
+    };
+
+    Ok(legacy::Certificate { certificate })
+}
+
+/// Treasury withdrawals are the only governance action with a coin field,
+/// every other one is wire-stable apart from the parameter update of a
+/// parameter change action, which the legacy schema drops on decode.
+pub fn convert_proposal(
+    p: upstream::GovernanceActionProposal,
+) -> Result<legacy::GovernanceActionProposal, ConvertError> {
+    use legacy::governance_action::GovernanceAction as L;
+    use upstream::governance_action::GovernanceAction as U;
+
+    let gov_action = match p.gov_action {
+        Some(upstream::GovernanceAction {
+            governance_action: Some(U::TreasuryWithdrawalsAction(x)),
+        }) => Some(legacy::GovernanceAction {
+            governance_action: Some(L::TreasuryWithdrawalsAction(
+                legacy::TreasuryWithdrawalsAction {
+                    withdrawals: try_map(x.withdrawals, |w| {
+                        Ok(legacy::WithdrawalAmount {
+                            reward_account: w.reward_account,
+                            coin: unwrap_u64(w.coin.as_ref())?,
+                        })
+                    })?,
+                    policy_hash: x.policy_hash,
+                },
+            )),
+        }),
+        other => roundtrip_opt(&other)?,
+    };
+
+    Ok(legacy::GovernanceActionProposal {
+        deposit: unwrap_u64(p.deposit.as_ref())?,
+        reward_account: p.reward_account,
+        gov_action,
+        anchor: roundtrip_opt(&p.anchor)?,
+    })
+}
+
 pub fn convert_tx(t: upstream::Tx) -> Result<legacy::Tx, ConvertError> {
     Ok(legacy::Tx {
         inputs: try_map(t.inputs, convert_tx_input)?,
         outputs: try_map(t.outputs, convert_tx_output)?,
+        certificates: try_map(t.certificates, convert_certificate)?,
+        mint: try_map(t.mint, convert_multiasset)?,
         witnesses: roundtrip_opt(&t.witnesses)?,
         fee: unwrap_u64(t.fee.as_ref())?,
+        auxiliary: roundtrip_opt(&t.auxiliary)?,
         hash: t.hash,
+        proposals: try_map(t.proposals, convert_proposal)?,
     })
 }
 
@@ -246,6 +370,7 @@ mod tests {
     //!    `utxorpc-spec 0.17` prost.
     //!  - JSON produced by `pparams_to_legacy_json` decodes unchanged
     //!    under `utxorpc-spec 0.17` pbjson.
+    //!
     //! Together these guarantee that pre-BigInt workers keep working.
 
     use super::*;
@@ -475,6 +600,169 @@ mod tests {
         }
     }
 
+    #[test]
+    fn convert_tx_mint_decodes_under_017() {
+        let upstream = v18::Tx {
+            mint: vec![v18::Multiasset {
+                policy_id: vec![0xBB; 28].into(),
+                assets: vec![
+                    v18::Asset {
+                        name: b"minted".to_vec().into(),
+                        quantity: Some(v18::asset::Quantity::MintCoin(big_int(10))),
+                    },
+                    v18::Asset {
+                        name: b"burnt".to_vec().into(),
+                        quantity: Some(v18::asset::Quantity::MintCoin(big_int(-2))),
+                    },
+                ],
+                redeemer: None,
+            }],
+            ..Default::default()
+        };
+        let bal = convert_tx(upstream).expect("convert");
+        let bytes = bal.encode_to_vec();
+        let decoded = v17::Tx::decode(bytes.as_slice()).expect("0.17.0 decode");
+        let assets = &decoded.mint[0].assets;
+        assert_eq!(decoded.mint[0].policy_id.to_vec(), vec![0xBB; 28]);
+        assert_eq!((assets[0].mint_coin, assets[0].output_coin), (10, 0));
+        assert_eq!(assets[1].mint_coin, -2);
+    }
+
+    #[test]
+    fn convert_certificates_decode_under_017() {
+        let credential = v18::StakeCredential {
+            stake_credential: Some(v18::stake_credential::StakeCredential::AddrKeyHash(
+                vec![0x11; 28].into(),
+            )),
+        };
+        let upstream = v18::Tx {
+            certificates: vec![
+                v18::Certificate {
+                    certificate: Some(v18::certificate::Certificate::StakeRegDelegCert(
+                        v18::StakeRegDelegCert {
+                            stake_credential: Some(credential.clone()),
+                            pool_keyhash: vec![0x22; 28].into(),
+                            coin: Some(big_int(2_000_000)),
+                        },
+                    )),
+                    redeemer: None,
+                },
+                v18::Certificate {
+                    certificate: Some(v18::certificate::Certificate::MirCert(Default::default())),
+                    redeemer: None,
+                },
+            ],
+            ..Default::default()
+        };
+        let bal = convert_tx(upstream).expect("convert");
+        assert_eq!(bal.certificates.len(), 2);
+        assert!(bal.certificates[1].certificate.is_none());
+
+        let bytes = bal.encode_to_vec();
+        let decoded = v17::Tx::decode(bytes.as_slice()).expect("0.17.0 decode");
+        match decoded.certificates[0].certificate.as_ref() {
+            Some(v17::certificate::Certificate::StakeRegDelegCert(cert)) => {
+                assert_eq!(cert.pool_keyhash.to_vec(), vec![0x22; 28]);
+                assert_eq!(cert.coin, 2_000_000);
+                assert!(cert.stake_credential.is_some());
+            }
+            other => panic!("unexpected certificate: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn convert_proposals_decode_under_017() {
+        use v18::governance_action::GovernanceAction as G;
+
+        let proposal = |action: G| v18::GovernanceActionProposal {
+            deposit: Some(big_int(100_000_000_000)),
+            reward_account: vec![0xE1; 29].into(),
+            gov_action: Some(v18::GovernanceAction {
+                governance_action: Some(action),
+            }),
+            anchor: None,
+        };
+        let upstream = v18::Tx {
+            proposals: vec![
+                proposal(G::TreasuryWithdrawalsAction(
+                    v18::TreasuryWithdrawalsAction {
+                        withdrawals: vec![v18::WithdrawalAmount {
+                            reward_account: vec![0xE1; 29].into(),
+                            coin: Some(big_int(5_000_000)),
+                        }],
+                        policy_hash: vec![].into(),
+                    },
+                )),
+                proposal(G::ParameterChangeAction(v18::ParameterChangeAction {
+                    gov_action_id: None,
+                    protocol_param_update: Some(v18::PParams {
+                        max_tx_size: 16384,
+                        ..Default::default()
+                    }),
+                    policy_hash: vec![0x44; 28].into(),
+                })),
+            ],
+            ..Default::default()
+        };
+        let bal = convert_tx(upstream).expect("convert");
+        let bytes = bal.encode_to_vec();
+        let decoded = v17::Tx::decode(bytes.as_slice()).expect("0.17.0 decode");
+        assert_eq!(decoded.proposals[0].deposit, 100_000_000_000);
+
+        let action = |i: usize| {
+            decoded.proposals[i]
+                .gov_action
+                .as_ref()
+                .and_then(|x| x.governance_action.clone())
+        };
+        match action(0) {
+            Some(v17::governance_action::GovernanceAction::TreasuryWithdrawalsAction(x)) => {
+                assert_eq!(x.withdrawals[0].coin, 5_000_000);
+            }
+            other => panic!("unexpected governance action: {other:?}"),
+        }
+        match action(1) {
+            Some(v17::governance_action::GovernanceAction::ParameterChangeAction(x)) => {
+                assert_eq!(x.policy_hash.to_vec(), vec![0x44; 28]);
+                assert!(x.protocol_param_update.is_none());
+            }
+            other => panic!("unexpected governance action: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn convert_then_decode_with_017_preserves_metadata() {
+        let upstream = v18::Tx {
+            auxiliary: Some(v18::AuxData {
+                metadata: vec![v18::Metadata {
+                    label: 674,
+                    value: Some(v18::Metadatum {
+                        metadatum: Some(v18::metadatum::Metadatum::Text("hello".to_string())),
+                    }),
+                }],
+                scripts: vec![],
+            }),
+            ..Default::default()
+        };
+        let bal = convert_tx(upstream).expect("convert");
+        let bytes = bal.encode_to_vec();
+        let decoded = v17::Tx::decode(bytes.as_slice()).expect("0.17.0 decode");
+        let metadata = &decoded
+            .auxiliary
+            .as_ref()
+            .expect("auxiliary present")
+            .metadata;
+        assert_eq!(metadata[0].label, 674);
+        match metadata[0]
+            .value
+            .as_ref()
+            .and_then(|x| x.metadatum.as_ref())
+        {
+            Some(v17::metadatum::Metadatum::Text(text)) => assert_eq!(text, "hello"),
+            other => panic!("unexpected metadatum: {other:?}"),
+        }
+    }
+
     fn big_int(n: i64) -> v18::BigInt {
         v18::BigInt {
             big_int: Some(v18::big_int::BigInt::Int(n)),
@@ -1,11 +1,19 @@
+use broadcast::BroadcastHost;
 use futures::future::join_all;
 use itertools::Itertools;
 use kv::KvHost;
 use ledgers::LedgerHost;
 use logging::LoggerHost;
 use router::Router;
+use serde::{Deserialize, Serialize};
 use sign::SignerHost;
-use std::{collections::HashMap, io::Read, path::Path, sync::Arc, time::Instant};
+use std::{
+    collections::{HashMap, HashSet},
+    io::Read,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Instant,
+};
 use submit::SubmitHost;
 use thiserror::Error;
 use tokio::sync::{Mutex, RwLock};
@@ -18,13 +26,18 @@ pub mod wit {
         path: "./wit",
         async: true,
         tracing: true,
+        additional_derives: [serde::Serialize, serde::Deserialize],
     });
 }
 
+mod cache;
+mod limiter;
 mod metrics;
+mod pool;
 mod router;
 
 // implementations
+pub mod broadcast;
 pub mod drivers;
 pub mod http;
 pub mod kv;
@@ -34,11 +47,159 @@ pub mod sign;
 pub mod store;
 pub mod submit;
 
-pub use store::{AtomicUpdateTrait, Store, StoreTrait};
+pub use limiter::Resource;
+pub use store::{AtomicUpdateTrait, DeadLetter, DeadLetterId, KvBatch, Store, StoreTrait};
 pub use wit::Response;
 
 pub type WorkerId = String;
 
+/// Execution budget of a worker, applied to every call into its wasm
+/// instance (init included).
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct WorkerLimits {
+    /// Max amount of wasmtime fuel a single call may consume, unlimited
+    /// when not set.
+    pub fuel: Option<u64>,
+
+    /// Max size in bytes of each linear memory of the worker.
+    pub memory: Option<usize>,
+
+    /// Max number of elements of each table of the worker.
+    pub table_elements: Option<usize>,
+
+    /// Max number of core wasm instances the worker component may create.
+    pub instances: Option<usize>,
+
+    /// Max number of extra instances serving requests concurrently. When not
+    /// set, requests are served one at a time by the instance handling chain
+    /// events. Pooled instances don't share in-memory state with the one
+    /// handling chain events, and can't write to the KV.
+    pub request_instances: Option<usize>,
+}
+
+/// What to do when a worker fails to handle an event, be it an error
+/// returned by the handler, a trap or an exceeded limit.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FailurePolicy {
+    /// Log the failure and move on to the next event.
+    #[default]
+    Skip,
+
+    /// Retry the chain entry of the failed event, doubling the wait after
+    /// each attempt. The cursor of the worker stays before the entry, which
+    /// is replayed from the store log once the wait is over, while other
+    /// workers move on. The worker is halted once attempts run out. Timer and
+    /// message events aren't logged, so their failures are skipped.
+    Retry { max_attempts: u32, backoff_ms: u64 },
+
+    /// Stop delivering events to the worker, freezing its cursor. Upgrading
+    /// or resuming the worker lifts the halt and resumes from the failed
+    /// block.
+    Halt,
+
+    /// Keep the failed event aside in the store, to be replayed later (see
+    /// [`Runtime::replay_dead_letter`]), and move on.
+    DeadLetter,
+}
+
+/// Cap on the wait between two attempts of [`FailurePolicy::Retry`].
+const MAX_RETRY_BACKOFF: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// How many slots behind the latest block a worker changed the KV in, its KV
+/// undo records are kept. Cardano can't roll back more than k = 2160 blocks,
+/// produced in about 3k/f = 129600 slots on mainnet.
+const KV_UNDO_HORIZON: BlockSlot = 129_600;
+
+/// Window of slots whose chain events are delivered to a worker, both ends
+/// included. Blocks outside of it are skipped, though they still move the
+/// cursor of the worker forward.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct SlotRange {
+    pub since: Option<BlockSlot>,
+    pub until: Option<BlockSlot>,
+}
+
+impl SlotRange {
+    pub fn contains(&self, slot: BlockSlot) -> bool {
+        self.since.is_none_or(|x| slot >= x) && self.until.is_none_or(|x| slot <= x)
+    }
+
+    /// Whether the worker has nothing left to see once the chain reached
+    /// `slot`.
+    pub fn is_over(&self, slot: BlockSlot) -> bool {
+        self.until.is_some_and(|x| slot > x)
+    }
+}
+
+/// How the runtime runs a worker, given when registering it (see
+/// [`Runtime::register_worker`]).
+#[derive(Clone, Debug)]
+pub struct WorkerOptions {
+    pub limits: WorkerLimits,
+    pub range: SlotRange,
+    pub policy: FailurePolicy,
+    /// Whether the KV writes the worker makes while handling a block are
+    /// reverted when the block is rolled back. Workers that undo their own
+    /// state from undo events can opt out.
+    pub kv_rollback: bool,
+}
+
+impl Default for WorkerOptions {
+    fn default() -> Self {
+        Self {
+            limits: Default::default(),
+            range: Default::default(),
+            policy: Default::default(),
+            kv_rollback: true,
+        }
+    }
+}
+
+/// Sizing of wasmtime's pooling instance allocator. Unset values keep the
+/// wasmtime defaults.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct PoolingConfig {
+    /// Max number of component instances alive at once, across workers.
+    pub total_component_instances: Option<u32>,
+
+    /// Max number of core wasm instances alive at once, across workers.
+    pub total_core_instances: Option<u32>,
+
+    /// Max number of linear memories alive at once, across workers.
+    pub total_memories: Option<u32>,
+
+    /// Max number of tables alive at once, across workers.
+    pub total_tables: Option<u32>,
+
+    /// Max size in bytes of a single linear memory.
+    pub max_memory_size: Option<usize>,
+}
+
+impl From<&PoolingConfig> for wasmtime::PoolingAllocationConfig {
+    fn from(value: &PoolingConfig) -> Self {
+        let mut config = wasmtime::PoolingAllocationConfig::default();
+
+        if let Some(x) = value.total_component_instances {
+            config.total_component_instances(x);
+        }
+        if let Some(x) = value.total_core_instances {
+            config.total_core_instances(x);
+        }
+        if let Some(x) = value.total_memories {
+            config.total_memories(x);
+        }
+        if let Some(x) = value.total_tables {
+            config.total_tables(x);
+        }
+        if let Some(x) = value.max_memory_size {
+            config.max_memory_size(x);
+        }
+
+        config
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum Error {
     #[error("wasm error {0}")]
@@ -53,6 +214,27 @@ pub enum Error {
     #[error("worker failed to handle event (code: '{0}', message: '{1}')")]
     Handle(u32, String),
 
+    #[error("worker ran out of fuel (limit: {0})")]
+    OutOfFuel(u64),
+
+    #[error("worker exceeded its {0} limit ({1})")]
+    ResourceLimit(Resource, usize),
+
+    #[error("worker '{0}' is halted after failing to handle an event")]
+    WorkerHalted(WorkerId),
+
+    #[error("worker '{0}' is paused")]
+    WorkerPaused(WorkerId),
+
+    #[error("worker '{0}' failed to handle an event, retrying later")]
+    RetryScheduled(WorkerId),
+
+    #[error("worker '{0}' can't catch up, the log is missing the entries after its cursor ({1})")]
+    LogGap(WorkerId, LogSeq),
+
+    #[error("dead letter not found '{0}'")]
+    DeadLetterNotFound(DeadLetterId),
+
     #[error("no target available to solve request")]
     NoTarget,
 
@@ -135,6 +317,12 @@ impl From<redb::StorageError> for Error {
     }
 }
 
+impl From<rusqlite::Error> for Error {
+    fn from(value: rusqlite::Error) -> Self {
+        Self::Store(value.to_string())
+    }
+}
+
 impl From<pallas::ledger::addresses::Error> for Error {
     fn from(value: pallas::ledger::addresses::Error) -> Self {
         Self::BadAddress(value)
@@ -189,6 +377,16 @@ pub enum TxInput {
 }
 
 impl TxInput {
+    /// The reference to the output being spent.
+    pub fn txo_ref(&self) -> wit::balius::app::driver::TxoRef {
+        match self {
+            Self::Cardano(tx_input) => wit::balius::app::driver::TxoRef {
+                tx_hash: tx_input.tx_hash.to_vec(),
+                txo_index: tx_input.output_index,
+            },
+        }
+    }
+
     pub fn to_bytes(&self) -> Vec<u8> {
         use prost::Message;
 
@@ -202,6 +400,110 @@ impl TxInput {
             Self::Cardano(tx_input) => tx_input.as_output.as_ref().map(|o| o.address.to_vec()),
         }
     }
+
+    /// The `(policy, name)` of every asset held by the output being spent,
+    /// when the input is resolved.
+    pub fn assets(&self) -> Vec<(Vec<u8>, Vec<u8>)> {
+        match self {
+            Self::Cardano(tx_input) => tx_input
+                .as_output
+                .as_ref()
+                .map(cardano_assets)
+                .unwrap_or_default(),
+        }
+    }
+}
+
+fn cardano_assets(output: &balius_core::proto::v0::cardano::TxOutput) -> Vec<(Vec<u8>, Vec<u8>)> {
+    output
+        .assets
+        .iter()
+        .flat_map(|m| {
+            m.assets
+                .iter()
+                .map(|a| (m.policy_id.to_vec(), a.name.to_vec()))
+        })
+        .collect()
+}
+
+pub enum Certificate {
+    Cardano(balius_core::proto::v0::cardano::Certificate),
+}
+
+impl Certificate {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        use prost::Message;
+
+        match self {
+            Self::Cardano(cert) => cert.encode_to_vec(),
+        }
+    }
+
+    /// Every kind of certificate this one is made of.
+    pub fn kinds(&self) -> Vec<wit::balius::app::driver::CertificateKind> {
+        use balius_core::proto::v0::cardano::certificate::Certificate as C;
+        use wit::balius::app::driver::CertificateKind as K;
+
+        match self {
+            Self::Cardano(cert) => match &cert.certificate {
+                Some(C::StakeRegistration(_)) | Some(C::RegCert(_)) => vec![K::StakeRegistration],
+                Some(C::StakeDeregistration(_)) | Some(C::UnregCert(_)) => {
+                    vec![K::StakeDeregistration]
+                }
+                Some(C::StakeDelegation(_)) => vec![K::StakeDelegation],
+                Some(C::PoolRegistration(_)) => vec![K::PoolRegistration],
+                Some(C::PoolRetirement(_)) => vec![K::PoolRetirement],
+                Some(C::VoteDelegCert(_)) => vec![K::VoteDelegation],
+                Some(C::StakeVoteDelegCert(_)) => vec![K::StakeDelegation, K::VoteDelegation],
+                Some(C::StakeRegDelegCert(_)) => vec![K::StakeRegistration, K::StakeDelegation],
+                Some(C::VoteRegDelegCert(_)) => vec![K::StakeRegistration, K::VoteDelegation],
+                Some(C::StakeVoteRegDelegCert(_)) => {
+                    vec![K::StakeRegistration, K::StakeDelegation, K::VoteDelegation]
+                }
+                Some(C::AuthCommitteeHotCert(_)) => vec![K::CommitteeHotAuth],
+                Some(C::ResignCommitteeColdCert(_)) => vec![K::CommitteeColdResign],
+                Some(C::RegDrepCert(_)) => vec![K::DrepRegistration],
+                Some(C::UnregDrepCert(_)) => vec![K::DrepDeregistration],
+                Some(C::UpdateDrepCert(_)) => vec![K::DrepUpdate],
+                None => vec![],
+            },
+        }
+    }
+}
+
+pub enum Proposal {
+    Cardano(balius_core::proto::v0::cardano::GovernanceActionProposal),
+}
+
+impl Proposal {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        use prost::Message;
+
+        match self {
+            Self::Cardano(proposal) => proposal.encode_to_vec(),
+        }
+    }
+
+    pub fn kind(&self) -> Option<wit::balius::app::driver::GovernanceActionKind> {
+        use balius_core::proto::v0::cardano::governance_action::GovernanceAction as G;
+        use wit::balius::app::driver::GovernanceActionKind as K;
+
+        match self {
+            Self::Cardano(proposal) => {
+                let action = proposal.gov_action.as_ref()?.governance_action.as_ref()?;
+
+                Some(match action {
+                    G::ParameterChangeAction(_) => K::ParameterChange,
+                    G::HardForkInitiationAction(_) => K::HardForkInitiation,
+                    G::TreasuryWithdrawalsAction(_) => K::TreasuryWithdrawals,
+                    G::NoConfidenceAction(_) => K::NoConfidence,
+                    G::UpdateCommitteeAction(_) => K::UpdateCommittee,
+                    G::NewConstitutionAction(_) => K::NewConstitution,
+                    G::InfoAction(_) => K::Info,
+                })
+            }
+        }
+    }
 }
 
 pub enum Utxo {
@@ -222,6 +524,13 @@ impl Utxo {
             Self::Cardano(utxo) => Some(utxo.address.to_vec()),
         }
     }
+
+    /// The `(policy, name)` of every asset held by the output.
+    pub fn assets(&self) -> Vec<(Vec<u8>, Vec<u8>)> {
+        match self {
+            Self::Cardano(utxo) => cardano_assets(utxo),
+        }
+    }
 }
 
 pub enum Tx {
@@ -252,6 +561,52 @@ impl Tx {
                 .collect(),
         }
     }
+    pub fn certificates(&self) -> Vec<Certificate> {
+        match self {
+            Self::Cardano(tx) => tx
+                .certificates
+                .iter()
+                .map(|c| Certificate::Cardano(c.clone()))
+                .collect(),
+        }
+    }
+    pub fn proposals(&self) -> Vec<Proposal> {
+        match self {
+            Self::Cardano(tx) => tx
+                .proposals
+                .iter()
+                .map(|p| Proposal::Cardano(p.clone()))
+                .collect(),
+        }
+    }
+    /// The labels of the metadata attached to the Tx.
+    pub fn metadata_labels(&self) -> Vec<u64> {
+        match self {
+            Self::Cardano(tx) => tx
+                .auxiliary
+                .iter()
+                .flat_map(|aux| aux.metadata.iter().map(|m| m.label))
+                .collect(),
+        }
+    }
+    /// The (policy, asset name, amount) minted by the Tx, negative when burned.
+    pub fn mint(&self) -> Vec<(Vec<u8>, Vec<u8>, i64)> {
+        match self {
+            Self::Cardano(tx) => tx
+                .mint
+                .iter()
+                .flat_map(|multiasset| {
+                    multiasset.assets.iter().map(|asset| {
+                        (
+                            multiasset.policy_id.to_vec(),
+                            asset.name.to_vec(),
+                            asset.mint_coin,
+                        )
+                    })
+                })
+                .collect(),
+        }
+    }
     pub fn to_bytes(&self) -> Vec<u8> {
         use prost::Message;
 
@@ -298,6 +653,12 @@ impl Block {
         }
     }
 
+    pub fn tx_count(&self) -> usize {
+        match self {
+            Self::Cardano(block) => block.body.iter().map(|b| b.tx.len()).sum(),
+        }
+    }
+
     pub fn chain_point(&self) -> ChainPoint {
         match self {
             Self::Cardano(block) => ChainPoint::Cardano(BlockRef {
@@ -333,6 +694,8 @@ struct WorkerState {
     pub sign: Option<sign::SignerHost>,
     pub submit: Option<submit::SubmitHost>,
     pub http: Option<http::Http>,
+    pub broadcast: Option<broadcast::BroadcastHost>,
+    pub limiter: limiter::WorkerLimiter,
 }
 
 impl wit::balius::app::driver::Host for WorkerState {
@@ -350,39 +713,384 @@ impl wit::balius::app::driver::Host for WorkerState {
     }
 }
 
+type StateFactory = Arc<dyn Fn() -> WorkerState + Send + Sync>;
+
+fn is_out_of_fuel(err: &wasmtime::Error) -> bool {
+    err.downcast_ref::<wasmtime::Trap>() == Some(&wasmtime::Trap::OutOfFuel)
+}
+
+/// Map the error of a failed call into the worker, surfacing limit breaches
+/// as typed errors.
+fn call_error(
+    store: &mut wasmtime::Store<WorkerState>,
+    limits: &WorkerLimits,
+    err: wasmtime::Error,
+) -> Error {
+    if let Some((resource, limit)) = store.data_mut().limiter.take_breach() {
+        return Error::ResourceLimit(resource, limit);
+    }
+
+    if is_out_of_fuel(&err) {
+        return Error::OutOfFuel(limits.fuel.unwrap_or_default());
+    }
+
+    err.into()
+}
+
+/// The block a chain event belongs to, `None` for other events.
+fn event_block_ref(event: &wit::Event) -> Option<BlockRef> {
+    use wit::Event;
+
+    let block = match event {
+        Event::Tx(x)
+        | Event::TxUndo(x)
+        | Event::Mint(x)
+        | Event::MintUndo(x)
+        | Event::Metadata(x)
+        | Event::MetadataUndo(x) => &x.block,
+        Event::Utxo(x) | Event::UtxoUndo(x) => &x.block,
+        Event::UtxoSpent(x) | Event::UtxoSpentUndo(x) => &x.block,
+        Event::Certificate(x) | Event::CertificateUndo(x) => &x.block,
+        Event::Proposal(x) | Event::ProposalUndo(x) => &x.block,
+        Event::Block(x) | Event::BlockUndo(x) => &x.block,
+        Event::BlockBegin(x) | Event::BlockEnd(x) => &x.block,
+        Event::Timer(_) | Event::Request(_) | Event::Message(_) => return None,
+    };
+
+    Some(BlockRef {
+        slot: block.block_slot,
+        hash: block.block_hash.clone().into(),
+        height: block.block_height,
+        ..Default::default()
+    })
+}
+
+/// A chain entry that failed under [`FailurePolicy::Retry`], replayed from
+/// the store log once `not_before` passes.
+struct PendingRetry {
+    attempts: u32,
+    not_before: Instant,
+}
+
 struct LoadedWorker {
     wasm_store: wasmtime::Store<WorkerState>,
     instance: wit::Worker,
     cursor: Option<LogSeq>,
     metrics: Arc<metrics::Metrics>,
+    limits: WorkerLimits,
+    range: SlotRange,
+    // set once the chain moved past the end of the range
+    feed_ended: bool,
+    policy: FailurePolicy,
+    // set once an event failed under the halt policy
+    halted: bool,
+    // set while a failed chain entry waits to be retried
+    retry: Option<PendingRetry>,
+    // failed events not yet saved to the store
+    dead_letters: Vec<DeadLetter>,
+    kv_rollback: bool,
+    // shared with every instance of the worker, so a reload keeps it
+    kv_overlay: Option<Arc<std::sync::Mutex<kv::overlay::Overlay>>>,
+    // undo records not yet saved to the store, by block hash. `None` drops
+    // the record of a block that was rolled back.
+    kv_journal: Vec<(BlockSlot, Vec<u8>, Option<KvBatch>)>,
+    // needed to replace the instance once a trap leaves it unusable
+    pre: wit::WorkerPre<WorkerState>,
+    new_state: StateFactory,
+    config: Vec<u8>,
 }
 
 impl LoadedWorker {
+    async fn load(
+        engine: &wasmtime::Engine,
+        pre: &wit::WorkerPre<WorkerState>,
+        new_state: &StateFactory,
+        config: &[u8],
+        limits: &WorkerLimits,
+        metrics: &Arc<metrics::Metrics>,
+        cursor: Option<LogSeq>,
+    ) -> Result<Self, Error> {
+        let (mut wasm_store, instance) = Self::instantiate(engine, pre, new_state, limits).await?;
+        Self::init(&mut wasm_store, &instance, config, limits).await?;
+
+        let kv_overlay = wasm_store.data().kv.as_ref().map(|kv| kv.overlay());
+
+        Ok(Self {
+            wasm_store,
+            instance,
+            cursor,
+            metrics: metrics.clone(),
+            limits: limits.clone(),
+            range: SlotRange::default(),
+            feed_ended: false,
+            policy: FailurePolicy::default(),
+            halted: false,
+            retry: None,
+            dead_letters: vec![],
+            kv_rollback: true,
+            kv_overlay,
+            kv_journal: vec![],
+            pre: pre.clone(),
+            new_state: new_state.clone(),
+            config: config.to_vec(),
+        })
+    }
+
+    /// Create a wasm instance of the worker, without running its `init`.
+    async fn instantiate(
+        engine: &wasmtime::Engine,
+        pre: &wit::WorkerPre<WorkerState>,
+        new_state: &StateFactory,
+        limits: &WorkerLimits,
+    ) -> Result<(wasmtime::Store<WorkerState>, wit::Worker), Error> {
+        let mut wasm_store = wasmtime::Store::new(engine, new_state());
+        wasm_store.limiter(|state| &mut state.limiter);
+        wasm_store.set_fuel(limits.fuel.unwrap_or(u64::MAX))?;
+
+        if let Err((resource, limit)) = wasm_store.data_mut().limiter.instantiating() {
+            return Err(Error::ResourceLimit(resource, limit));
+        }
+
+        let instance = match pre.instantiate_async(&mut wasm_store).await {
+            Ok(instance) => instance,
+            Err(err) => return Err(call_error(&mut wasm_store, limits, err)),
+        };
+
+        Ok((wasm_store, instance))
+    }
+
+    async fn init(
+        wasm_store: &mut wasmtime::Store<WorkerState>,
+        instance: &wit::Worker,
+        config: &[u8],
+        limits: &WorkerLimits,
+    ) -> Result<(), Error> {
+        instance
+            .call_init(&mut *wasm_store, &config.to_vec())
+            .await
+            .map_err(|err| call_error(wasm_store, limits, err))
+    }
+
+    /// Replace the wasm instance with a fresh one, after a trap left the
+    /// current one unusable.
+    ///
+    /// The new instance runs `init` again: the channels of the worker and
+    /// whatever else it set up from its config live in the instance memory,
+    /// so an instance that skipped it couldn't handle any event. Its KV
+    /// access goes through the overlay of the worker from the start, so
+    /// `init` sees and buffers writes like the event being handled does.
+    async fn reload(&mut self) -> Result<(), Error> {
+        let engine = self.wasm_store.engine().clone();
+        let (mut wasm_store, instance) =
+            Self::instantiate(&engine, &self.pre, &self.new_state, &self.limits).await?;
+
+        if let (Some(kv), Some(overlay)) = (wasm_store.data_mut().kv.as_mut(), &self.kv_overlay) {
+            kv.set_overlay(overlay.clone());
+        }
+
+        Self::init(&mut wasm_store, &instance, &self.config, &self.limits).await?;
+
+        self.wasm_store = wasm_store;
+        self.instance = instance;
+
+        Ok(())
+    }
+
     pub async fn dispatch_event(
         &mut self,
         channel: u32,
         event: &wit::Event,
     ) -> Result<wit::Response, Error> {
-        self.instance
+        let fuel = self.limits.fuel.unwrap_or(u64::MAX);
+        self.wasm_store.set_fuel(fuel)?;
+
+        let result = self
+            .instance
             .call_handle(&mut self.wasm_store, channel, event)
-            .await?
-            .map_err(|err| Error::Handle(err.code, err.message))
+            .await;
+
+        let consumed = fuel - self.wasm_store.get_fuel()?;
+        self.metrics
+            .fuel_consumed(&self.wasm_store.data().worker_id, consumed);
+
+        match result {
+            Ok(response) => response.map_err(|err| Error::Handle(err.code, err.message)),
+            Err(err) => {
+                // a trap leaves the instance unusable
+                let err = call_error(&mut self.wasm_store, &self.limits, err);
+                warn!(
+                    worker = self.wasm_store.data().worker_id,
+                    %err, "worker trapped, reloading instance"
+                );
+                self.reload().await?;
+                Err(err)
+            }
+        }
+    }
+
+    async fn handle_request(
+        &mut self,
+        method: &str,
+        params: Vec<u8>,
+    ) -> Result<wit::Response, Error> {
+        let channel = self.wasm_store.data().router.find_request_target(method)?;
+
+        let evt = wit::Event::Request(params);
+
+        self.dispatch_event(channel, &evt).await
     }
 
     async fn acknowledge_event(&mut self, channel: u32, event: &wit::Event) -> Result<(), Error> {
-        let result = self.dispatch_event(channel, event).await;
+        let worker_id = self.wasm_store.data().worker_id.clone();
 
-        match result {
+        let err = match self.dispatch_event(channel, event).await {
             Ok(wit::Response::Acknowledge) => {
                 tracing::debug!("worker acknowledge");
+                return Ok(());
             }
             Ok(_) => {
                 tracing::warn!("worker returned unexpected data");
+                return Ok(());
+            }
+            Err(
+                err @ (Error::Handle(..)
+                | Error::OutOfFuel(_)
+                | Error::ResourceLimit(..)
+                | Error::Wasm(_)),
+            ) => err,
+            Err(err) => return Err(err),
+        };
+
+        match self.policy.clone() {
+            FailurePolicy::Skip => {
+                warn!(worker = worker_id, %err, "skipping failed event");
+                self.metrics.handler_failure(&worker_id, "skip");
+                Ok(())
+            }
+            FailurePolicy::DeadLetter => {
+                warn!(worker = worker_id, %err, "moving failed event to dead letters");
+                self.metrics.handler_failure(&worker_id, "dead_letter");
+
+                let code = match &err {
+                    Error::Handle(code, _) => Some(*code),
+                    _ => None,
+                };
+
+                let encoded = serde_json::to_vec(event)
+                    .map_err(|e| Error::Store(format!("failed to encode dead letter: {e}")))?;
+
+                self.dead_letters.push(DeadLetter {
+                    worker: worker_id,
+                    channel,
+                    event: encoded,
+                    code,
+                    message: err.to_string(),
+                    block: event_block_ref(event),
+                });
+
+                Ok(())
             }
-            Err(Error::Handle(code, message)) => {
-                tracing::warn!(code, message);
+            FailurePolicy::Retry { .. } if event_block_ref(event).is_none() => {
+                warn!(worker = worker_id, %err, "skipping failed event, only chain events are retried");
+                self.metrics.handler_failure(&worker_id, "skip");
+                Ok(())
+            }
+            FailurePolicy::Retry {
+                max_attempts,
+                backoff_ms,
+            } => {
+                let attempts = self.retry.as_ref().map_or(0, |x| x.attempts);
+
+                if attempts >= max_attempts {
+                    return Err(self.halt(err));
+                }
+
+                let backoff = std::time::Duration::from_millis(
+                    backoff_ms.saturating_mul(2u64.saturating_pow(attempts)),
+                )
+                .min(MAX_RETRY_BACKOFF);
+
+                warn!(worker = worker_id, %err, attempts, ?backoff, "retrying failed event");
+                self.metrics.handler_failure(&worker_id, "retry");
+
+                // the entry is replayed from the log, see `catch_up_worker`
+                self.retry = Some(PendingRetry {
+                    attempts: attempts + 1,
+                    not_before: Instant::now() + backoff,
+                });
+
+                Err(Error::RetryScheduled(worker_id))
             }
-            Err(e) => return Err(e),
+            FailurePolicy::Halt => Err(self.halt(err)),
+        }
+    }
+
+    /// Stop delivering events to the worker after a failed event.
+    fn halt(&mut self, err: Error) -> Error {
+        let worker_id = self.wasm_store.data().worker_id.clone();
+
+        tracing::error!(worker = worker_id, %err, "halting worker after failed event");
+        self.metrics.handler_failure(&worker_id, "halt");
+        self.halted = true;
+        self.retry = None;
+
+        Error::WorkerHalted(worker_id)
+    }
+
+    /// Whether a failed chain entry waits to be retried later.
+    fn is_backing_off(&self) -> bool {
+        self.retry
+            .as_ref()
+            .is_some_and(|x| x.not_before > Instant::now())
+    }
+
+    /// Take the dead letters not yet saved to the store.
+    fn take_dead_letters(&mut self) -> Vec<DeadLetter> {
+        std::mem::take(&mut self.dead_letters)
+    }
+
+    /// Take the KV writes buffered while handling chain events.
+    fn take_kv_writes(&mut self) -> KvBatch {
+        match &self.kv_overlay {
+            Some(overlay) => overlay.lock().unwrap().take_writes(),
+            None => KvBatch::default(),
+        }
+    }
+
+    /// Revert the KV writes the worker made while handling a block.
+    async fn revert_kv(
+        &mut self,
+        store: &Store,
+        block: &Block,
+        journal: &mut Vec<(BlockSlot, Vec<u8>, Option<KvBatch>)>,
+    ) -> Result<(), Error> {
+        let worker_id = self.wasm_store.data().worker_id.clone();
+        let Some(kv) = self.wasm_store.data().kv.as_ref() else {
+            return Ok(());
+        };
+
+        let slot = block.slot();
+        let block_hash = block.hash();
+
+        // the record may not be saved yet, eg: during a catch-up batch
+        let pending = self
+            .kv_journal
+            .iter()
+            .rev()
+            .find(|(_, hash, _)| *hash == block_hash)
+            .map(|(_, _, undo)| undo.clone());
+
+        let undo = match pending {
+            Some(undo) => undo,
+            None => store.get_kv_undo(&worker_id, slot, &block_hash).await?,
+        };
+
+        if let Some(undo) = undo {
+            kv.revert(&undo)
+                .await
+                .map_err(|err| Error::KvError(err.to_string()))?;
+
+            journal.push((slot, block_hash, None));
         }
 
         Ok(())
@@ -393,9 +1101,10 @@ impl LoadedWorker {
         let block_hash = block.hash();
         let block_height = block.height();
         let block_slot = block.slot();
-        for tx in block.txs()? {
+        let txs = block.txs()?;
+        for tx in &txs {
             let tx_hash = tx.hash();
-            let channels = self.wasm_store.data().router.find_tx_targets(&tx);
+            let channels = self.wasm_store.data().router.find_tx_targets(tx);
             if !channels.is_empty() {
                 let event = wit::Event::Tx(wit::balius::app::driver::Tx {
                     block: wit::balius::app::driver::BlockRef {
@@ -412,44 +1121,193 @@ impl LoadedWorker {
                 }
             }
 
-            for (index, utxo) in tx.outputs().into_iter().enumerate() {
-                let channels = self.wasm_store.data().router.find_utxo_targets(&utxo);
-                if channels.is_empty() {
-                    continue;
+            let channels = self.wasm_store.data().router.find_mint_targets(tx);
+            if !channels.is_empty() {
+                let event = wit::Event::Mint(wit::balius::app::driver::Tx {
+                    block: wit::balius::app::driver::BlockRef {
+                        block_hash: block_hash.clone(),
+                        block_height,
+                        block_slot,
+                    },
+                    body: tx.to_bytes(),
+                    hash: tx_hash.clone(),
+                });
+                for channel in channels {
+                    self.metrics.mint_handled(&worker_id);
+                    self.acknowledge_event(channel, &event).await?;
                 }
+            }
 
-                let event = wit::Event::Utxo(wit::balius::app::driver::Utxo {
+            let channels = self.wasm_store.data().router.find_metadata_targets(tx);
+            if !channels.is_empty() {
+                let event = wit::Event::Metadata(wit::balius::app::driver::Tx {
                     block: wit::balius::app::driver::BlockRef {
                         block_hash: block_hash.clone(),
                         block_height,
                         block_slot,
                     },
-                    body: utxo.to_bytes(),
-                    ref_: wit::balius::app::driver::TxoRef {
-                        tx_hash: tx_hash.clone(),
-                        txo_index: index as u32,
-                    },
+                    body: tx.to_bytes(),
+                    hash: tx_hash.clone(),
                 });
-
                 for channel in channels {
-                    self.metrics.utxo_handled(&worker_id);
+                    self.metrics.metadata_handled(&worker_id);
                     self.acknowledge_event(channel, &event).await?;
                 }
             }
-        }
 
-        Ok(())
-    }
+            for (index, cert) in tx.certificates().into_iter().enumerate() {
+                let channels = self
+                    .wasm_store
+                    .data()
+                    .router
+                    .find_certificate_targets(&cert);
+                if channels.is_empty() {
+                    continue;
+                }
+
+                let event = wit::Event::Certificate(wit::balius::app::driver::Certificate {
+                    block: wit::balius::app::driver::BlockRef {
+                        block_hash: block_hash.clone(),
+                        block_height,
+                        block_slot,
+                    },
+                    body: cert.to_bytes(),
+                    tx_hash: tx_hash.clone(),
+                    index: index as u32,
+                });
+
+                for channel in channels {
+                    self.metrics.certificate_handled(&worker_id);
+                    self.acknowledge_event(channel, &event).await?;
+                }
+            }
+
+            for (index, proposal) in tx.proposals().into_iter().enumerate() {
+                let channels = self
+                    .wasm_store
+                    .data()
+                    .router
+                    .find_proposal_targets(&proposal);
+                if channels.is_empty() {
+                    continue;
+                }
+
+                let event = wit::Event::Proposal(wit::balius::app::driver::Proposal {
+                    block: wit::balius::app::driver::BlockRef {
+                        block_hash: block_hash.clone(),
+                        block_height,
+                        block_slot,
+                    },
+                    body: proposal.to_bytes(),
+                    tx_hash: tx_hash.clone(),
+                    index: index as u32,
+                });
+
+                for channel in channels {
+                    self.metrics.proposal_handled(&worker_id);
+                    self.acknowledge_event(channel, &event).await?;
+                }
+            }
+
+            for input in tx.inputs() {
+                let channels = self
+                    .wasm_store
+                    .data()
+                    .router
+                    .find_utxo_spent_targets(&input);
+                if channels.is_empty() {
+                    continue;
+                }
+
+                let event = wit::Event::UtxoSpent(wit::balius::app::driver::SpentUtxo {
+                    block: wit::balius::app::driver::BlockRef {
+                        block_hash: block_hash.clone(),
+                        block_height,
+                        block_slot,
+                    },
+                    body: input.to_bytes(),
+                    ref_: input.txo_ref(),
+                    spent_by: tx_hash.clone(),
+                });
+
+                for channel in channels {
+                    self.metrics.utxo_spent_handled(&worker_id);
+                    self.acknowledge_event(channel, &event).await?;
+                }
+            }
+
+            for (index, utxo) in tx.outputs().into_iter().enumerate() {
+                let channels = self.wasm_store.data().router.find_utxo_targets(&utxo);
+                if channels.is_empty() {
+                    continue;
+                }
+
+                let event = wit::Event::Utxo(wit::balius::app::driver::Utxo {
+                    block: wit::balius::app::driver::BlockRef {
+                        block_hash: block_hash.clone(),
+                        block_height,
+                        block_slot,
+                    },
+                    body: utxo.to_bytes(),
+                    ref_: wit::balius::app::driver::TxoRef {
+                        tx_hash: tx_hash.clone(),
+                        txo_index: index as u32,
+                    },
+                });
+
+                for channel in channels {
+                    self.metrics.utxo_handled(&worker_id);
+                    self.acknowledge_event(channel, &event).await?;
+                }
+            }
+        }
+
+        let channels = self.wasm_store.data().router.find_block_targets();
+        if !channels.is_empty() {
+            let event = wit::Event::Block(wit::balius::app::driver::Block {
+                block: wit::balius::app::driver::BlockRef {
+                    block_hash: block_hash.clone(),
+                    block_height,
+                    block_slot,
+                },
+                txs: txs.iter().map(Tx::to_bytes).collect(),
+            });
+            for channel in channels {
+                self.metrics.block_handled(&worker_id);
+                self.acknowledge_event(channel, &event).await?;
+            }
+        }
+
+        Ok(())
+    }
 
     async fn undo_block(&mut self, block: &Block) -> Result<(), Error> {
         let worker_id = self.wasm_store.data().worker_id.clone();
         let block_hash = block.hash();
         let block_height = block.height();
         let block_slot = block.slot();
-        for tx in block.txs()? {
+        let txs = block.txs()?;
+
+        let channels = self.wasm_store.data().router.find_block_undo_targets();
+        if !channels.is_empty() {
+            let event = wit::Event::BlockUndo(wit::balius::app::driver::Block {
+                block: wit::balius::app::driver::BlockRef {
+                    block_hash: block_hash.clone(),
+                    block_height,
+                    block_slot,
+                },
+                txs: txs.iter().map(Tx::to_bytes).collect(),
+            });
+            for channel in channels {
+                self.metrics.undo_block_handled(&worker_id);
+                self.acknowledge_event(channel, &event).await?;
+            }
+        }
+
+        for tx in &txs {
             let tx_hash = tx.hash();
             for (index, utxo) in tx.outputs().into_iter().enumerate().rev() {
-                let channels = self.wasm_store.data().router.find_utxo_targets(&utxo);
+                let channels = self.wasm_store.data().router.find_utxo_undo_targets(&utxo);
                 if channels.is_empty() {
                     continue;
                 }
@@ -473,7 +1331,105 @@ impl LoadedWorker {
                 }
             }
 
-            let channels = self.wasm_store.data().router.find_tx_targets(&tx);
+            for input in tx.inputs().into_iter().rev() {
+                let channels = self
+                    .wasm_store
+                    .data()
+                    .router
+                    .find_utxo_spent_undo_targets(&input);
+                if channels.is_empty() {
+                    continue;
+                }
+
+                let event = wit::Event::UtxoSpentUndo(wit::balius::app::driver::SpentUtxo {
+                    block: wit::balius::app::driver::BlockRef {
+                        block_hash: block_hash.clone(),
+                        block_height,
+                        block_slot,
+                    },
+                    body: input.to_bytes(),
+                    ref_: input.txo_ref(),
+                    spent_by: tx_hash.clone(),
+                });
+
+                for channel in channels {
+                    self.metrics.undo_utxo_spent_handled(&worker_id);
+                    self.acknowledge_event(channel, &event).await?;
+                }
+            }
+
+            let channels = self.wasm_store.data().router.find_metadata_targets(tx);
+            if !channels.is_empty() {
+                let event = wit::Event::MetadataUndo(wit::balius::app::driver::Tx {
+                    block: wit::balius::app::driver::BlockRef {
+                        block_hash: block_hash.clone(),
+                        block_height,
+                        block_slot,
+                    },
+                    body: tx.to_bytes(),
+                    hash: tx_hash.clone(),
+                });
+                for channel in channels {
+                    self.metrics.undo_metadata_handled(&worker_id);
+                    self.acknowledge_event(channel, &event).await?;
+                }
+            }
+
+            for (index, proposal) in tx.proposals().into_iter().enumerate().rev() {
+                let channels = self
+                    .wasm_store
+                    .data()
+                    .router
+                    .find_proposal_targets(&proposal);
+                if channels.is_empty() {
+                    continue;
+                }
+
+                let event = wit::Event::ProposalUndo(wit::balius::app::driver::Proposal {
+                    block: wit::balius::app::driver::BlockRef {
+                        block_hash: block_hash.clone(),
+                        block_height,
+                        block_slot,
+                    },
+                    body: proposal.to_bytes(),
+                    tx_hash: tx_hash.clone(),
+                    index: index as u32,
+                });
+
+                for channel in channels {
+                    self.metrics.undo_proposal_handled(&worker_id);
+                    self.acknowledge_event(channel, &event).await?;
+                }
+            }
+
+            for (index, cert) in tx.certificates().into_iter().enumerate().rev() {
+                let channels = self
+                    .wasm_store
+                    .data()
+                    .router
+                    .find_certificate_targets(&cert);
+                if channels.is_empty() {
+                    continue;
+                }
+
+                let event = wit::Event::CertificateUndo(wit::balius::app::driver::Certificate {
+                    block: wit::balius::app::driver::BlockRef {
+                        block_hash: block_hash.clone(),
+                        block_height,
+                        block_slot,
+                    },
+                    body: cert.to_bytes(),
+                    tx_hash: tx_hash.clone(),
+                    index: index as u32,
+                });
+
+                for channel in channels {
+                    self.metrics.undo_certificate_handled(&worker_id);
+                    self.acknowledge_event(channel, &event).await?;
+                }
+            }
+
+            let channels = self.wasm_store.data().router.find_tx_undo_targets(tx);
             if !channels.is_empty() {
                 let event = wit::Event::TxUndo(wit::balius::app::driver::Tx {
                     block: wit::balius::app::driver::BlockRef {
@@ -489,65 +1445,624 @@ impl LoadedWorker {
                     self.acknowledge_event(channel, &event).await?;
                 }
             }
+
+            let channels = self.wasm_store.data().router.find_mint_targets(tx);
+            if !channels.is_empty() {
+                let event = wit::Event::MintUndo(wit::balius::app::driver::Tx {
+                    block: wit::balius::app::driver::BlockRef {
+                        block_hash: block_hash.clone(),
+                        block_height,
+                        block_slot,
+                    },
+                    body: tx.to_bytes(),
+                    hash: tx_hash.clone(),
+                });
+                for channel in channels {
+                    self.metrics.undo_mint_handled(&worker_id);
+                    self.acknowledge_event(channel, &event).await?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Notify the block-begin / block-end channels about a block boundary.
+    async fn notify_block_boundary(
+        &mut self,
+        block: &Block,
+        undo: bool,
+        end: bool,
+    ) -> Result<(), Error> {
+        let router = &self.wasm_store.data().router;
+        let channels = if end {
+            router.find_block_end_targets()
+        } else {
+            router.find_block_begin_targets()
+        };
+
+        if channels.is_empty() {
+            return Ok(());
+        }
+
+        let boundary = wit::balius::app::driver::BlockBoundary {
+            block: wit::balius::app::driver::BlockRef {
+                block_hash: block.hash(),
+                block_height: block.height(),
+                block_slot: block.slot(),
+            },
+            tx_count: block.tx_count() as u32,
+            undo,
+        };
+        let event = if end {
+            wit::Event::BlockEnd(boundary)
+        } else {
+            wit::Event::BlockBegin(boundary)
+        };
+
+        for channel in channels {
+            self.acknowledge_event(channel, &event).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Handle one entry of the store log. KV writes are buffered until the
+    /// cursor is persisted, and dropped if the entry fails.
+    async fn apply_chain(
+        &mut self,
+        store: &Store,
+        undo_blocks: &Vec<Block>,
+        next_block: &Block,
+    ) -> Result<(), Error> {
+        let overlay = self.kv_overlay.clone();
+
+        if let Some(overlay) = &overlay {
+            overlay.lock().unwrap().begin_entry();
+        }
+
+        let mut journal = vec![];
+        let result = self
+            .apply_chain_entry(store, undo_blocks, next_block, &mut journal)
+            .await;
+
+        if let Some(overlay) = &overlay {
+            overlay.lock().unwrap().end_entry(result.is_ok());
+        }
+
+        if result.is_ok() {
+            self.kv_journal.extend(journal);
+            self.retry = None;
+        }
+
+        result
+    }
+
+    async fn apply_chain_entry(
+        &mut self,
+        store: &Store,
+        undo_blocks: &Vec<Block>,
+        next_block: &Block,
+        journal: &mut Vec<(BlockSlot, Vec<u8>, Option<KvBatch>)>,
+    ) -> Result<(), Error> {
+        for block in undo_blocks {
+            if !self.range.contains(block.slot()) {
+                continue;
+            }
+
+            self.notify_block_boundary(block, true, false).await?;
+            self.undo_block(block).await?;
+            self.notify_block_boundary(block, true, true).await?;
+
+            if self.kv_rollback {
+                self.revert_kv(store, block, journal).await?;
+            }
+        }
+
+        if self.range.contains(next_block.slot()) {
+            let recording = self.kv_overlay.clone().filter(|_| self.kv_rollback);
+
+            if let Some(overlay) = &recording {
+                overlay.lock().unwrap().begin_block();
+            }
+
+            self.notify_block_boundary(next_block, false, false).await?;
+            self.apply_block(next_block).await?;
+            self.notify_block_boundary(next_block, false, true).await?;
+
+            if let Some(overlay) = &recording {
+                let undo = overlay.lock().unwrap().end_block();
+
+                if !undo.is_empty() {
+                    journal.push((next_block.slot(), next_block.hash(), Some(undo)));
+                }
+            }
+        }
+
+        let feed_ended = self.range.is_over(next_block.slot());
+        if feed_ended && !self.feed_ended {
+            info!(
+                worker = self.wasm_store.data().worker_id,
+                until = self.range.until,
+                "worker reached the end of its slot range, no more chain events"
+            );
+        }
+        self.feed_ended = feed_ended;
+
+        Ok(())
+    }
+}
+
+type WorkerMap = HashMap<String, Mutex<LoadedWorker>>;
+type TimerMap = HashMap<WorkerId, Vec<(u32, String)>>;
+
+struct PreparedWorker {
+    worker: LoadedWorker,
+    timers: Vec<(u32, String)>,
+    pool: Option<Arc<pool::RequestPool>>,
+}
+
+#[derive(Clone)]
+pub struct Runtime {
+    engine: wasmtime::Engine,
+    linker: wasmtime::component::Linker<WorkerState>,
+    loaded: Arc<RwLock<WorkerMap>>,
+    timers: Arc<RwLock<TimerMap>>,
+    catching_up: Arc<RwLock<HashSet<WorkerId>>>,
+    paused: Arc<RwLock<HashSet<WorkerId>>>,
+    // redb blocks the thread when a write transaction is already open, so
+    // writers wait on this instead
+    store_writes: Arc<Mutex<()>>,
+    pools: Arc<RwLock<HashMap<WorkerId, Arc<pool::RequestPool>>>>,
+    compile_cache: Option<cache::ComponentCache>,
+
+    store: store::Store,
+    ledger: Option<ledgers::Ledger>,
+    logging: Option<logging::Logger>,
+    kv: Option<kv::Kv>,
+    sign: Option<sign::Signer>,
+    submit: Option<submit::Submit>,
+    http: Option<http::Http>,
+    broadcast: Option<broadcast::Broadcast>,
+
+    metrics: Arc<metrics::Metrics>,
+}
+
+impl Runtime {
+    pub fn builder(store: store::Store) -> RuntimeBuilder {
+        RuntimeBuilder::new(store)
+    }
+
+    /// Point of the chain to resume syncing from.
+    ///
+    /// That's the head of the store log, workers behind it catch up from the
+    /// log itself (see [`Runtime::catch_up_worker`]). If nothing was logged
+    /// yet, but every worker has a start slot, sync can start from the
+    /// earliest of those slots.
+    pub async fn chain_cursor(&self) -> Result<Option<ChainPoint>, Error> {
+        if let Some(seq) = self.store.last_log_seq().await? {
+            debug!(seq, "resuming from head of the log");
+            return self.store.find_chain_point(seq).await;
+        }
+
+        let mut earliest_start = None;
+
+        for w in self.loaded.read().await.values() {
+            let Some(since) = w.lock().await.range.since else {
+                return Ok(None);
+            };
+
+            earliest_start = match earliest_start {
+                Some(prev) => Some(std::cmp::min(prev, since)),
+                None => Some(since),
+            };
+        }
+
+        if let Some(slot) = earliest_start {
+            debug!(slot, "starting from earliest worker start slot");
+
+            // a point without hash, the chain-sync driver resolves it to
+            // the first block at or after the slot
+            return Ok(Some(ChainPoint::Cardano(BlockRef {
+                slot,
+                ..Default::default()
+            })));
+        }
+
+        Ok(None)
+    }
+
+    /// Workers whose cursor is behind the head of the store log. These
+    /// don't receive live chain events until they catch up.
+    pub async fn lagging_workers(&self) -> Result<Vec<WorkerId>, Error> {
+        let head = self.store.last_log_seq().await?;
+        let paused = self.paused.read().await.clone();
+
+        let mut lagging = vec![];
+
+        for (id, w) in self.loaded.read().await.iter() {
+            let w = w.lock().await;
+
+            if !w.halted && !w.is_backing_off() && !paused.contains(id) && w.cursor < head {
+                lagging.push(id.clone());
+            }
+        }
+
+        Ok(lagging)
+    }
+
+    /// Replay up to `batch` logged entries into a worker behind the head of
+    /// the log. Returns whether the worker reached the head, in which case
+    /// it follows live chain events from then on.
+    pub async fn catch_up_worker(&self, id: &str, batch: usize) -> Result<bool, Error> {
+        if self.is_paused(id).await {
+            return Ok(false);
+        }
+
+        self.catching_up.write().await.insert(id.to_owned());
+
+        let result = async {
+            let workers = self.loaded.read().await;
+            let mut worker = workers
+                .get(id)
+                .ok_or(Error::WorkerNotFound(id.to_owned()))?
+                .lock()
+                .await;
+
+            if worker.is_backing_off() {
+                return Ok(false);
+            }
+
+            let start = worker.cursor;
+            let entries = self.store.read_log(start, batch).await?;
+
+            // the log can't replay what came before its first entry
+            match (start, entries.first()) {
+                (Some(cursor), Some((seq, _))) if *seq != cursor + 1 => {
+                    return Err(Error::LogGap(id.to_owned(), cursor));
+                }
+                (None, Some((_, entry))) => {
+                    let first = Block::from_bytes(&entry.next_block).slot();
+
+                    if worker.range.since.is_none_or(|since| since < first) {
+                        warn!(
+                            worker = id,
+                            since = worker.range.since,
+                            first,
+                            "worker starts before the log, earlier chain events aren't replayed"
+                        );
+                    }
+                }
+                _ => (),
+            }
+
+            let mut applied = Ok(());
+
+            for (seq, entry) in entries {
+                let undo_blocks = entry
+                    .undo_blocks
+                    .iter()
+                    .map(|x| Block::from_bytes(x))
+                    .collect();
+                let next_block = Block::from_bytes(&entry.next_block);
+
+                applied = worker
+                    .apply_chain(&self.store, &undo_blocks, &next_block)
+                    .await;
+                if applied.is_err() {
+                    break;
+                }
+
+                worker.cursor = Some(seq);
+            }
+
+            // keep the progress made, even if the batch didn't complete
+            if let Some(seq) = worker.cursor.filter(|_| worker.cursor != start) {
+                self.commit_chain_progress(seq, vec![&mut *worker]).await?;
+            }
+
+            match applied {
+                // replayed again once the backoff is over
+                Err(Error::RetryScheduled(_)) => return Ok(false),
+                x => x?,
+            }
+
+            let head = self.store.last_log_seq().await?;
+            debug!(
+                worker = id,
+                cursor = worker.cursor,
+                head,
+                "worker catching up"
+            );
+
+            Ok(worker.cursor >= head)
+        }
+        .await;
+
+        if !matches!(result, Ok(false)) {
+            self.catching_up.write().await.remove(id);
+        }
+
+        result
+    }
+
+    /// Builds the initial state of every instance of the worker.
+    fn state_factory(
+        &self,
+        id: &str,
+        limits: &WorkerLimits,
+        core_instances: usize,
+    ) -> StateFactory {
+        let id = id.to_owned();
+        let limits = limits.clone();
+        let ledger = self.ledger.clone();
+        let logging = self.logging.clone();
+        let kv = self.kv.clone();
+        let sign = self.sign.clone();
+        let submit = self.submit.clone();
+        let http = self.http.clone();
+        let broadcast = self.broadcast.clone();
+        let metrics = self.metrics.clone();
+
+        Arc::new(move || WorkerState {
+            worker_id: id.clone(),
+            router: Router::new(),
+            ledger: ledger.as_ref().map(|l| LedgerHost::new(&id, l, &metrics)),
+            logging: logging
+                .as_ref()
+                .map(|kv| LoggerHost::new(&id, kv, &metrics)),
+            kv: kv.as_ref().map(|kv| KvHost::new(&id, kv, &metrics)),
+            sign: sign.as_ref().map(|s| SignerHost::new(&id, s, &metrics)),
+            submit: submit.as_ref().map(|s| SubmitHost::new(&id, s, &metrics)),
+            http: http.clone(),
+            broadcast: broadcast
+                .as_ref()
+                .map(|b| BroadcastHost::new(&id, b, &metrics)),
+            limiter: limiter::WorkerLimiter::new(&id, &limits, core_instances, &metrics),
+        })
+    }
+
+    /// Persist the cursor of workers that handled chain events up to `seq`,
+    /// along with what those events left behind, in a single transaction:
+    /// KV writes, KV undo records and dead letters. KV writes reach the
+    /// provider right after the commit, so the caller must hold the lock of
+    /// every worker until this returns.
+    async fn commit_chain_progress(
+        &self,
+        seq: LogSeq,
+        mut workers: Vec<&mut LoadedWorker>,
+    ) -> Result<(), Error> {
+        let _writes = self.store_writes.lock().await;
+        let mut store_update = self.store.start_atomic_update(seq).await?;
+        let mut kv_writes = vec![];
+
+        for worker in workers.iter_mut() {
+            let id = worker.wasm_store.data().worker_id.clone();
+            store_update.update_worker_cursor(&id).await?;
+
+            let mut latest = None;
+
+            for (slot, block_hash, undo) in std::mem::take(&mut worker.kv_journal) {
+                match undo {
+                    Some(undo) => {
+                        store_update
+                            .save_kv_undo(&id, slot, &block_hash, &undo)
+                            .await?;
+                        latest = Some(slot);
+                    }
+                    None => store_update.remove_kv_undo(&id, slot, &block_hash).await?,
+                }
+            }
+
+            if let Some(horizon) = latest.and_then(|x| x.checked_sub(KV_UNDO_HORIZON)) {
+                store_update.prune_kv_undo(&id, horizon).await?;
+            }
+
+            let writes = worker.take_kv_writes();
+            if !writes.is_empty() {
+                store_update.set_kv_redo(&id, &writes).await?;
+            }
+
+            kv_writes.push(writes);
+
+            for letter in worker.take_dead_letters() {
+                store_update.add_dead_letter(&letter).await?;
+            }
+        }
+
+        store_update.commit().await?;
+
+        let mut applied = vec![];
+
+        for (worker, writes) in workers.iter().zip(kv_writes) {
+            if writes.is_empty() {
+                continue;
+            }
+
+            let id = &worker.wasm_store.data().worker_id;
+            self.apply_kv_writes(id, &writes).await?;
+            applied.push(id.clone());
         }
 
-        Ok(())
+        self.store.clear_kv_redo(&applied).await
     }
 
-    async fn apply_chain(
-        &mut self,
-        undo_blocks: &Vec<Block>,
-        next_block: &Block,
-    ) -> Result<(), Error> {
-        for block in undo_blocks {
-            self.undo_block(block).await?;
+    async fn apply_kv_writes(&self, id: &str, writes: &KvBatch) -> Result<(), Error> {
+        let Some(kv) = self.kv.as_ref() else {
+            return Ok(());
+        };
+
+        kv.apply(id, writes)
+            .await
+            .map_err(|err| Error::KvError(err.to_string()))
+    }
+
+    /// Save the dead letters of a worker kept in memory so far.
+    async fn flush_dead_letters(&self, worker: &mut LoadedWorker) -> Result<(), Error> {
+        let letters = worker.take_dead_letters();
+
+        if letters.is_empty() {
+            return Ok(());
         }
 
-        self.apply_block(next_block).await
+        let _writes = self.store_writes.lock().await;
+        self.store.add_dead_letters(&letters).await
     }
-}
 
-type WorkerMap = HashMap<String, Mutex<LoadedWorker>>;
+    /// Events that workers failed to handle under
+    /// [`FailurePolicy::DeadLetter`], optionally only those of one worker.
+    pub async fn dead_letters(
+        &self,
+        worker: Option<&str>,
+    ) -> Result<Vec<(DeadLetterId, DeadLetter)>, Error> {
+        self.store.list_dead_letters(worker).await
+    }
 
-#[derive(Clone)]
-pub struct Runtime {
-    engine: wasmtime::Engine,
-    linker: wasmtime::component::Linker<WorkerState>,
-    loaded: Arc<RwLock<WorkerMap>>,
+    /// Dispatch a dead letter to its worker again, removing it once handled.
+    /// The letter stays in the store if the worker fails again.
+    ///
+    /// Note that replayed chain events reach the worker out of order, after
+    /// the blocks that followed them.
+    pub async fn replay_dead_letter(&self, id: DeadLetterId) -> Result<(), Error> {
+        let letter = self
+            .store
+            .get_dead_letter(id)
+            .await?
+            .ok_or(Error::DeadLetterNotFound(id))?;
 
-    store: store::Store,
-    ledger: Option<ledgers::Ledger>,
-    logging: Option<logging::Logger>,
-    kv: Option<kv::Kv>,
-    sign: Option<sign::Signer>,
-    submit: Option<submit::Submit>,
-    http: Option<http::Http>,
+        let event: wit::Event = serde_json::from_slice(&letter.event)
+            .map_err(|e| Error::Store(format!("failed to decode dead letter: {e}")))?;
 
-    metrics: Arc<metrics::Metrics>,
-}
+        let workers = self.loaded.read().await;
+        let mut worker = workers
+            .get(&letter.worker)
+            .ok_or(Error::WorkerNotFound(letter.worker.clone()))?
+            .lock()
+            .await;
 
-impl Runtime {
-    pub fn builder(store: store::Store) -> RuntimeBuilder {
-        RuntimeBuilder::new(store)
+        match worker.dispatch_event(letter.channel, &event).await? {
+            wit::Response::Acknowledge => {}
+            _ => warn!(worker = letter.worker, "worker returned unexpected data"),
+        }
+
+        info!(worker = letter.worker, id, "replayed dead letter");
+
+        let _writes = self.store_writes.lock().await;
+        self.store.remove_dead_letter(id).await
     }
 
-    pub async fn chain_cursor(&self) -> Result<Option<ChainPoint>, Error> {
-        let mut lowest_seq = None;
-        for w in self.loaded.read().await.values() {
-            if let Some(cursor) = w.lock().await.cursor {
-                lowest_seq = match lowest_seq {
-                    Some(prev) => Some(std::cmp::min(prev, cursor)),
-                    None => Some(cursor),
-                };
+    /// Drop a dead letter without replaying it.
+    pub async fn discard_dead_letter(&self, id: DeadLetterId) -> Result<(), Error> {
+        self.store
+            .get_dead_letter(id)
+            .await?
+            .ok_or(Error::DeadLetterNotFound(id))?;
+
+        let _writes = self.store_writes.lock().await;
+        self.store.remove_dead_letter(id).await
+    }
+
+    /// Compile and instantiate a worker, running its `init`, without making
+    /// it visible to the runtime yet.
+    async fn prepare_worker(
+        &self,
+        id: &str,
+        wasm: &[u8],
+        config: serde_json::Value,
+        options: WorkerOptions,
+        cursor: Option<LogSeq>,
+    ) -> Result<PreparedWorker, Error> {
+        // writes committed with the cursor that didn't reach the provider
+        // before the process stopped
+        if let Some(writes) = self.store.get_kv_redo(id).await? {
+            info!(worker = id, "applying KV writes of the last chain commit");
+            self.apply_kv_writes(id, &writes).await?;
+
+            let _writes = self.store_writes.lock().await;
+            self.store.clear_kv_redo(&[id.to_owned()]).await?;
+        }
+
+        let component = match &self.compile_cache {
+            Some(cache) => cache.load(&self.engine, wasm)?,
+            None => wasmtime::component::Component::new(&self.engine, wasm)?,
+        };
+        let pre = wit::WorkerPre::new(self.linker.instantiate_pre(&component)?)?;
+        let config = serde_json::to_vec(&config).unwrap();
+        let limits = options.limits;
+        let core_instances = limiter::core_instances(wasm)?;
+        let new_state = self.state_factory(id, &limits, core_instances);
+
+        let mut worker = LoadedWorker::load(
+            &self.engine,
+            &pre,
+            &new_state,
+            &config,
+            &limits,
+            &self.metrics,
+            cursor,
+        )
+        .await?;
+
+        worker.range = options.range;
+
+        worker.policy = options.policy;
+
+        worker.kv_rollback = options.kv_rollback;
+
+        if let (Some(seq), Some(_)) = (cursor, worker.range.until) {
+            if let Some(point) = self.store.find_chain_point(seq).await? {
+                worker.feed_ended = worker.range.is_over(point.slot());
             }
         }
 
-        if let Some(seq) = lowest_seq {
-            debug!(lowest_seq, "found lowest seq");
-            return self.store.find_chain_point(seq).await;
+        let timers = worker.wasm_store.data().router.find_timer_targets();
+        for (_, interval) in timers.iter() {
+            interval
+                .parse::<drivers::timer::Interval>()
+                .map_err(|e| Error::Config(format!("worker '{id}': {e}")))?;
         }
 
-        Ok(None)
+        let pool = limits
+            .request_instances
+            .filter(|size| *size > 0)
+            .map(|size| {
+                Arc::new(pool::RequestPool::new(
+                    size,
+                    &self.engine,
+                    &pre,
+                    &new_state,
+                    &config,
+                    &limits,
+                    &self.metrics,
+                ))
+            });
+
+        Ok(PreparedWorker {
+            worker,
+            timers,
+            pool,
+        })
+    }
+
+    /// Make a prepared worker visible to the runtime, replacing any previous
+    /// worker with the same id.
+    async fn install_worker(&self, id: &str, loaded: &mut WorkerMap, prepared: PreparedWorker) {
+        let PreparedWorker {
+            worker,
+            timers,
+            pool,
+        } = prepared;
+
+        loaded.insert(id.to_owned(), Mutex::new(worker));
+
+        self.metrics.workers_loaded(loaded.len() as u64);
+
+        self.timers.write().await.insert(id.to_owned(), timers);
+
+        match pool {
+            Some(pool) => self.pools.write().await.insert(id.to_owned(), pool),
+            None => self.pools.write().await.remove(id),
+        };
     }
 
     pub async fn register_worker(
@@ -555,59 +2070,64 @@ impl Runtime {
         id: &str,
         wasm: &[u8],
         config: serde_json::Value,
+        options: WorkerOptions,
     ) -> Result<(), Error> {
-        let component = wasmtime::component::Component::new(&self.engine, wasm)?;
+        let cursor = self.store.get_worker_cursor(id).await?;
+        debug!(cursor, id, "found cursor for worker");
 
-        let mut wasm_store = wasmtime::Store::new(
-            &self.engine,
-            WorkerState {
-                worker_id: id.to_owned(),
-                router: Router::new(),
-                ledger: self
-                    .ledger
-                    .as_ref()
-                    .map(|l| LedgerHost::new(id, l, &self.metrics)),
-                logging: self
-                    .logging
-                    .as_ref()
-                    .map(|kv| LoggerHost::new(id, kv, &self.metrics)),
-                kv: self
-                    .kv
-                    .as_ref()
-                    .map(|kv| KvHost::new(id, kv, &self.metrics)),
-                sign: self
-                    .sign
-                    .as_ref()
-                    .map(|s| SignerHost::new(id, s, &self.metrics)),
-                submit: self
-                    .submit
-                    .as_ref()
-                    .map(|s| SubmitHost::new(id, s, &self.metrics)),
-                http: self.http.clone(),
-            },
-        );
+        let prepared = self
+            .prepare_worker(id, wasm, config, options, cursor)
+            .await?;
 
-        let instance =
-            wit::Worker::instantiate_async(&mut wasm_store, &component, &self.linker).await?;
+        let mut loaded = self.loaded.write().await;
+        self.install_worker(id, &mut loaded, prepared).await;
 
-        let config = serde_json::to_vec(&config).unwrap();
-        instance.call_init(&mut wasm_store, &config).await?;
+        Ok(())
+    }
+
+    /// Replace a loaded worker with a new version, keeping its cursor. The
+    /// options of the previous version don't carry over.
+    ///
+    /// The new version is fully instantiated (`init` included) before the
+    /// swap, so if anything fails the previous version keeps running
+    /// untouched. The swap waits for the block being processed, if any, so
+    /// the new version picks up chain events exactly where the old one left.
+    pub async fn upgrade_worker(
+        &self,
+        id: &str,
+        wasm: &[u8],
+        config: serde_json::Value,
+        options: WorkerOptions,
+    ) -> Result<(), Error> {
+        if !self.loaded.read().await.contains_key(id) {
+            return Err(Error::WorkerNotFound(id.to_owned()));
+        }
 
         let cursor = self.store.get_worker_cursor(id).await?;
-        debug!(cursor, id, "found cursor for worker");
+
+        let mut prepared = match self.prepare_worker(id, wasm, config, options, cursor).await {
+            Ok(x) => x,
+            Err(err) => {
+                warn!(worker = id, %err, "upgrade failed, keeping previous version");
+                return Err(err);
+            }
+        };
 
         let mut loaded = self.loaded.write().await;
-        loaded.insert(
-            id.to_owned(),
-            Mutex::new(LoadedWorker {
-                wasm_store,
-                instance,
-                cursor,
-                metrics: self.metrics.clone(),
-            }),
-        );
 
-        self.metrics.workers_loaded(loaded.len() as u64);
+        let previous = loaded
+            .get_mut(id)
+            .ok_or(Error::WorkerNotFound(id.to_owned()))?;
+
+        // resume from where the running version is, not from what was
+        // persisted when we started preparing the new one
+        prepared.worker.cursor = previous.get_mut().cursor;
+        prepared.worker.feed_ended = previous.get_mut().feed_ended;
+        prepared.worker.dead_letters = std::mem::take(&mut previous.get_mut().dead_letters);
+
+        self.install_worker(id, &mut loaded, prepared).await;
+
+        info!(worker = id, "upgraded worker");
 
         Ok(())
     }
@@ -621,10 +2141,11 @@ impl Runtime {
         id: &str,
         url: &url::Url,
         config: serde_json::Value,
+        options: WorkerOptions,
     ) -> Result<(), Error> {
         let (store, path) = object_store::parse_url(url)?;
         let bytes = store.get(&path).await?.bytes().await?;
-        self.register_worker(id, &bytes, config).await
+        self.register_worker(id, &bytes, config, options).await
     }
 
     pub async fn register_worker_from_file(
@@ -632,16 +2153,20 @@ impl Runtime {
         id: &str,
         wasm_path: impl AsRef<Path>,
         config: serde_json::Value,
+        options: WorkerOptions,
     ) -> Result<(), Error> {
         let mut file = std::fs::File::open(wasm_path)?;
         let mut buffer = Vec::new();
         file.read_to_end(&mut buffer)?;
-        self.register_worker(id, &buffer, config).await
+        self.register_worker(id, &buffer, config, options).await
     }
 
     pub async fn remove_worker(&self, id: &str) -> Result<(), Error> {
         let mut loaded = self.loaded.write().await;
         let removed = loaded.remove(id);
+        self.timers.write().await.remove(id);
+        self.pools.write().await.remove(id);
+        self.paused.write().await.remove(id);
 
         self.metrics.workers_loaded(loaded.len() as u64);
 
@@ -657,6 +2182,57 @@ impl Runtime {
         Ok(())
     }
 
+    async fn is_paused(&self, id: &str) -> bool {
+        self.paused.read().await.contains(id)
+    }
+
+    /// Stop delivering events to a worker, without unloading it. Requests to
+    /// a paused worker fail with [`Error::WorkerPaused`] and its cursor stays
+    /// where it was. Returns once the event being handled (if any) is done.
+    ///
+    /// The worker stays paused across upgrades, until resumed or removed.
+    pub async fn pause_worker(&self, id: &str) -> Result<(), Error> {
+        let workers = self.loaded.read().await;
+        let worker = workers
+            .get(id)
+            .ok_or(Error::WorkerNotFound(id.to_owned()))?;
+
+        self.paused.write().await.insert(id.to_owned());
+
+        // event handlers check the pause once they hold the worker
+        let _ = worker.lock().await;
+
+        info!(worker = id, "paused worker");
+        Ok(())
+    }
+
+    /// Lift the pause of a worker, as well as the halt after a failed event
+    /// (see [`FailurePolicy::Halt`]). Blocks logged in the meantime, starting
+    /// with the failed one for a halted worker, are delivered by the catch-up
+    /// driver (see [`Runtime::catch_up_worker`]) before the worker rejoins
+    /// the live feed.
+    pub async fn resume_worker(&self, id: &str) -> Result<(), Error> {
+        let workers = self.loaded.read().await;
+        let mut worker = workers
+            .get(id)
+            .ok_or(Error::WorkerNotFound(id.to_owned()))?
+            .lock()
+            .await;
+
+        if std::mem::take(&mut worker.halted) {
+            info!(worker = id, "lifted halt of worker");
+        }
+
+        // a pending retry is due right away
+        worker.retry = None;
+
+        if self.paused.write().await.remove(id) {
+            info!(worker = id, "resumed worker");
+        }
+
+        Ok(())
+    }
+
     pub async fn handle_chain(
         &mut self,
         undo_blocks: &Vec<Block>,
@@ -665,34 +2241,71 @@ impl Runtime {
         let start = Instant::now();
         info!("applying block");
 
-        let log_seq = self.store.write_ahead(undo_blocks, next_block).await?;
+        let (head, log_seq) = {
+            let _writes = self.store_writes.lock().await;
+            let head = self.store.last_log_seq().await?;
+            let log_seq = self.store.write_ahead(undo_blocks, next_block).await?;
+            (head, log_seq)
+        };
 
         let workers = self.loaded.read().await;
+        let catching_up = self.catching_up.read().await.clone();
 
-        let mut store_update = self.store.start_atomic_update(log_seq).await?;
+        type Update<'a> = (tokio::sync::MutexGuard<'a, LoadedWorker>, f64);
 
-        let update = async |worker: &Mutex<LoadedWorker>| -> Result<(String, f64), Error> {
-            let worker_start = Instant::now();
-            let mut lock = worker.lock().await;
-            lock.apply_chain(undo_blocks, next_block).await?;
+        let this = &*self;
+        let catching_up = &catching_up;
 
-            Ok((
-                lock.wasm_store.data().worker_id.clone(),
-                worker_start.elapsed().as_secs_f64() * 1000.0,
-            ))
-        };
-        let updates = workers.values().map(update).collect_vec();
+        let updates = workers
+            .iter()
+            .map(|(id, worker)| async move {
+                if catching_up.contains(id) {
+                    return Ok(None);
+                }
+
+                let worker_start = Instant::now();
+                let mut lock = worker.lock().await;
+
+                // checked once locked, see `pause_worker`
+                if this.paused.read().await.contains(id) {
+                    return Ok(None);
+                }
+
+                // workers behind the head are fed from the log instead, see
+                // `catch_up_worker`
+                if lock.halted || lock.cursor != head {
+                    return Ok(None);
+                }
 
-        for (x, duration) in join_all(updates)
+                match lock.apply_chain(&this.store, undo_blocks, next_block).await {
+                    // the cursor stays where it was
+                    Err(Error::WorkerHalted(_) | Error::RetryScheduled(_)) => return Ok(None),
+                    x => x?,
+                }
+                lock.cursor = Some(log_seq);
+
+                let duration = worker_start.elapsed().as_secs_f64() * 1000.0;
+
+                Ok::<Option<Update>, Error>(Some((lock, duration)))
+            })
+            .collect_vec();
+
+        // workers stay locked until their KV writes reach the provider
+        let mut updated = join_all(updates)
             .await
             .into_iter()
-            .collect::<Result<Vec<(String, f64)>, _>>()?
-        {
-            self.metrics.handle_worker_chain_duration_ms(&x, duration);
-            store_update.update_worker_cursor(&x).await?;
+            .collect::<Result<Vec<Option<Update>>, _>>()?
+            .into_iter()
+            .flatten()
+            .collect_vec();
+
+        for (lock, duration) in updated.iter() {
+            self.metrics
+                .handle_worker_chain_duration_ms(&lock.wasm_store.data().worker_id, *duration);
         }
 
-        store_update.commit().await?;
+        let updated = updated.iter_mut().map(|(lock, _)| &mut **lock).collect();
+        self.commit_chain_progress(log_seq, updated).await?;
 
         self.metrics
             .handle_chain_duration_ms(start.elapsed().as_secs_f64() * 1000.0);
@@ -702,13 +2315,32 @@ impl Runtime {
         Ok(())
     }
 
-    pub async fn handle_request(
+    /// List the timer channels registered by every loaded worker, along
+    /// with the raw interval each of them requested.
+    ///
+    /// Timer routes are captured when the worker is registered, so this
+    /// doesn't need to wait for workers busy handling other events.
+    pub async fn timer_channels(&self) -> Vec<(WorkerId, u32, String)> {
+        self.timers
+            .read()
+            .await
+            .iter()
+            .flat_map(|(id, timers)| {
+                timers
+                    .iter()
+                    .map(|(channel, interval)| (id.clone(), *channel, interval.clone()))
+            })
+            .collect()
+    }
+
+    /// Dispatch a timer event fired at `timestamp` (unix milliseconds) into
+    /// the given channel of a worker.
+    pub async fn handle_timer(
         &self,
         worker_id: &str,
-        method: &str,
-        params: Vec<u8>,
-    ) -> Result<wit::Response, Error> {
-        let start = Instant::now();
+        channel: u32,
+        timestamp: u64,
+    ) -> Result<(), Error> {
         let workers = self.loaded.read().await;
         let mut worker = workers
             .get(worker_id)
@@ -716,15 +2348,110 @@ impl Runtime {
             .lock()
             .await;
 
-        let channel = worker
-            .wasm_store
-            .data()
-            .router
-            .find_request_target(method)?;
+        if worker.halted {
+            debug!(worker = worker_id, "skipping timer of halted worker");
+            return Ok(());
+        }
 
-        let evt = wit::Event::Request(params);
+        if self.is_paused(worker_id).await {
+            debug!(worker = worker_id, "skipping timer of paused worker");
+            return Ok(());
+        }
+
+        let evt = wit::Event::Timer(timestamp);
+
+        self.metrics.timer_handled(worker_id);
+        worker.acknowledge_event(channel, &evt).await?;
+
+        self.flush_dead_letters(&mut worker).await
+    }
+
+    /// Queue a message into the broadcast bus, as if it was published by an
+    /// external source (eg: a message broker).
+    pub fn publish_message(&self, topic: &str, payload: Vec<u8>) -> Result<(), Error> {
+        let bus = self
+            .broadcast
+            .as_ref()
+            .ok_or(Error::Config("broadcast is not enabled".to_string()))?;
+
+        bus.publish(broadcast::Message {
+            source: None,
+            topic: topic.to_owned(),
+            payload,
+        })
+    }
+
+    /// Deliver a broadcast message to every worker subscribed to its topic.
+    /// Failures are logged per worker so that one worker can't prevent the
+    /// others from receiving the message.
+    pub async fn handle_message(&self, message: &broadcast::Message) {
+        let workers = self.loaded.read().await;
+
+        for (id, worker) in workers.iter() {
+            let mut worker = worker.lock().await;
+
+            if worker.halted || self.is_paused(id).await {
+                continue;
+            }
+
+            let channels = worker
+                .wasm_store
+                .data()
+                .router
+                .find_message_targets(&message.topic);
+
+            let evt = wit::Event::Message(message.payload.clone());
+
+            for channel in channels {
+                self.metrics.message_handled(id);
+                if let Err(err) = worker.acknowledge_event(channel, &evt).await {
+                    warn!(worker = id, topic = message.topic, %err, "failed to deliver message");
+                }
+            }
+
+            if let Err(err) = self.flush_dead_letters(&mut worker).await {
+                warn!(worker = id, %err, "failed to save dead letters");
+            }
+        }
+    }
+
+    pub async fn handle_request(
+        &self,
+        worker_id: &str,
+        method: &str,
+        params: Vec<u8>,
+    ) -> Result<wit::Response, Error> {
+        if self.is_paused(worker_id).await {
+            return Err(Error::WorkerPaused(worker_id.to_owned()));
+        }
+
+        let start = Instant::now();
+        let pool = self.pools.read().await.get(worker_id).cloned();
+
+        let result = match pool {
+            Some(pool) => {
+                let mut worker = pool.acquire().await?;
+                let result = worker.handle_request(method, params).await;
+
+                // an unexpected trap leaves the instance unusable
+                if matches!(result, Err(Error::Wasm(_))) {
+                    worker.discard();
+                }
+
+                result
+            }
+            None => {
+                let workers = self.loaded.read().await;
+                let mut worker = workers
+                    .get(worker_id)
+                    .ok_or(Error::WorkerNotFound(worker_id.to_string()))?
+                    .lock()
+                    .await;
+
+                worker.handle_request(method, params).await
+            }
+        };
 
-        let result = worker.dispatch_event(channel, &evt).await;
         self.metrics.request(worker_id, method, result.is_ok());
         let duration_ms = start.elapsed().as_secs_f64() * 1000.0;
         self.metrics
@@ -740,129 +2467,164 @@ impl<T: 'static> wasmtime::component::HasData for HasField<T> {
 
 pub struct RuntimeBuilder {
     store: store::Store,
-    engine: wasmtime::Engine,
-    linker: wasmtime::component::Linker<WorkerState>,
+    config: wasmtime::Config,
+    compile_cache: Option<PathBuf>,
     ledger: Option<ledgers::Ledger>,
     logging: Option<logging::Logger>,
     kv: Option<kv::Kv>,
     sign: Option<sign::Signer>,
     submit: Option<submit::Submit>,
     http: Option<http::Http>,
+    broadcast: Option<broadcast::Broadcast>,
 }
 
 impl RuntimeBuilder {
     pub fn new(store: store::Store) -> Self {
         let mut config = wasmtime::Config::new();
         config.async_support(true);
-        let engine = wasmtime::Engine::new(&config).unwrap();
-        let mut linker = wasmtime::component::Linker::new(&engine);
-
-        wit::balius::app::driver::add_to_linker::<_, HasSelf<_>>(
-            &mut linker,
-            |state: &mut WorkerState| state,
-        )
-        .unwrap();
+        config.consume_fuel(true);
 
         Self {
             store,
-            engine,
-            linker,
+            config,
+            compile_cache: None,
             ledger: None,
             logging: None,
             kv: None,
             sign: None,
             submit: None,
             http: None,
+            broadcast: None,
         }
     }
 
-    pub fn with_ledger(mut self, ledger: ledgers::Ledger) -> Self {
-        self.ledger = Some(ledger);
+    /// Back worker instances with wasmtime's pooling allocator, which makes
+    /// instantiation cheap enough to keep pools of request instances.
+    pub fn with_pooling_allocator(mut self, pooling: &PoolingConfig) -> Self {
+        self.config
+            .allocation_strategy(wasmtime::InstanceAllocationStrategy::Pooling(
+                pooling.into(),
+            ));
 
-        wit::balius::app::ledger::add_to_linker::<_, HasField<_>>(
-            &mut self.linker,
-            |state: &mut WorkerState| state.ledger.as_mut().unwrap(),
-        )
-        .unwrap();
+        self
+    }
+
+    /// Keep compiled components in `dir`, so that registering a worker
+    /// already seen by a compatible engine skips compilation.
+    pub fn with_compile_cache(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.compile_cache = Some(dir.into());
+        self
+    }
 
+    pub fn with_ledger(mut self, ledger: ledgers::Ledger) -> Self {
+        self.ledger = Some(ledger);
         self
     }
 
     pub fn with_kv(mut self, kv: kv::Kv) -> Self {
-        wit::balius::app::kv::add_to_linker::<_, HasField<_>>(
-            &mut self.linker,
-            |state: &mut WorkerState| state.kv.as_mut().unwrap(),
-        )
-        .unwrap();
         self.kv = Some(kv);
-
         self
     }
 
     pub fn with_logger(mut self, logging: logging::Logger) -> Self {
         self.logging = Some(logging);
-
-        wit::balius::app::logging::add_to_linker::<_, HasField<_>>(
-            &mut self.linker,
-            |state: &mut WorkerState| state.logging.as_mut().unwrap(),
-        )
-        .unwrap();
-
         self
     }
 
     pub fn with_signer(mut self, sign: sign::Signer) -> Self {
         self.sign = Some(sign);
-
-        wit::balius::app::sign::add_to_linker::<_, HasField<_>>(
-            &mut self.linker,
-            |state: &mut WorkerState| state.sign.as_mut().unwrap(),
-        )
-        .unwrap();
-
         self
     }
 
     pub fn with_submit(mut self, submit: submit::Submit) -> Self {
         self.submit = Some(submit);
-
-        wit::balius::app::submit::add_to_linker::<_, HasField<_>>(
-            &mut self.linker,
-            |state: &mut WorkerState| state.submit.as_mut().unwrap(),
-        )
-        .unwrap();
-
         self
     }
 
     pub fn with_http(mut self, http: http::Http) -> Self {
         self.http = Some(http);
-        wit::balius::app::http::add_to_linker::<_, HasField<_>>(
-            &mut self.linker,
-            |state: &mut WorkerState| state.http.as_mut().unwrap(),
-        )
-        .unwrap();
+        self
+    }
 
+    pub fn with_broadcast(mut self, broadcast: broadcast::Broadcast) -> Self {
+        self.broadcast = Some(broadcast);
         self
     }
 
     pub fn build(self) -> Result<Runtime, Error> {
-        let mut this = self;
-        if this.logging.is_none() {
-            this = this.with_logger(logging::Logger::Silent);
-        }
-
         let RuntimeBuilder {
             store,
-            engine,
-            linker,
+            config,
+            compile_cache,
             ledger,
             logging,
             kv,
             sign,
             submit,
             http,
-        } = this;
+            broadcast,
+        } = self;
+
+        let compile_cache = compile_cache.map(cache::ComponentCache::new).transpose()?;
+
+        let logging = logging.or(Some(logging::Logger::Silent));
+
+        let engine = wasmtime::Engine::new(&config)?;
+        let mut linker = wasmtime::component::Linker::new(&engine);
+
+        wit::balius::app::driver::add_to_linker::<_, HasSelf<_>>(
+            &mut linker,
+            |state: &mut WorkerState| state,
+        )?;
+
+        if ledger.is_some() {
+            wit::balius::app::ledger::add_to_linker::<_, HasField<_>>(
+                &mut linker,
+                |state: &mut WorkerState| state.ledger.as_mut().unwrap(),
+            )?;
+        }
+
+        if kv.is_some() {
+            wit::balius::app::kv::add_to_linker::<_, HasField<_>>(
+                &mut linker,
+                |state: &mut WorkerState| state.kv.as_mut().unwrap(),
+            )?;
+        }
+
+        if logging.is_some() {
+            wit::balius::app::logging::add_to_linker::<_, HasField<_>>(
+                &mut linker,
+                |state: &mut WorkerState| state.logging.as_mut().unwrap(),
+            )?;
+        }
+
+        if sign.is_some() {
+            wit::balius::app::sign::add_to_linker::<_, HasField<_>>(
+                &mut linker,
+                |state: &mut WorkerState| state.sign.as_mut().unwrap(),
+            )?;
+        }
+
+        if submit.is_some() {
+            wit::balius::app::submit::add_to_linker::<_, HasField<_>>(
+                &mut linker,
+                |state: &mut WorkerState| state.submit.as_mut().unwrap(),
+            )?;
+        }
+
+        if http.is_some() {
+            wit::balius::app::http::add_to_linker::<_, HasField<_>>(
+                &mut linker,
+                |state: &mut WorkerState| state.http.as_mut().unwrap(),
+            )?;
+        }
+
+        if broadcast.is_some() {
+            wit::balius::app::broadcast::add_to_linker::<_, HasField<_>>(
+                &mut linker,
+                |state: &mut WorkerState| state.broadcast.as_mut().unwrap(),
+            )?;
+        }
 
         let metrics: Arc<metrics::Metrics> = Default::default();
         metrics.workers_loaded(0);
@@ -870,6 +2632,12 @@ impl RuntimeBuilder {
         Ok(Runtime {
             metrics,
             loaded: Default::default(),
+            timers: Default::default(),
+            catching_up: Default::default(),
+            paused: Default::default(),
+            store_writes: Default::default(),
+            pools: Default::default(),
+            compile_cache,
             engine,
             linker,
             store,
@@ -879,6 +2647,7 @@ impl RuntimeBuilder {
             sign,
             submit,
             http,
+            broadcast,
         })
     }
 }
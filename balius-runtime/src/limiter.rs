@@ -0,0 +1,210 @@
+//! Per-store resource limiter enforcing the [`WorkerLimits`] of a worker.
+//!
+//! Growth requests over the limit are rejected with a trap, so a runaway
+//! worker fails its current call instead of taking the whole daemon down.
+//! The breach is recorded in the limiter so the runtime can surface it as
+//! [`Error::ResourceLimit`](crate::Error::ResourceLimit) once the call
+//! returns.
+
+use std::sync::Arc;
+
+use crate::{metrics::Metrics, WorkerLimits};
+
+/// Matches the default of `wasmtime::ResourceLimiter::instances`.
+const DEFAULT_MAX_INSTANCES: usize = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resource {
+    Memory,
+    Table,
+    Instances,
+}
+
+impl Resource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Resource::Memory => "memory",
+            Resource::Table => "table",
+            Resource::Instances => "instances",
+        }
+    }
+}
+
+impl std::fmt::Display for Resource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A limit breach, as `(resource, limit)`.
+pub type Breach = (Resource, usize);
+
+/// Count the core wasm instances created when instantiating a component,
+/// which is what the instance cap applies to.
+pub fn core_instances(wasm: &[u8]) -> wasmtime::Result<usize> {
+    let mut count = 0;
+
+    for payload in wasmparser::Parser::new(0).parse_all(wasm) {
+        if let wasmparser::Payload::InstanceSection(reader) = payload? {
+            for instance in reader {
+                if let wasmparser::Instance::Instantiate { .. } = instance? {
+                    count += 1;
+                }
+            }
+        }
+    }
+
+    Ok(count)
+}
+
+pub struct WorkerLimiter {
+    worker_id: String,
+    limits: WorkerLimits,
+    core_instances: usize,
+    metrics: Arc<Metrics>,
+    breach: Option<Breach>,
+}
+
+impl WorkerLimiter {
+    /// `core_instances` is the number of instances the worker component
+    /// creates, see [`core_instances`].
+    pub fn new(
+        worker_id: &str,
+        limits: &WorkerLimits,
+        core_instances: usize,
+        metrics: &Arc<Metrics>,
+    ) -> Self {
+        Self {
+            worker_id: worker_id.to_owned(),
+            limits: limits.clone(),
+            core_instances,
+            metrics: metrics.clone(),
+            breach: None,
+        }
+    }
+
+    /// Take the breach recorded since the last call, if any.
+    pub fn take_breach(&mut self) -> Option<Breach> {
+        self.breach.take()
+    }
+
+    /// Check the instance cap before instantiating the worker.
+    ///
+    /// wasmtime enforces the cap on its own, without going through the
+    /// limiter hooks, and fails with an untyped error. Denying here instead
+    /// records the breach like any other growth request over the limit.
+    pub fn instantiating(&mut self) -> Result<(), Breach> {
+        match self.check(
+            Resource::Instances,
+            self.limits.instances,
+            self.core_instances,
+        ) {
+            Ok(_) => Ok(()),
+            Err(_) => Err(self.take_breach().expect("breach was just recorded")),
+        }
+    }
+
+    fn record(&mut self, resource: Resource, limit: usize) {
+        self.metrics
+            .resource_limit_exceeded(&self.worker_id, resource.as_str());
+        self.breach = Some((resource, limit));
+    }
+
+    fn check(
+        &mut self,
+        resource: Resource,
+        limit: Option<usize>,
+        desired: usize,
+    ) -> wasmtime::Result<bool> {
+        match limit {
+            Some(limit) if desired > limit => {
+                self.record(resource, limit);
+                Err(wasmtime::Error::msg(format!(
+                    "worker exceeded its {resource} limit ({limit})"
+                )))
+            }
+            _ => Ok(true),
+        }
+    }
+}
+
+impl wasmtime::ResourceLimiter for WorkerLimiter {
+    fn memory_growing(
+        &mut self,
+        _current: usize,
+        desired: usize,
+        _maximum: Option<usize>,
+    ) -> wasmtime::Result<bool> {
+        self.check(Resource::Memory, self.limits.memory, desired)
+    }
+
+    fn table_growing(
+        &mut self,
+        _current: usize,
+        desired: usize,
+        _maximum: Option<usize>,
+    ) -> wasmtime::Result<bool> {
+        self.check(Resource::Table, self.limits.table_elements, desired)
+    }
+
+    fn instances(&self) -> usize {
+        self.limits.instances.unwrap_or(DEFAULT_MAX_INSTANCES)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wasmtime::ResourceLimiter as _;
+
+    use super::*;
+
+    #[test]
+    fn rejects_growth_over_limit() {
+        let limits = WorkerLimits {
+            memory: Some(2 * 65536),
+            ..Default::default()
+        };
+        let mut limiter = WorkerLimiter::new("a", &limits, 1, &Arc::new(Metrics::new()));
+
+        assert!(limiter.memory_growing(0, 2 * 65536, None).unwrap());
+        assert_eq!(limiter.take_breach(), None);
+
+        assert!(limiter.memory_growing(2 * 65536, 3 * 65536, None).is_err());
+        assert_eq!(limiter.take_breach(), Some((Resource::Memory, 2 * 65536)));
+
+        // tables are unlimited
+        assert!(limiter.table_growing(0, 1_000_000, None).unwrap());
+        assert_eq!(limiter.take_breach(), None);
+    }
+
+    #[test]
+    fn rejects_instantiation_over_instance_cap() {
+        let limits = WorkerLimits {
+            instances: Some(2),
+            ..Default::default()
+        };
+        let metrics = Arc::new(Metrics::new());
+
+        let mut limiter = WorkerLimiter::new("a", &limits, 2, &metrics);
+        assert_eq!(limiter.instantiating(), Ok(()));
+
+        let mut limiter = WorkerLimiter::new("a", &limits, 3, &metrics);
+        assert_eq!(limiter.instantiating(), Err((Resource::Instances, 2)));
+        assert_eq!(limiter.take_breach(), None);
+    }
+
+    #[test]
+    fn counts_core_instances_of_component() {
+        let component = wat::parse_str(
+            r#"(component
+                (core module $m)
+                (core instance (instantiate $m))
+                (core instance (instantiate $m))
+                (core instance)
+            )"#,
+        )
+        .unwrap();
+
+        assert_eq!(core_instances(&component).unwrap(), 2);
+    }
+}
@@ -12,11 +12,31 @@ pub struct Metrics {
     kv_get: Counter<u64>,
     kv_set: Counter<u64>,
     kv_list: Counter<u64>,
+    kv_delete: Counter<u64>,
+    kv_compare_and_swap: Counter<u64>,
     log: Counter<u64>,
     utxo_handled: Counter<u64>,
     tx_handled: Counter<u64>,
     undo_utxo_handled: Counter<u64>,
     undo_tx_handled: Counter<u64>,
+    utxo_spent_handled: Counter<u64>,
+    undo_utxo_spent_handled: Counter<u64>,
+    mint_handled: Counter<u64>,
+    undo_mint_handled: Counter<u64>,
+    certificate_handled: Counter<u64>,
+    undo_certificate_handled: Counter<u64>,
+    proposal_handled: Counter<u64>,
+    undo_proposal_handled: Counter<u64>,
+    metadata_handled: Counter<u64>,
+    undo_metadata_handled: Counter<u64>,
+    block_handled: Counter<u64>,
+    undo_block_handled: Counter<u64>,
+    fuel_consumed: Counter<u64>,
+    resource_limit_exceeded: Counter<u64>,
+    handler_failure: Counter<u64>,
+    timer_handled: Counter<u64>,
+    message_handled: Counter<u64>,
+    broadcast_publish: Counter<u64>,
     submit_tx: Counter<u64>,
     signer_sign_payload: Counter<u64>,
     ledger_read_utxos: Counter<u64>,
@@ -54,6 +74,16 @@ impl Metrics {
             .with_description("Total amount of kv list calls.")
             .build();
 
+        let kv_delete = meter
+            .u64_counter("kv_delete")
+            .with_description("Total amount of kv delete calls.")
+            .build();
+
+        let kv_compare_and_swap = meter
+            .u64_counter("kv_compare_and_swap")
+            .with_description("Total amount of kv compare-and-swap calls.")
+            .build();
+
         let log = meter
             .u64_counter("log")
             .with_description("Total amount of log lines written.")
@@ -79,6 +109,96 @@ impl Metrics {
             .with_description("Amount of undo Tx event handled per worker.")
             .build();
 
+        let utxo_spent_handled = meter
+            .u64_counter("utxo_spent_handled")
+            .with_description("Amount of spent UTxO event handled per worker.")
+            .build();
+
+        let undo_utxo_spent_handled = meter
+            .u64_counter("undo_utxo_spent_handled")
+            .with_description("Amount of undo spent UTxO event handled per worker.")
+            .build();
+
+        let mint_handled = meter
+            .u64_counter("mint_handled")
+            .with_description("Amount of mint event handled per worker.")
+            .build();
+
+        let undo_mint_handled = meter
+            .u64_counter("undo_mint_handled")
+            .with_description("Amount of undo mint event handled per worker.")
+            .build();
+
+        let certificate_handled = meter
+            .u64_counter("certificate_handled")
+            .with_description("Amount of certificate event handled per worker.")
+            .build();
+
+        let undo_certificate_handled = meter
+            .u64_counter("undo_certificate_handled")
+            .with_description("Amount of undo certificate event handled per worker.")
+            .build();
+
+        let proposal_handled = meter
+            .u64_counter("proposal_handled")
+            .with_description("Amount of governance proposal event handled per worker.")
+            .build();
+
+        let undo_proposal_handled = meter
+            .u64_counter("undo_proposal_handled")
+            .with_description("Amount of undo governance proposal event handled per worker.")
+            .build();
+
+        let metadata_handled = meter
+            .u64_counter("metadata_handled")
+            .with_description("Amount of metadata event handled per worker.")
+            .build();
+
+        let undo_metadata_handled = meter
+            .u64_counter("undo_metadata_handled")
+            .with_description("Amount of undo metadata event handled per worker.")
+            .build();
+
+        let block_handled = meter
+            .u64_counter("block_handled")
+            .with_description("Amount of block event handled per worker.")
+            .build();
+
+        let undo_block_handled = meter
+            .u64_counter("undo_block_handled")
+            .with_description("Amount of undo block event handled per worker.")
+            .build();
+
+        let fuel_consumed = meter
+            .u64_counter("fuel_consumed")
+            .with_description("Amount of wasm fuel consumed per worker.")
+            .build();
+
+        let resource_limit_exceeded = meter
+            .u64_counter("resource_limit_exceeded")
+            .with_description("Amount of resource limit breaches per worker.")
+            .build();
+
+        let handler_failure = meter
+            .u64_counter("handler_failure")
+            .with_description("Amount of events workers failed to handle, per action taken.")
+            .build();
+
+        let timer_handled = meter
+            .u64_counter("timer_handled")
+            .with_description("Amount of timer event handled per worker.")
+            .build();
+
+        let message_handled = meter
+            .u64_counter("message_handled")
+            .with_description("Amount of broadcast message event handled per worker.")
+            .build();
+
+        let broadcast_publish = meter
+            .u64_counter("broadcast_publish")
+            .with_description("Amount of broadcast messages published per worker.")
+            .build();
+
         let submit_tx = meter
             .u64_counter("submit_tx")
             .with_description("Amount of submit_tx calls per worker.")
@@ -151,11 +271,31 @@ impl Metrics {
             kv_get,
             kv_set,
             kv_list,
+            kv_delete,
+            kv_compare_and_swap,
             log,
             utxo_handled,
             tx_handled,
             undo_utxo_handled,
             undo_tx_handled,
+            utxo_spent_handled,
+            undo_utxo_spent_handled,
+            mint_handled,
+            undo_mint_handled,
+            certificate_handled,
+            undo_certificate_handled,
+            proposal_handled,
+            undo_proposal_handled,
+            metadata_handled,
+            undo_metadata_handled,
+            block_handled,
+            undo_block_handled,
+            fuel_consumed,
+            resource_limit_exceeded,
+            handler_failure,
+            timer_handled,
+            message_handled,
+            broadcast_publish,
             submit_tx,
             signer_sign_payload,
             ledger_read_utxos,
@@ -196,6 +336,16 @@ impl Metrics {
             .add(1, &[KeyValue::new("worker", worker_id.to_owned())]);
     }
 
+    pub fn kv_delete(&self, worker_id: &str) {
+        self.kv_delete
+            .add(1, &[KeyValue::new("worker", worker_id.to_owned())]);
+    }
+
+    pub fn kv_compare_and_swap(&self, worker_id: &str) {
+        self.kv_compare_and_swap
+            .add(1, &[KeyValue::new("worker", worker_id.to_owned())]);
+    }
+
     pub fn log(&self, worker_id: &str, level: &Level) {
         self.log.add(
             1,
@@ -226,6 +376,111 @@ impl Metrics {
             .add(1, &[KeyValue::new("worker", worker_id.to_owned())]);
     }
 
+    pub fn utxo_spent_handled(&self, worker_id: &str) {
+        self.utxo_spent_handled
+            .add(1, &[KeyValue::new("worker", worker_id.to_owned())]);
+    }
+
+    pub fn undo_utxo_spent_handled(&self, worker_id: &str) {
+        self.undo_utxo_spent_handled
+            .add(1, &[KeyValue::new("worker", worker_id.to_owned())]);
+    }
+
+    pub fn mint_handled(&self, worker_id: &str) {
+        self.mint_handled
+            .add(1, &[KeyValue::new("worker", worker_id.to_owned())]);
+    }
+
+    pub fn undo_mint_handled(&self, worker_id: &str) {
+        self.undo_mint_handled
+            .add(1, &[KeyValue::new("worker", worker_id.to_owned())]);
+    }
+
+    pub fn certificate_handled(&self, worker_id: &str) {
+        self.certificate_handled
+            .add(1, &[KeyValue::new("worker", worker_id.to_owned())]);
+    }
+
+    pub fn undo_certificate_handled(&self, worker_id: &str) {
+        self.undo_certificate_handled
+            .add(1, &[KeyValue::new("worker", worker_id.to_owned())]);
+    }
+
+    pub fn proposal_handled(&self, worker_id: &str) {
+        self.proposal_handled
+            .add(1, &[KeyValue::new("worker", worker_id.to_owned())]);
+    }
+
+    pub fn undo_proposal_handled(&self, worker_id: &str) {
+        self.undo_proposal_handled
+            .add(1, &[KeyValue::new("worker", worker_id.to_owned())]);
+    }
+
+    pub fn metadata_handled(&self, worker_id: &str) {
+        self.metadata_handled
+            .add(1, &[KeyValue::new("worker", worker_id.to_owned())]);
+    }
+
+    pub fn undo_metadata_handled(&self, worker_id: &str) {
+        self.undo_metadata_handled
+            .add(1, &[KeyValue::new("worker", worker_id.to_owned())]);
+    }
+
+    pub fn block_handled(&self, worker_id: &str) {
+        self.block_handled
+            .add(1, &[KeyValue::new("worker", worker_id.to_owned())]);
+    }
+
+    pub fn undo_block_handled(&self, worker_id: &str) {
+        self.undo_block_handled
+            .add(1, &[KeyValue::new("worker", worker_id.to_owned())]);
+    }
+
+    pub fn fuel_consumed(&self, worker_id: &str, fuel: u64) {
+        self.fuel_consumed
+            .add(fuel, &[KeyValue::new("worker", worker_id.to_owned())]);
+    }
+
+    pub fn handler_failure(&self, worker_id: &str, action: &str) {
+        self.handler_failure.add(
+            1,
+            &[
+                KeyValue::new("worker", worker_id.to_owned()),
+                KeyValue::new("action", action.to_owned()),
+            ],
+        );
+    }
+
+    pub fn resource_limit_exceeded(&self, worker_id: &str, resource: &str) {
+        self.resource_limit_exceeded.add(
+            1,
+            &[
+                KeyValue::new("worker", worker_id.to_owned()),
+                KeyValue::new("resource", resource.to_owned()),
+            ],
+        );
+    }
+
+    pub fn timer_handled(&self, worker_id: &str) {
+        self.timer_handled
+            .add(1, &[KeyValue::new("worker", worker_id.to_owned())]);
+    }
+
+    pub fn message_handled(&self, worker_id: &str) {
+        self.message_handled
+            .add(1, &[KeyValue::new("worker", worker_id.to_owned())]);
+    }
+
+    pub fn broadcast_publish(&self, worker_id: &str, topic: &str) {
+        self.broadcast_publish.add(
+            1,
+            &[
+                KeyValue::new("worker", worker_id.to_owned()),
+                KeyValue::new("topic", topic.to_owned()),
+            ],
+        );
+    }
+
     pub fn submit_tx(&self, worker_id: &str) {
         self.submit_tx
             .add(1, &[KeyValue::new("worker", worker_id.to_owned())]);
@@ -0,0 +1,140 @@
+//! Pool of worker instances dedicated to serving requests.
+//!
+//! The instance handling chain events is shared by every event of the
+//! worker, so requests routed through it are served one at a time. A pool
+//! keeps extra instances of the same component, created from its
+//! `InstancePre`, that serve requests concurrently. Pooled instances don't
+//! share in-memory state with the chain instance (or with each other), so
+//! only workers whose request handlers rely on host state (kv, ledger, etc)
+//! should enable it.
+//!
+//! Every pooled instance runs the `init` of the worker, since that's where
+//! it registers its handlers. To keep that from repeating side effects,
+//! pooled instances are read-only: KV writes fail, both from `init` and from
+//! request handlers, so a worker whose `init` writes to the KV can't be
+//! pooled.
+
+use std::{
+    ops::{Deref, DerefMut},
+    sync::Arc,
+};
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::{metrics::Metrics, wit, Error, LoadedWorker, StateFactory, WorkerLimits, WorkerState};
+
+pub(crate) struct RequestPool {
+    engine: wasmtime::Engine,
+    pre: wit::WorkerPre<WorkerState>,
+    new_state: StateFactory,
+    config: Vec<u8>,
+    limits: WorkerLimits,
+    metrics: Arc<Metrics>,
+    idle: Arc<std::sync::Mutex<Vec<LoadedWorker>>>,
+    permits: Arc<Semaphore>,
+}
+
+impl RequestPool {
+    pub fn new(
+        size: usize,
+        engine: &wasmtime::Engine,
+        pre: &wit::WorkerPre<WorkerState>,
+        new_state: &StateFactory,
+        config: &[u8],
+        limits: &WorkerLimits,
+        metrics: &Arc<Metrics>,
+    ) -> Self {
+        let new_state = new_state.clone();
+
+        Self {
+            engine: engine.clone(),
+            pre: pre.clone(),
+            new_state: Arc::new(move || {
+                let mut state = new_state();
+
+                if let Some(kv) = state.kv.as_mut() {
+                    kv.set_read_only();
+                }
+
+                state
+            }),
+            config: config.to_vec(),
+            limits: limits.clone(),
+            metrics: metrics.clone(),
+            idle: Default::default(),
+            permits: Arc::new(Semaphore::new(size)),
+        }
+    }
+
+    /// Take an instance out of the pool, waiting if all of them are busy.
+    /// Instances are created lazily, up to the size of the pool.
+    pub async fn acquire(&self) -> Result<PooledWorker, Error> {
+        let permit = self
+            .permits
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(|_| Error::Driver("request pool is closed".to_string()))?;
+
+        let idle = self.idle.lock().unwrap().pop();
+
+        let worker = match idle {
+            Some(worker) => worker,
+            None => {
+                LoadedWorker::load(
+                    &self.engine,
+                    &self.pre,
+                    &self.new_state,
+                    &self.config,
+                    &self.limits,
+                    &self.metrics,
+                    None,
+                )
+                .await?
+            }
+        };
+
+        Ok(PooledWorker {
+            worker: Some(worker),
+            idle: self.idle.clone(),
+            _permit: permit,
+        })
+    }
+}
+
+/// An instance borrowed from a [`RequestPool`], returned to it on drop.
+pub(crate) struct PooledWorker {
+    worker: Option<LoadedWorker>,
+    idle: Arc<std::sync::Mutex<Vec<LoadedWorker>>>,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl PooledWorker {
+    /// Drop the instance instead of returning it to the pool, eg: after a
+    /// trap left it unusable.
+    pub fn discard(mut self) {
+        self.worker = None;
+    }
+}
+
+impl Deref for PooledWorker {
+    type Target = LoadedWorker;
+
+    fn deref(&self) -> &Self::Target {
+        self.worker.as_ref().unwrap()
+    }
+}
+
+impl DerefMut for PooledWorker {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.worker.as_mut().unwrap()
+    }
+}
+
+impl Drop for PooledWorker {
+    fn drop(&mut self) {
+        if let Some(worker) = self.worker.take() {
+            self.idle.lock().unwrap().push(worker);
+        }
+    }
+}
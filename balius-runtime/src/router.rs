@@ -1,40 +1,228 @@
 use std::collections::{HashMap, HashSet};
 
+use pallas::ledger::addresses::Address;
+
 use crate::{
-    wit::balius::app::driver::{EventPattern, UtxoPattern},
-    Tx, Utxo,
+    wit::balius::app::driver::{
+        AddressPattern, CertificateKind, EventPattern, GovernanceActionKind, TokenPattern,
+        UtxoPattern,
+    },
+    Certificate, Proposal, Tx, TxInput, Utxo,
 };
 
 type ChannelId = u32;
 type Method = String;
 type AddressBytes = Vec<u8>;
+type CredentialHash = Vec<u8>;
+type PolicyId = Vec<u8>;
+type AssetName = Vec<u8>;
+type TimerInterval = String;
+type Topic = String;
+type MetadataLabel = u64;
+
+/// The different ways the address of an output can be matched.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum AddressKey {
+    Exact(AddressBytes),
+    PaymentCredential(CredentialHash),
+    StakeCredential(CredentialHash),
+}
+
+/// The different ways an output (either a UTxO or the inputs / outputs of a
+/// Tx) can be matched.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum UtxoKey {
+    Every,
+    Address(AddressKey),
+    Policy(PolicyId),
+    Asset(PolicyId, AssetName),
+    AddressPolicy(AddressKey, PolicyId),
+    AddressAsset(AddressKey, PolicyId, AssetName),
+}
+
+fn infer_address_key(pattern: &AddressPattern) -> AddressKey {
+    match pattern {
+        AddressPattern::Exact(x) => AddressKey::Exact(x.to_vec()),
+        AddressPattern::PaymentCredential(x) => AddressKey::PaymentCredential(x.to_vec()),
+        AddressPattern::StakeCredential(x) => AddressKey::StakeCredential(x.to_vec()),
+    }
+}
+
+impl std::hash::Hash for CertificateKind {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        (*self as u8).hash(state)
+    }
+}
+
+impl std::hash::Hash for GovernanceActionKind {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        (*self as u8).hash(state)
+    }
+}
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 enum MatchKey {
     RequestMethod(Method),
-    EveryUtxo,
-    UtxoAddress(AddressBytes),
-    EveryTx,
-    TxAddress(AddressBytes),
+    Utxo(UtxoKey),
+    UtxoUndo(UtxoKey),
+    UtxoSpent(UtxoKey),
+    UtxoSpentUndo(UtxoKey),
+    Tx(UtxoKey),
+    TxUndo(UtxoKey),
+    Mint(UtxoKey),
+    Burn(UtxoKey),
+    Certificate(CertificateKind),
+    Proposal(GovernanceActionKind),
+    Metadata(MetadataLabel),
+    Block,
+    BlockUndo,
+    BlockBegin,
+    BlockEnd,
+    Timer(TimerInterval),
+    Message(Topic),
+}
+
+fn infer_token_key(pattern: &TokenPattern) -> UtxoKey {
+    match &pattern.name {
+        None => UtxoKey::Policy(pattern.policy.to_vec()),
+        Some(name) => UtxoKey::Asset(pattern.policy.to_vec(), name.to_vec()),
+    }
+}
+
+fn infer_utxo_key(pattern: &UtxoPattern) -> UtxoKey {
+    let UtxoPattern { address, token } = pattern;
+    let address = address.as_ref().map(infer_address_key);
+
+    match (address, token) {
+        (None, None) => UtxoKey::Every,
+        (Some(address), None) => UtxoKey::Address(address),
+        (None, Some(TokenPattern { policy, name: None })) => UtxoKey::Policy(policy.to_vec()),
+        (
+            None,
+            Some(TokenPattern {
+                policy,
+                name: Some(name),
+            }),
+        ) => UtxoKey::Asset(policy.to_vec(), name.to_vec()),
+        (Some(address), Some(TokenPattern { policy, name: None })) => {
+            UtxoKey::AddressPolicy(address, policy.to_vec())
+        }
+        (
+            Some(address),
+            Some(TokenPattern {
+                policy,
+                name: Some(name),
+            }),
+        ) => UtxoKey::AddressAsset(address, policy.to_vec(), name.to_vec()),
+    }
+}
+
+/// Every key an address can be matched by: the exact bytes plus, for Shelley
+/// addresses, the hashes of its payment and stake credentials.
+fn address_keys(address: &[u8]) -> Vec<AddressKey> {
+    let mut keys = vec![AddressKey::Exact(address.to_vec())];
+
+    if let Ok(Address::Shelley(shelley)) = Address::from_bytes(address) {
+        keys.push(AddressKey::PaymentCredential(
+            shelley.payment().as_hash().to_vec(),
+        ));
+
+        if let Some(stake) = shelley.delegation().as_hash() {
+            keys.push(AddressKey::StakeCredential(stake.to_vec()));
+        }
+    }
+
+    keys
+}
+
+/// Every key (other than `Every`) that an output with the given address and
+/// assets can be matched by.
+fn output_keys(address: Option<Vec<u8>>, assets: Vec<(PolicyId, AssetName)>) -> HashSet<UtxoKey> {
+    let mut keys = HashSet::new();
+
+    let address = address.as_deref().map(address_keys).unwrap_or_default();
+
+    for address in &address {
+        keys.insert(UtxoKey::Address(address.clone()));
+    }
+
+    for (policy, name) in assets {
+        for address in &address {
+            keys.insert(UtxoKey::AddressPolicy(address.clone(), policy.clone()));
+            keys.insert(UtxoKey::AddressAsset(
+                address.clone(),
+                policy.clone(),
+                name.clone(),
+            ));
+        }
+
+        keys.insert(UtxoKey::Policy(policy.clone()));
+        keys.insert(UtxoKey::Asset(policy, name));
+    }
+
+    keys
+}
+
+fn utxo_keys(utxo: &Utxo) -> HashSet<UtxoKey> {
+    let mut keys = output_keys(utxo.address(), utxo.assets());
+    keys.insert(UtxoKey::Every);
+    keys
+}
+
+fn input_keys(input: &TxInput) -> HashSet<UtxoKey> {
+    let mut keys = output_keys(input.address(), input.assets());
+    keys.insert(UtxoKey::Every);
+    keys
+}
+
+fn tx_keys(tx: &Tx) -> HashSet<UtxoKey> {
+    let mut keys = HashSet::from([UtxoKey::Every]);
+
+    for input in tx.inputs() {
+        keys.extend(output_keys(input.address(), input.assets()));
+    }
+
+    for output in tx.outputs() {
+        keys.extend(output_keys(output.address(), output.assets()));
+    }
+
+    keys
+}
+
+/// Token keys for the assets minted (or, if `burned`, burned) by the Tx.
+fn mint_keys(tx: &Tx, burned: bool) -> HashSet<UtxoKey> {
+    let mut keys = HashSet::new();
+
+    for (policy, name, amount) in tx.mint() {
+        if (amount < 0) == burned && amount != 0 {
+            keys.insert(UtxoKey::Policy(policy.clone()));
+            keys.insert(UtxoKey::Asset(policy, name));
+        }
+    }
+
+    keys
 }
 
 fn infer_match_keys(pattern: &EventPattern) -> Vec<MatchKey> {
     match pattern {
         EventPattern::Request(x) => vec![MatchKey::RequestMethod(x.to_owned())],
-        EventPattern::Tx(UtxoPattern { address, token }) => match (address, token) {
-            (None, None) => vec![MatchKey::EveryTx],
-            (Some(address), None) => vec![MatchKey::TxAddress(address.to_vec())],
-            _ => todo!(),
-        },
-        EventPattern::TxUndo(_) => todo!(),
-        EventPattern::Utxo(UtxoPattern { address, token }) => match (address, token) {
-            (None, None) => vec![MatchKey::EveryUtxo],
-            (Some(address), None) => vec![MatchKey::UtxoAddress(address.to_vec())],
-            _ => todo!(),
-        },
-        EventPattern::UtxoUndo(_) => todo!(),
-        EventPattern::Timer(_) => todo!(),
-        EventPattern::Message(_) => todo!(),
+        EventPattern::Tx(x) => vec![MatchKey::Tx(infer_utxo_key(x))],
+        EventPattern::TxUndo(x) => vec![MatchKey::TxUndo(infer_utxo_key(x))],
+        EventPattern::Utxo(x) => vec![MatchKey::Utxo(infer_utxo_key(x))],
+        EventPattern::UtxoUndo(x) => vec![MatchKey::UtxoUndo(infer_utxo_key(x))],
+        EventPattern::Timer(x) => vec![MatchKey::Timer(x.to_owned())],
+        EventPattern::Message(x) => vec![MatchKey::Message(x.to_owned())],
+        EventPattern::UtxoSpent(x) => vec![MatchKey::UtxoSpent(infer_utxo_key(x))],
+        EventPattern::UtxoSpentUndo(x) => vec![MatchKey::UtxoSpentUndo(infer_utxo_key(x))],
+        EventPattern::Mint(x) => vec![MatchKey::Mint(infer_token_key(x))],
+        EventPattern::Burn(x) => vec![MatchKey::Burn(infer_token_key(x))],
+        EventPattern::Certificate(x) => vec![MatchKey::Certificate(*x)],
+        EventPattern::Metadata(x) => vec![MatchKey::Metadata(*x)],
+        EventPattern::Block => vec![MatchKey::Block],
+        EventPattern::BlockUndo => vec![MatchKey::BlockUndo],
+        EventPattern::BlockBegin => vec![MatchKey::BlockBegin],
+        EventPattern::BlockEnd => vec![MatchKey::BlockEnd],
+        EventPattern::Proposal(x) => vec![MatchKey::Proposal(*x)],
     }
 }
 
@@ -60,45 +248,159 @@ impl Router {
         }
     }
 
-    pub fn find_tx_targets(&self, tx: &Tx) -> HashSet<ChannelId> {
+    fn find_targets(
+        &self,
+        keys: HashSet<UtxoKey>,
+        wrap: fn(UtxoKey) -> MatchKey,
+    ) -> HashSet<ChannelId> {
         let mut targets = HashSet::new();
-        let mut add_targets = |key: &MatchKey| {
-            if let Some(channels) = self.routes.get(key) {
+
+        for key in keys {
+            if let Some(channels) = self.routes.get(&wrap(key)) {
                 targets.extend(channels);
             }
-        };
+        }
 
-        add_targets(&MatchKey::EveryTx);
-        for input in tx.inputs() {
-            if let Some(address) = input.address() {
-                add_targets(&MatchKey::TxAddress(address));
-            }
+        targets
+    }
+
+    fn has_routes(&self, is_kind: fn(&MatchKey) -> bool) -> bool {
+        self.routes.keys().any(is_kind)
+    }
+
+    pub fn find_tx_targets(&self, tx: &Tx) -> HashSet<ChannelId> {
+        self.find_targets(tx_keys(tx), MatchKey::Tx)
+    }
+
+    /// Channels that should receive the rollback of a Tx. Once a worker
+    /// registers a dedicated tx-undo channel, rollbacks are routed
+    /// exclusively to those; otherwise they go to the tx channels.
+    pub fn find_tx_undo_targets(&self, tx: &Tx) -> HashSet<ChannelId> {
+        if self.has_routes(|x| matches!(x, MatchKey::TxUndo(_))) {
+            self.find_targets(tx_keys(tx), MatchKey::TxUndo)
+        } else {
+            self.find_tx_targets(tx)
         }
-        for output in tx.outputs() {
-            if let Some(address) = output.address() {
-                add_targets(&MatchKey::TxAddress(address));
+    }
+
+    /// Channels interested in the tokens minted or burned by the Tx. The same
+    /// channels receive the rollback of the Tx.
+    pub fn find_mint_targets(&self, tx: &Tx) -> HashSet<ChannelId> {
+        let mut targets = self.find_targets(mint_keys(tx, false), MatchKey::Mint);
+        targets.extend(self.find_targets(mint_keys(tx, true), MatchKey::Burn));
+        targets
+    }
+
+    /// Channels interested in any of the kinds the certificate is made of.
+    /// The same channels receive the rollback of the certificate.
+    pub fn find_certificate_targets(&self, cert: &Certificate) -> HashSet<ChannelId> {
+        let mut targets = HashSet::new();
+
+        for kind in cert.kinds() {
+            if let Some(channels) = self.routes.get(&MatchKey::Certificate(kind)) {
+                targets.extend(channels);
             }
         }
 
         targets
     }
 
-    pub fn find_utxo_targets(&self, utxo: &Utxo) -> HashSet<ChannelId> {
+    /// Channels interested in the kind of action proposed. The same channels
+    /// receive the rollback of the proposal.
+    pub fn find_proposal_targets(&self, proposal: &Proposal) -> HashSet<ChannelId> {
+        match proposal.kind() {
+            Some(kind) => self.find_key_targets(&MatchKey::Proposal(kind)),
+            None => HashSet::new(),
+        }
+    }
+
+    /// Channels interested in any of the metadata labels of the Tx. The same
+    /// channels receive the rollback of the Tx.
+    pub fn find_metadata_targets(&self, tx: &Tx) -> HashSet<ChannelId> {
         let mut targets = HashSet::new();
-        let mut add_targets = |key: &MatchKey| {
-            if let Some(channels) = self.routes.get(key) {
+
+        for label in tx.metadata_labels() {
+            if let Some(channels) = self.routes.get(&MatchKey::Metadata(label)) {
                 targets.extend(channels);
             }
-        };
+        }
+
+        targets
+    }
+
+    fn find_key_targets(&self, key: &MatchKey) -> HashSet<ChannelId> {
+        self.routes.get(key).cloned().unwrap_or_default()
+    }
+
+    pub fn find_block_targets(&self) -> HashSet<ChannelId> {
+        self.find_key_targets(&MatchKey::Block)
+    }
 
-        add_targets(&MatchKey::EveryUtxo);
-        if let Some(address) = utxo.address() {
-            add_targets(&MatchKey::UtxoAddress(address));
+    /// Channels that should receive the rollback of a block. Once a worker
+    /// registers a dedicated block-undo channel, rollbacks are routed
+    /// exclusively to those; otherwise they go to the block channels.
+    pub fn find_block_undo_targets(&self) -> HashSet<ChannelId> {
+        if self.has_routes(|x| matches!(x, MatchKey::BlockUndo)) {
+            self.find_key_targets(&MatchKey::BlockUndo)
+        } else {
+            self.find_block_targets()
         }
+    }
 
-        // TODO: match by policy / asset
+    pub fn find_block_begin_targets(&self) -> HashSet<ChannelId> {
+        self.find_key_targets(&MatchKey::BlockBegin)
+    }
 
-        targets
+    pub fn find_block_end_targets(&self) -> HashSet<ChannelId> {
+        self.find_key_targets(&MatchKey::BlockEnd)
+    }
+
+    pub fn find_utxo_targets(&self, utxo: &Utxo) -> HashSet<ChannelId> {
+        self.find_targets(utxo_keys(utxo), MatchKey::Utxo)
+    }
+
+    /// Channels that should receive the rollback of a UTxO. Once a worker
+    /// registers a dedicated utxo-undo channel, rollbacks are routed
+    /// exclusively to those; otherwise they go to the utxo channels.
+    pub fn find_utxo_undo_targets(&self, utxo: &Utxo) -> HashSet<ChannelId> {
+        if self.has_routes(|x| matches!(x, MatchKey::UtxoUndo(_))) {
+            self.find_targets(utxo_keys(utxo), MatchKey::UtxoUndo)
+        } else {
+            self.find_utxo_targets(utxo)
+        }
+    }
+
+    /// Channels watching the output being spent by the input. Inputs that
+    /// aren't resolved by the chain source only match catch-all patterns.
+    pub fn find_utxo_spent_targets(&self, input: &TxInput) -> HashSet<ChannelId> {
+        self.find_targets(input_keys(input), MatchKey::UtxoSpent)
+    }
+
+    /// Channels that should receive the rollback of a spent UTxO, following
+    /// the same rules as `find_utxo_undo_targets`.
+    pub fn find_utxo_spent_undo_targets(&self, input: &TxInput) -> HashSet<ChannelId> {
+        if self.has_routes(|x| matches!(x, MatchKey::UtxoSpentUndo(_))) {
+            self.find_targets(input_keys(input), MatchKey::UtxoSpentUndo)
+        } else {
+            self.find_utxo_spent_targets(input)
+        }
+    }
+
+    pub fn find_timer_targets(&self) -> Vec<(ChannelId, TimerInterval)> {
+        self.routes
+            .iter()
+            .filter_map(|(key, channels)| match key {
+                MatchKey::Timer(interval) => Some(channels.iter().map(|c| (*c, interval.clone()))),
+                _ => None,
+            })
+            .flatten()
+            .collect()
+    }
+
+    pub fn find_message_targets(&self, topic: &str) -> HashSet<ChannelId> {
+        let key = MatchKey::Message(topic.to_owned());
+
+        self.routes.get(&key).cloned().unwrap_or_default()
     }
 
     pub fn find_request_target(&self, method: &str) -> Result<ChannelId, super::Error> {
@@ -137,4 +439,413 @@ mod tests {
 
         assert_eq!(channel, channel);
     }
+
+    #[test]
+    fn test_timer_channel() {
+        let mut router = Router::new();
+
+        router.register_channel(1, &EventPattern::Timer("5m".to_string()));
+        router.register_channel(2, &EventPattern::Timer("0 15 * * *".to_string()));
+        router.register_channel(3, &EventPattern::Request("test_method".to_string()));
+
+        let mut targets = router.find_timer_targets();
+        targets.sort();
+
+        assert_eq!(
+            targets,
+            vec![(1, "5m".to_string()), (2, "0 15 * * *".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_message_channel() {
+        let mut router = Router::new();
+
+        router.register_channel(1, &EventPattern::Message("prices".to_string()));
+        router.register_channel(2, &EventPattern::Message("prices".to_string()));
+        router.register_channel(3, &EventPattern::Message("orders".to_string()));
+
+        assert_eq!(router.find_message_targets("prices"), HashSet::from([1, 2]));
+        assert_eq!(router.find_message_targets("orders"), HashSet::from([3]));
+        assert!(router.find_message_targets("unknown").is_empty());
+    }
+
+    fn ship_utxo(address: &[u8], policy: &[u8], name: &[u8]) -> Utxo {
+        use balius_core::proto::v0::cardano as proto;
+
+        Utxo::Cardano(proto::TxOutput {
+            address: address.to_vec().into(),
+            coin: 2_000_000,
+            assets: vec![proto::Multiasset {
+                policy_id: policy.to_vec().into(),
+                assets: vec![proto::Asset {
+                    name: name.to_vec().into(),
+                    output_coin: 1,
+                    mint_coin: 0,
+                }],
+            }],
+            datum: None,
+        })
+    }
+
+    fn token(policy: &[u8], name: Option<&[u8]>) -> Option<TokenPattern> {
+        Some(TokenPattern {
+            policy: policy.to_vec(),
+            name: name.map(|x| x.to_vec()),
+        })
+    }
+
+    #[test]
+    fn test_utxo_token_channels() {
+        let mut router = Router::new();
+
+        let by_policy = UtxoPattern {
+            address: None,
+            token: token(b"ships", None),
+        };
+        let by_asset = UtxoPattern {
+            address: None,
+            token: token(b"ships", Some(b"ship1")),
+        };
+        let by_address_and_policy = UtxoPattern {
+            address: Some(AddressPattern::Exact(b"space".to_vec())),
+            token: token(b"ships", None),
+        };
+        let by_address_and_asset = UtxoPattern {
+            address: Some(AddressPattern::Exact(b"space".to_vec())),
+            token: token(b"ships", Some(b"ship2")),
+        };
+
+        router.register_channel(1, &EventPattern::Utxo(by_policy));
+        router.register_channel(2, &EventPattern::Utxo(by_asset));
+        router.register_channel(3, &EventPattern::Utxo(by_address_and_policy));
+        router.register_channel(4, &EventPattern::Utxo(by_address_and_asset));
+
+        let targets = router.find_utxo_targets(&ship_utxo(b"space", b"ships", b"ship1"));
+        assert_eq!(targets, HashSet::from([1, 2, 3]));
+
+        let targets = router.find_utxo_targets(&ship_utxo(b"space", b"ships", b"ship2"));
+        assert_eq!(targets, HashSet::from([1, 3, 4]));
+
+        let targets = router.find_utxo_targets(&ship_utxo(b"earth", b"ships", b"ship2"));
+        assert_eq!(targets, HashSet::from([1]));
+
+        let targets = router.find_utxo_targets(&ship_utxo(b"space", b"fuel", b"fuel"));
+        assert!(targets.is_empty());
+    }
+
+    #[test]
+    fn test_tx_token_channels() {
+        use balius_core::proto::v0::cardano as proto;
+
+        let mut router = Router::new();
+
+        let by_asset = UtxoPattern {
+            address: None,
+            token: token(b"ships", Some(b"ship1")),
+        };
+        router.register_channel(1, &EventPattern::Tx(by_asset));
+
+        let Utxo::Cardano(spent) = ship_utxo(b"space", b"ships", b"ship1");
+        let consuming = Tx::Cardano(proto::Tx {
+            inputs: vec![proto::TxInput {
+                tx_hash: vec![0; 32].into(),
+                output_index: 0,
+                as_output: Some(spent),
+            }],
+            ..Default::default()
+        });
+        assert_eq!(router.find_tx_targets(&consuming), HashSet::from([1]));
+
+        let Utxo::Cardano(produced) = ship_utxo(b"space", b"ships", b"ship2");
+        let unrelated = Tx::Cardano(proto::Tx {
+            outputs: vec![produced],
+            ..Default::default()
+        });
+        assert!(router.find_tx_targets(&unrelated).is_empty());
+    }
+
+    #[test]
+    fn test_undo_channels() {
+        let mut router = Router::new();
+
+        let every = UtxoPattern {
+            address: None,
+            token: None,
+        };
+
+        router.register_channel(1, &EventPattern::Utxo(every.clone()));
+        router.register_channel(2, &EventPattern::Tx(every.clone()));
+
+        // without dedicated undo channels, rollbacks go to the apply ones
+        let utxo = ship_utxo(b"space", b"ships", b"ship1");
+        assert_eq!(router.find_utxo_undo_targets(&utxo), HashSet::from([1]));
+
+        router.register_channel(3, &EventPattern::UtxoUndo(every.clone()));
+
+        assert_eq!(router.find_utxo_targets(&utxo), HashSet::from([1]));
+        assert_eq!(router.find_utxo_undo_targets(&utxo), HashSet::from([3]));
+
+        let tx = Tx::Cardano(Default::default());
+        assert_eq!(router.find_tx_undo_targets(&tx), HashSet::from([2]));
+
+        router.register_channel(4, &EventPattern::TxUndo(every));
+
+        assert_eq!(router.find_tx_targets(&tx), HashSet::from([2]));
+        assert_eq!(router.find_tx_undo_targets(&tx), HashSet::from([4]));
+    }
+
+    #[test]
+    fn test_utxo_spent_channels() {
+        use balius_core::proto::v0::cardano as proto;
+
+        let mut router = Router::new();
+
+        let by_address = UtxoPattern {
+            address: Some(AddressPattern::Exact(b"orders".to_vec())),
+            token: None,
+        };
+        router.register_channel(1, &EventPattern::UtxoSpent(by_address.clone()));
+        router.register_channel(2, &EventPattern::Utxo(by_address));
+
+        let Utxo::Cardano(order) = ship_utxo(b"orders", b"ships", b"ship1");
+        let resolved = TxInput::Cardano(proto::TxInput {
+            tx_hash: vec![0; 32].into(),
+            output_index: 0,
+            as_output: Some(order),
+        });
+        let unresolved = TxInput::Cardano(proto::TxInput {
+            tx_hash: vec![0; 32].into(),
+            output_index: 1,
+            as_output: None,
+        });
+
+        assert_eq!(
+            router.find_utxo_spent_targets(&resolved),
+            HashSet::from([1])
+        );
+        assert!(router.find_utxo_spent_targets(&unresolved).is_empty());
+        assert_eq!(
+            router.find_utxo_spent_undo_targets(&resolved),
+            HashSet::from([1])
+        );
+    }
+
+    #[test]
+    fn test_credential_channels() {
+        use pallas::ledger::addresses::{
+            Network, ShelleyAddress, ShelleyDelegationPart, ShelleyPaymentPart,
+        };
+
+        let mut router = Router::new();
+
+        let pool = [1; 28];
+        let (alice, bob) = ([2; 28], [3; 28]);
+
+        let address = |stake: [u8; 28]| {
+            ShelleyAddress::new(
+                Network::Testnet,
+                ShelleyPaymentPart::Script(pool.into()),
+                ShelleyDelegationPart::Key(stake.into()),
+            )
+            .to_vec()
+        };
+
+        let by_payment = UtxoPattern {
+            address: Some(AddressPattern::PaymentCredential(pool.to_vec())),
+            token: None,
+        };
+        let by_stake = UtxoPattern {
+            address: Some(AddressPattern::StakeCredential(alice.to_vec())),
+            token: token(b"ships", None),
+        };
+        router.register_channel(1, &EventPattern::Utxo(by_payment));
+        router.register_channel(2, &EventPattern::Utxo(by_stake));
+
+        let targets = router.find_utxo_targets(&ship_utxo(&address(alice), b"ships", b"ship1"));
+        assert_eq!(targets, HashSet::from([1, 2]));
+
+        let targets = router.find_utxo_targets(&ship_utxo(&address(bob), b"ships", b"ship1"));
+        assert_eq!(targets, HashSet::from([1]));
+
+        let targets = router.find_utxo_targets(&ship_utxo(b"not-shelley", b"ships", b"ship1"));
+        assert!(targets.is_empty());
+    }
+
+    #[test]
+    fn test_mint_channels() {
+        use balius_core::proto::v0::cardano as proto;
+
+        let mut router = Router::new();
+
+        router.register_channel(1, &EventPattern::Mint(token(b"ships", None).unwrap()));
+        router.register_channel(
+            2,
+            &EventPattern::Mint(token(b"ships", Some(b"ship1")).unwrap()),
+        );
+        router.register_channel(3, &EventPattern::Burn(token(b"ships", None).unwrap()));
+
+        let mint = |name: &[u8], mint_coin: i64| {
+            Tx::Cardano(proto::Tx {
+                mint: vec![proto::Multiasset {
+                    policy_id: b"ships".to_vec().into(),
+                    assets: vec![proto::Asset {
+                        name: name.to_vec().into(),
+                        output_coin: 0,
+                        mint_coin,
+                    }],
+                }],
+                ..Default::default()
+            })
+        };
+
+        assert_eq!(
+            router.find_mint_targets(&mint(b"ship1", 1)),
+            HashSet::from([1, 2])
+        );
+        assert_eq!(
+            router.find_mint_targets(&mint(b"ship2", 1)),
+            HashSet::from([1])
+        );
+        assert_eq!(
+            router.find_mint_targets(&mint(b"ship1", -1)),
+            HashSet::from([3])
+        );
+        assert!(router
+            .find_mint_targets(&Tx::Cardano(Default::default()))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_certificate_channels() {
+        use balius_core::proto::v0::cardano as proto;
+        use proto::certificate::Certificate as C;
+
+        let mut router = Router::new();
+
+        router.register_channel(
+            1,
+            &EventPattern::Certificate(CertificateKind::StakeDelegation),
+        );
+        router.register_channel(
+            2,
+            &EventPattern::Certificate(CertificateKind::StakeRegistration),
+        );
+        router.register_channel(3, &EventPattern::Certificate(CertificateKind::DrepUpdate));
+
+        let cert = |c: C| {
+            Certificate::Cardano(proto::Certificate {
+                certificate: Some(c),
+            })
+        };
+
+        let delegation = cert(C::StakeDelegation(Default::default()));
+        assert_eq!(
+            router.find_certificate_targets(&delegation),
+            HashSet::from([1])
+        );
+
+        let combined = cert(C::StakeRegDelegCert(Default::default()));
+        assert_eq!(
+            router.find_certificate_targets(&combined),
+            HashSet::from([1, 2])
+        );
+
+        let retirement = cert(C::PoolRetirement(Default::default()));
+        assert!(router.find_certificate_targets(&retirement).is_empty());
+    }
+
+    #[test]
+    fn test_proposal_channels() {
+        use balius_core::proto::v0::cardano as proto;
+        use proto::governance_action::GovernanceAction as G;
+
+        let mut router = Router::new();
+
+        router.register_channel(
+            1,
+            &EventPattern::Proposal(GovernanceActionKind::TreasuryWithdrawals),
+        );
+        router.register_channel(2, &EventPattern::Proposal(GovernanceActionKind::Info));
+
+        let proposal = |action: Option<G>| {
+            Proposal::Cardano(proto::GovernanceActionProposal {
+                gov_action: Some(proto::GovernanceAction {
+                    governance_action: action,
+                }),
+                ..Default::default()
+            })
+        };
+
+        let withdrawal = proposal(Some(G::TreasuryWithdrawalsAction(Default::default())));
+        assert_eq!(
+            router.find_proposal_targets(&withdrawal),
+            HashSet::from([1])
+        );
+
+        let info = proposal(Some(G::InfoAction(6)));
+        assert_eq!(router.find_proposal_targets(&info), HashSet::from([2]));
+
+        let hard_fork = proposal(Some(G::HardForkInitiationAction(Default::default())));
+        assert!(router.find_proposal_targets(&hard_fork).is_empty());
+        assert!(router.find_proposal_targets(&proposal(None)).is_empty());
+    }
+
+    #[test]
+    fn test_metadata_channels() {
+        use balius_core::proto::v0::cardano as proto;
+
+        let mut router = Router::new();
+
+        router.register_channel(1, &EventPattern::Metadata(674));
+        router.register_channel(2, &EventPattern::Metadata(721));
+
+        let tagged = |labels: &[u64]| {
+            Tx::Cardano(proto::Tx {
+                auxiliary: Some(proto::AuxData {
+                    metadata: labels
+                        .iter()
+                        .map(|label| proto::Metadata {
+                            label: *label,
+                            value: None,
+                        })
+                        .collect(),
+                    scripts: vec![],
+                }),
+                ..Default::default()
+            })
+        };
+
+        assert_eq!(
+            router.find_metadata_targets(&tagged(&[674])),
+            HashSet::from([1])
+        );
+        assert_eq!(
+            router.find_metadata_targets(&tagged(&[674, 721])),
+            HashSet::from([1, 2])
+        );
+        assert!(router.find_metadata_targets(&tagged(&[1])).is_empty());
+        assert!(router
+            .find_metadata_targets(&Tx::Cardano(Default::default()))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_block_channels() {
+        let mut router = Router::new();
+
+        router.register_channel(1, &EventPattern::Block);
+        router.register_channel(2, &EventPattern::BlockBegin);
+        router.register_channel(3, &EventPattern::BlockEnd);
+
+        assert_eq!(router.find_block_targets(), HashSet::from([1]));
+        assert_eq!(router.find_block_undo_targets(), HashSet::from([1]));
+        assert_eq!(router.find_block_begin_targets(), HashSet::from([2]));
+        assert_eq!(router.find_block_end_targets(), HashSet::from([3]));
+
+        router.register_channel(4, &EventPattern::BlockUndo);
+
+        assert_eq!(router.find_block_targets(), HashSet::from([1]));
+        assert_eq!(router.find_block_undo_targets(), HashSet::from([4]));
+    }
 }
@@ -1,13 +1,15 @@
 pub mod redb;
+pub mod sqlite;
 
 use prost::Message;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
-use crate::{Block, ChainPoint, Error};
+use crate::{Block, BlockSlot, ChainPoint, Error};
 
 pub type WorkerId = String;
 pub type LogSeq = u64;
+pub type DeadLetterId = u64;
 
 #[derive(Message)]
 pub struct LogEntry {
@@ -17,15 +19,89 @@ pub struct LogEntry {
     pub undo_blocks: Vec<Vec<u8>>,
 }
 
+/// An event a worker failed to handle, kept aside to be replayed once the
+/// worker is fixed.
+#[derive(Message, Clone)]
+pub struct DeadLetter {
+    #[prost(string, tag = "1")]
+    pub worker: String,
+    #[prost(uint32, tag = "2")]
+    pub channel: u32,
+    /// The event as JSON
+    #[prost(bytes, tag = "3")]
+    pub event: Vec<u8>,
+    /// Error code, when the failure was an error returned by the handler
+    #[prost(uint32, optional, tag = "4")]
+    pub code: Option<u32>,
+    #[prost(string, tag = "5")]
+    pub message: String,
+    /// Block the event belongs to, `None` for events not tied to the chain
+    #[prost(message, optional, tag = "6")]
+    pub block: Option<utxorpc::spec::sync::BlockRef>,
+}
+
+#[derive(Message, Clone)]
+pub struct KvWrite {
+    #[prost(string, tag = "1")]
+    pub key: String,
+    /// `None` deletes the key
+    #[prost(bytes, optional, tag = "2")]
+    pub value: Option<Vec<u8>>,
+}
+
+/// A set of KV writes of a worker, applied in order.
+#[derive(Message, Clone)]
+pub struct KvBatch {
+    #[prost(message, repeated, tag = "1")]
+    pub writes: Vec<KvWrite>,
+}
+
+impl KvBatch {
+    pub fn is_empty(&self) -> bool {
+        self.writes.is_empty()
+    }
+}
+
 #[async_trait::async_trait]
 pub trait AtomicUpdateTrait {
     async fn update_worker_cursor(&mut self, id: &str) -> Result<(), super::Error>;
+
+    /// Keep the writes that revert the KV changes a worker made while
+    /// handling a block, until the block is undone or pruned.
+    async fn save_kv_undo(
+        &mut self,
+        id: &str,
+        slot: BlockSlot,
+        block_hash: &[u8],
+        batch: &KvBatch,
+    ) -> Result<(), super::Error>;
+
+    async fn remove_kv_undo(
+        &mut self,
+        id: &str,
+        slot: BlockSlot,
+        block_hash: &[u8],
+    ) -> Result<(), super::Error>;
+
+    /// Drop the KV undo records of a worker for blocks before `slot`, which
+    /// can't be rolled back anymore.
+    async fn prune_kv_undo(&mut self, id: &str, slot: BlockSlot) -> Result<(), super::Error>;
+
+    /// Record the KV writes committed along with the cursor of a worker, so
+    /// they can be applied again if the process stops before they reach the
+    /// KV provider.
+    async fn set_kv_redo(&mut self, id: &str, batch: &KvBatch) -> Result<(), super::Error>;
+
+    /// Keep an event the worker failed to handle, along with its cursor.
+    async fn add_dead_letter(&mut self, letter: &DeadLetter) -> Result<(), super::Error>;
+
     async fn commit(&mut self) -> Result<(), super::Error>;
 }
 
 #[allow(clippy::large_enum_variant)]
 pub enum AtomicUpdate {
     Redb(redb::AtomicUpdate),
+    Sqlite(sqlite::AtomicUpdate),
     Custom(Arc<Mutex<dyn AtomicUpdateTrait + Send + Sync>>),
 }
 
@@ -34,12 +110,65 @@ impl AtomicUpdateTrait for AtomicUpdate {
     async fn update_worker_cursor(&mut self, id: &str) -> Result<(), super::Error> {
         match self {
             AtomicUpdate::Redb(au) => au.update_worker_cursor(id).await,
+            AtomicUpdate::Sqlite(au) => au.update_worker_cursor(id).await,
             AtomicUpdate::Custom(au) => au.lock().await.update_worker_cursor(id).await,
         }
     }
+    async fn save_kv_undo(
+        &mut self,
+        id: &str,
+        slot: BlockSlot,
+        block_hash: &[u8],
+        batch: &KvBatch,
+    ) -> Result<(), super::Error> {
+        match self {
+            AtomicUpdate::Redb(au) => au.save_kv_undo(id, slot, block_hash, batch).await,
+            AtomicUpdate::Sqlite(au) => au.save_kv_undo(id, slot, block_hash, batch).await,
+            AtomicUpdate::Custom(au) => {
+                au.lock()
+                    .await
+                    .save_kv_undo(id, slot, block_hash, batch)
+                    .await
+            }
+        }
+    }
+    async fn remove_kv_undo(
+        &mut self,
+        id: &str,
+        slot: BlockSlot,
+        block_hash: &[u8],
+    ) -> Result<(), super::Error> {
+        match self {
+            AtomicUpdate::Redb(au) => au.remove_kv_undo(id, slot, block_hash).await,
+            AtomicUpdate::Sqlite(au) => au.remove_kv_undo(id, slot, block_hash).await,
+            AtomicUpdate::Custom(au) => au.lock().await.remove_kv_undo(id, slot, block_hash).await,
+        }
+    }
+    async fn prune_kv_undo(&mut self, id: &str, slot: BlockSlot) -> Result<(), super::Error> {
+        match self {
+            AtomicUpdate::Redb(au) => au.prune_kv_undo(id, slot).await,
+            AtomicUpdate::Sqlite(au) => au.prune_kv_undo(id, slot).await,
+            AtomicUpdate::Custom(au) => au.lock().await.prune_kv_undo(id, slot).await,
+        }
+    }
+    async fn set_kv_redo(&mut self, id: &str, batch: &KvBatch) -> Result<(), super::Error> {
+        match self {
+            AtomicUpdate::Redb(au) => au.set_kv_redo(id, batch).await,
+            AtomicUpdate::Sqlite(au) => au.set_kv_redo(id, batch).await,
+            AtomicUpdate::Custom(au) => au.lock().await.set_kv_redo(id, batch).await,
+        }
+    }
+    async fn add_dead_letter(&mut self, letter: &DeadLetter) -> Result<(), super::Error> {
+        match self {
+            AtomicUpdate::Redb(au) => au.add_dead_letter(letter).await,
+            AtomicUpdate::Sqlite(au) => au.add_dead_letter(letter).await,
+            AtomicUpdate::Custom(au) => au.lock().await.add_dead_letter(letter).await,
+        }
+    }
     async fn commit(&mut self) -> Result<(), super::Error> {
         match self {
             AtomicUpdate::Redb(au) => au.commit().await,
+            AtomicUpdate::Sqlite(au) => au.commit().await,
             AtomicUpdate::Custom(au) => au.lock().await.commit().await,
         }
     }
@@ -48,6 +177,7 @@ impl AtomicUpdateTrait for AtomicUpdate {
 #[derive(Clone)]
 pub enum Store {
     Redb(redb::Store),
+    Sqlite(sqlite::Store),
     Custom(Arc<Mutex<dyn StoreTrait + Send + Sync>>),
 }
 
@@ -62,6 +192,43 @@ pub trait StoreTrait {
     async fn get_worker_cursor(&self, id: &str) -> Result<Option<LogSeq>, super::Error>;
     async fn start_atomic_update(&self, log_seq: LogSeq) -> Result<AtomicUpdate, super::Error>;
     async fn handle_reset(&self, point: ChainPoint) -> Result<Vec<Block>, super::Error>;
+
+    /// Seq of the last entry of the log, if any.
+    async fn last_log_seq(&self) -> Result<Option<LogSeq>, super::Error>;
+
+    /// Read up to `limit` log entries, in order, starting right after `after`
+    /// (from the beginning of the log when `None`).
+    async fn read_log(
+        &self,
+        after: Option<LogSeq>,
+        limit: usize,
+    ) -> Result<Vec<(LogSeq, LogEntry)>, super::Error>;
+
+    async fn add_dead_letters(&self, letters: &[DeadLetter]) -> Result<(), super::Error>;
+
+    /// List dead letters in the order they were added, optionally only those
+    /// of a single worker.
+    async fn list_dead_letters(
+        &self,
+        worker: Option<&str>,
+    ) -> Result<Vec<(DeadLetterId, DeadLetter)>, super::Error>;
+
+    async fn get_dead_letter(&self, id: DeadLetterId) -> Result<Option<DeadLetter>, super::Error>;
+
+    async fn remove_dead_letter(&self, id: DeadLetterId) -> Result<(), super::Error>;
+
+    async fn get_kv_undo(
+        &self,
+        id: &str,
+        slot: BlockSlot,
+        block_hash: &[u8],
+    ) -> Result<Option<KvBatch>, super::Error>;
+
+    async fn get_kv_redo(&self, id: &str) -> Result<Option<KvBatch>, super::Error>;
+
+    /// Drop the redo records of workers, once their writes reached the KV
+    /// provider.
+    async fn clear_kv_redo(&self, ids: &[WorkerId]) -> Result<(), super::Error>;
 }
 
 #[async_trait::async_trait]
@@ -69,6 +236,7 @@ impl StoreTrait for Store {
     async fn find_chain_point(&self, seq: LogSeq) -> Result<Option<ChainPoint>, Error> {
         match self {
             Store::Redb(store) => store.find_chain_point(seq).await,
+            Store::Sqlite(store) => store.find_chain_point(seq).await,
             Store::Custom(store) => store.lock().await.find_chain_point(seq).await,
         }
     }
@@ -79,6 +247,7 @@ impl StoreTrait for Store {
     ) -> Result<LogSeq, Error> {
         match self {
             Store::Redb(store) => store.write_ahead(undo_blocks, next_block).await,
+            Store::Sqlite(store) => store.write_ahead(undo_blocks, next_block).await,
             Store::Custom(store) => {
                 store
                     .lock()
@@ -91,12 +260,14 @@ impl StoreTrait for Store {
     async fn get_worker_cursor(&self, id: &str) -> Result<Option<LogSeq>, super::Error> {
         match self {
             Store::Redb(store) => store.get_worker_cursor(id).await,
+            Store::Sqlite(store) => store.get_worker_cursor(id).await,
             Store::Custom(store) => store.lock().await.get_worker_cursor(id).await,
         }
     }
     async fn start_atomic_update(&self, log_seq: LogSeq) -> Result<AtomicUpdate, super::Error> {
         match self {
             Store::Redb(store) => store.start_atomic_update(log_seq).await,
+            Store::Sqlite(store) => store.start_atomic_update(log_seq).await,
             Store::Custom(store) => store.lock().await.start_atomic_update(log_seq).await,
         }
     }
@@ -104,7 +275,92 @@ impl StoreTrait for Store {
     async fn handle_reset(&self, point: ChainPoint) -> Result<Vec<Block>, super::Error> {
         match self {
             Store::Redb(store) => store.handle_reset(point).await,
+            Store::Sqlite(store) => store.handle_reset(point).await,
             Store::Custom(store) => store.lock().await.handle_reset(point).await,
         }
     }
+
+    async fn last_log_seq(&self) -> Result<Option<LogSeq>, super::Error> {
+        match self {
+            Store::Redb(store) => store.last_log_seq().await,
+            Store::Sqlite(store) => store.last_log_seq().await,
+            Store::Custom(store) => store.lock().await.last_log_seq().await,
+        }
+    }
+
+    async fn read_log(
+        &self,
+        after: Option<LogSeq>,
+        limit: usize,
+    ) -> Result<Vec<(LogSeq, LogEntry)>, super::Error> {
+        match self {
+            Store::Redb(store) => store.read_log(after, limit).await,
+            Store::Sqlite(store) => store.read_log(after, limit).await,
+            Store::Custom(store) => store.lock().await.read_log(after, limit).await,
+        }
+    }
+
+    async fn add_dead_letters(&self, letters: &[DeadLetter]) -> Result<(), super::Error> {
+        match self {
+            Store::Redb(store) => store.add_dead_letters(letters).await,
+            Store::Sqlite(store) => store.add_dead_letters(letters).await,
+            Store::Custom(store) => store.lock().await.add_dead_letters(letters).await,
+        }
+    }
+
+    async fn list_dead_letters(
+        &self,
+        worker: Option<&str>,
+    ) -> Result<Vec<(DeadLetterId, DeadLetter)>, super::Error> {
+        match self {
+            Store::Redb(store) => store.list_dead_letters(worker).await,
+            Store::Sqlite(store) => store.list_dead_letters(worker).await,
+            Store::Custom(store) => store.lock().await.list_dead_letters(worker).await,
+        }
+    }
+
+    async fn get_dead_letter(&self, id: DeadLetterId) -> Result<Option<DeadLetter>, super::Error> {
+        match self {
+            Store::Redb(store) => store.get_dead_letter(id).await,
+            Store::Sqlite(store) => store.get_dead_letter(id).await,
+            Store::Custom(store) => store.lock().await.get_dead_letter(id).await,
+        }
+    }
+
+    async fn remove_dead_letter(&self, id: DeadLetterId) -> Result<(), super::Error> {
+        match self {
+            Store::Redb(store) => store.remove_dead_letter(id).await,
+            Store::Sqlite(store) => store.remove_dead_letter(id).await,
+            Store::Custom(store) => store.lock().await.remove_dead_letter(id).await,
+        }
+    }
+
+    async fn get_kv_undo(
+        &self,
+        id: &str,
+        slot: BlockSlot,
+        block_hash: &[u8],
+    ) -> Result<Option<KvBatch>, super::Error> {
+        match self {
+            Store::Redb(store) => store.get_kv_undo(id, slot, block_hash).await,
+            Store::Sqlite(store) => store.get_kv_undo(id, slot, block_hash).await,
+            Store::Custom(store) => store.lock().await.get_kv_undo(id, slot, block_hash).await,
+        }
+    }
+
+    async fn get_kv_redo(&self, id: &str) -> Result<Option<KvBatch>, super::Error> {
+        match self {
+            Store::Redb(store) => store.get_kv_redo(id).await,
+            Store::Sqlite(store) => store.get_kv_redo(id).await,
+            Store::Custom(store) => store.lock().await.get_kv_redo(id).await,
+        }
+    }
+
+    async fn clear_kv_redo(&self, ids: &[WorkerId]) -> Result<(), super::Error> {
+        match self {
+            Store::Redb(store) => store.clear_kv_redo(ids).await,
+            Store::Sqlite(store) => store.clear_kv_redo(ids).await,
+            Store::Custom(store) => store.lock().await.clear_kv_redo(ids).await,
+        }
+    }
 }
@@ -3,10 +3,10 @@ use redb::{ReadableTable as _, TableDefinition, WriteTransaction};
 use std::{collections::VecDeque, path::Path, sync::Arc};
 use tracing::warn;
 
-use crate::{Block, ChainPoint, Error};
+use crate::{Block, BlockSlot, ChainPoint, Error};
 
 use super::StoreTrait;
-pub use super::{AtomicUpdateTrait, LogEntry, LogSeq, WorkerId};
+pub use super::{AtomicUpdateTrait, DeadLetter, DeadLetterId, KvBatch, LogEntry, LogSeq, WorkerId};
 
 impl redb::Value for LogEntry {
     type SelfType<'a>
@@ -43,8 +43,82 @@ impl redb::Value for LogEntry {
     }
 }
 
+impl redb::Value for DeadLetter {
+    type SelfType<'a>
+        = DeadLetter
+    where
+        Self: 'a;
+
+    type AsBytes<'a>
+        = Vec<u8>
+    where
+        Self: 'a;
+
+    fn fixed_width() -> Option<usize> {
+        None
+    }
+
+    fn from_bytes<'a>(data: &'a [u8]) -> Self::SelfType<'a>
+    where
+        Self: 'a,
+    {
+        prost::Message::decode(data).unwrap()
+    }
+
+    fn as_bytes<'a, 'b: 'a>(value: &'a Self::SelfType<'b>) -> Self::AsBytes<'a>
+    where
+        Self: 'a,
+        Self: 'b,
+    {
+        value.encode_to_vec()
+    }
+
+    fn type_name() -> redb::TypeName {
+        redb::TypeName::new("DeadLetter")
+    }
+}
+
+impl redb::Value for KvBatch {
+    type SelfType<'a>
+        = KvBatch
+    where
+        Self: 'a;
+
+    type AsBytes<'a>
+        = Vec<u8>
+    where
+        Self: 'a;
+
+    fn fixed_width() -> Option<usize> {
+        None
+    }
+
+    fn from_bytes<'a>(data: &'a [u8]) -> Self::SelfType<'a>
+    where
+        Self: 'a,
+    {
+        prost::Message::decode(data).unwrap()
+    }
+
+    fn as_bytes<'a, 'b: 'a>(value: &'a Self::SelfType<'b>) -> Self::AsBytes<'a>
+    where
+        Self: 'a,
+        Self: 'b,
+    {
+        value.encode_to_vec()
+    }
+
+    fn type_name() -> redb::TypeName {
+        redb::TypeName::new("KvBatch")
+    }
+}
+
 const CURSORS: TableDefinition<WorkerId, LogSeq> = TableDefinition::new("cursors");
 const WAL: TableDefinition<LogSeq, LogEntry> = TableDefinition::new("wal");
+const DEAD_LETTERS: TableDefinition<DeadLetterId, DeadLetter> =
+    TableDefinition::new("dead_letters");
+const KV_UNDO: TableDefinition<(&str, BlockSlot, &[u8]), KvBatch> = TableDefinition::new("kv_undo");
+const KV_REDO: TableDefinition<WorkerId, KvBatch> = TableDefinition::new("kv_redo");
 
 const DEFAULT_CACHE_SIZE_MB: usize = 50;
 
@@ -76,6 +150,84 @@ impl AtomicUpdateTrait for AtomicUpdate {
         Ok(())
     }
 
+    async fn save_kv_undo(
+        &mut self,
+        id: &str,
+        slot: BlockSlot,
+        block_hash: &[u8],
+        batch: &KvBatch,
+    ) -> Result<(), super::Error> {
+        let Some(wx) = self.wx.as_mut() else {
+            return Err(super::Error::Store(
+                "Transaction already commited".to_string(),
+            ));
+        };
+
+        let mut table = wx.open_table(KV_UNDO)?;
+        table.insert((id, slot, block_hash), batch)?;
+
+        Ok(())
+    }
+
+    async fn remove_kv_undo(
+        &mut self,
+        id: &str,
+        slot: BlockSlot,
+        block_hash: &[u8],
+    ) -> Result<(), super::Error> {
+        let Some(wx) = self.wx.as_mut() else {
+            return Err(super::Error::Store(
+                "Transaction already commited".to_string(),
+            ));
+        };
+
+        let mut table = wx.open_table(KV_UNDO)?;
+        table.remove((id, slot, block_hash))?;
+
+        Ok(())
+    }
+
+    async fn prune_kv_undo(&mut self, id: &str, slot: BlockSlot) -> Result<(), super::Error> {
+        let Some(wx) = self.wx.as_mut() else {
+            return Err(super::Error::Store(
+                "Transaction already commited".to_string(),
+            ));
+        };
+
+        let mut table = wx.open_table(KV_UNDO)?;
+        let empty: &[u8] = &[];
+        table.retain_in((id, 0, empty)..(id, slot, empty), |_, _| false)?;
+
+        Ok(())
+    }
+
+    async fn set_kv_redo(&mut self, id: &str, batch: &KvBatch) -> Result<(), super::Error> {
+        let Some(wx) = self.wx.as_mut() else {
+            return Err(super::Error::Store(
+                "Transaction already commited".to_string(),
+            ));
+        };
+
+        let mut table = wx.open_table(KV_REDO)?;
+        table.insert(id.to_owned(), batch)?;
+
+        Ok(())
+    }
+
+    async fn add_dead_letter(&mut self, letter: &DeadLetter) -> Result<(), super::Error> {
+        let Some(wx) = self.wx.as_mut() else {
+            return Err(super::Error::Store(
+                "Transaction already commited".to_string(),
+            ));
+        };
+
+        let mut table = wx.open_table(DEAD_LETTERS)?;
+        let id = table.last()?.map(|(k, _)| k.value() + 1).unwrap_or(1);
+        table.insert(id, letter)?;
+
+        Ok(())
+    }
+
     async fn commit(&mut self) -> Result<(), super::Error> {
         let Some(wx) = self.wx.take() else {
             return Err(super::Error::Store(
@@ -144,6 +296,33 @@ impl Store {
                     target.insert(k.value(), v.value())?;
                 }
             }
+
+            if let Ok(source) = rx.open_table(DEAD_LETTERS) {
+                let mut target = wx.open_table(DEAD_LETTERS)?;
+
+                for entry in source.iter()? {
+                    let (k, v) = entry?;
+                    target.insert(k.value(), v.value())?;
+                }
+            }
+
+            if let Ok(source) = rx.open_table(KV_UNDO) {
+                let mut target = wx.open_table(KV_UNDO)?;
+
+                for entry in source.iter()? {
+                    let (k, v) = entry?;
+                    target.insert(k.value(), v.value())?;
+                }
+            }
+
+            if let Ok(source) = rx.open_table(KV_REDO) {
+                let mut target = wx.open_table(KV_REDO)?;
+
+                for entry in source.iter()? {
+                    let (k, v) = entry?;
+                    target.insert(k.value(), v.value())?;
+                }
+            }
         }
 
         wx.commit()?;
@@ -248,4 +427,148 @@ impl StoreTrait for Store {
         }
         Ok(undos.into())
     }
+
+    async fn last_log_seq(&self) -> Result<Option<LogSeq>, super::Error> {
+        Self::load_log_seq(&self.db)
+    }
+
+    async fn read_log(
+        &self,
+        after: Option<LogSeq>,
+        limit: usize,
+    ) -> Result<Vec<(LogSeq, LogEntry)>, super::Error> {
+        let rx = self.db.begin_read()?;
+
+        let table = match rx.open_table(WAL) {
+            Ok(table) => table,
+            Err(redb::TableError::TableDoesNotExist(_)) => return Ok(vec![]),
+            Err(e) => return Err(e.into()),
+        };
+
+        let start = after.map(|seq| seq + 1).unwrap_or_default();
+
+        table
+            .range(start..)?
+            .take(limit)
+            .map(|entry| {
+                let (k, v) = entry?;
+                Ok((k.value(), v.value()))
+            })
+            .collect()
+    }
+
+    async fn add_dead_letters(&self, letters: &[DeadLetter]) -> Result<(), super::Error> {
+        if letters.is_empty() {
+            return Ok(());
+        }
+
+        let wx = self.db.begin_write()?;
+        {
+            let mut table = wx.open_table(DEAD_LETTERS)?;
+            let first = table.last()?.map(|(k, _)| k.value() + 1).unwrap_or(1);
+
+            for (id, letter) in (first..).zip(letters) {
+                table.insert(id, letter)?;
+            }
+        }
+        wx.commit()?;
+
+        Ok(())
+    }
+
+    async fn list_dead_letters(
+        &self,
+        worker: Option<&str>,
+    ) -> Result<Vec<(DeadLetterId, DeadLetter)>, super::Error> {
+        let rx = self.db.begin_read()?;
+
+        let table = match rx.open_table(DEAD_LETTERS) {
+            Ok(table) => table,
+            Err(redb::TableError::TableDoesNotExist(_)) => return Ok(vec![]),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut out = vec![];
+
+        for entry in table.iter()? {
+            let (k, v) = entry?;
+            let letter = v.value();
+
+            if worker.is_none_or(|x| x == letter.worker) {
+                out.push((k.value(), letter));
+            }
+        }
+
+        Ok(out)
+    }
+
+    async fn get_dead_letter(&self, id: DeadLetterId) -> Result<Option<DeadLetter>, super::Error> {
+        let rx = self.db.begin_read()?;
+
+        let table = match rx.open_table(DEAD_LETTERS) {
+            Ok(table) => table,
+            Err(redb::TableError::TableDoesNotExist(_)) => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        let letter = table.get(id)?;
+        Ok(letter.map(|x| x.value()))
+    }
+
+    async fn remove_dead_letter(&self, id: DeadLetterId) -> Result<(), super::Error> {
+        let wx = self.db.begin_write()?;
+        wx.open_table(DEAD_LETTERS)?.remove(id)?;
+        wx.commit()?;
+
+        Ok(())
+    }
+
+    async fn get_kv_undo(
+        &self,
+        id: &str,
+        slot: BlockSlot,
+        block_hash: &[u8],
+    ) -> Result<Option<KvBatch>, super::Error> {
+        let rx = self.db.begin_read()?;
+
+        let table = match rx.open_table(KV_UNDO) {
+            Ok(table) => table,
+            Err(redb::TableError::TableDoesNotExist(_)) => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        let batch = table.get((id, slot, block_hash))?;
+        Ok(batch.map(|x| x.value()))
+    }
+
+    async fn get_kv_redo(&self, id: &str) -> Result<Option<KvBatch>, super::Error> {
+        let rx = self.db.begin_read()?;
+
+        let table = match rx.open_table(KV_REDO) {
+            Ok(table) => table,
+            Err(redb::TableError::TableDoesNotExist(_)) => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        let batch = table.get(id.to_owned())?;
+        Ok(batch.map(|x| x.value()))
+    }
+
+    async fn clear_kv_redo(&self, ids: &[WorkerId]) -> Result<(), super::Error> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+
+        let wx = self.db.begin_write()?;
+        {
+            let mut table = wx.open_table(KV_REDO)?;
+
+            for id in ids {
+                table.remove(id.clone())?;
+            }
+        }
+        wx.commit()?;
+
+        Ok(())
+    }
 }
@@ -0,0 +1,589 @@
+//! SQLite implementation of the store.
+//!
+//! Keeps the same data as the redb store, in tables that can be inspected
+//! and backed up with standard SQL tooling. Log entries, dead letters and KV
+//! batches are kept as protobuf blobs, next to a few plain columns to make
+//! them easier to query.
+
+use prost::Message;
+use rusqlite::{backup::Backup, params, Connection, OptionalExtension as _};
+use std::{
+    collections::VecDeque,
+    path::Path,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use crate::{Block, BlockSlot, ChainPoint, Error};
+
+use super::StoreTrait;
+pub use super::{AtomicUpdateTrait, DeadLetter, DeadLetterId, KvBatch, LogEntry, LogSeq, WorkerId};
+
+const MIGRATIONS: &[&str] = &[r#"
+    CREATE TABLE wal (
+        seq INTEGER PRIMARY KEY,
+        slot INTEGER NOT NULL,
+        hash BLOB NOT NULL,
+        entry BLOB NOT NULL
+    );
+
+    CREATE TABLE cursors (
+        worker TEXT PRIMARY KEY,
+        seq INTEGER NOT NULL
+    );
+
+    CREATE TABLE dead_letters (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        worker TEXT NOT NULL,
+        channel INTEGER NOT NULL,
+        message TEXT NOT NULL,
+        letter BLOB NOT NULL
+    );
+
+    CREATE INDEX dead_letters_worker ON dead_letters (worker);
+
+    CREATE TABLE kv_undo (
+        worker TEXT NOT NULL,
+        slot INTEGER NOT NULL,
+        block_hash BLOB NOT NULL,
+        batch BLOB NOT NULL,
+        PRIMARY KEY (worker, slot, block_hash)
+    );
+
+    CREATE TABLE kv_redo (
+        worker TEXT PRIMARY KEY,
+        batch BLOB NOT NULL
+    );
+"#];
+
+/// Bring the schema of a database up to date, tracking the migrations
+/// already applied in its `user_version`.
+pub(crate) fn migrate(conn: &mut Connection, migrations: &[&str]) -> Result<(), String> {
+    let tx = conn.transaction().map_err(|err| err.to_string())?;
+
+    let version: usize = tx
+        .pragma_query_value(None, "user_version", |row| row.get(0))
+        .map_err(|err| err.to_string())?;
+
+    if version > migrations.len() {
+        return Err(format!(
+            "database schema version {version} is newer than the supported {}",
+            migrations.len()
+        ));
+    }
+
+    for (i, migration) in migrations.iter().enumerate().skip(version) {
+        tx.execute_batch(migration).map_err(|err| err.to_string())?;
+        tx.pragma_update(None, "user_version", i + 1)
+            .map_err(|err| err.to_string())?;
+    }
+
+    tx.commit().map_err(|err| err.to_string())
+}
+
+/// Copy a database into a new in-memory one.
+pub(crate) fn copy_to_memory(conn: &Connection) -> rusqlite::Result<Connection> {
+    let mut target = Connection::open_in_memory()?;
+
+    Backup::new(conn, &mut target)?.run_to_completion(100, Duration::ZERO, None)?;
+
+    Ok(target)
+}
+
+enum Change {
+    Cursor(WorkerId),
+    SaveKvUndo(WorkerId, BlockSlot, Vec<u8>, Vec<u8>),
+    RemoveKvUndo(WorkerId, BlockSlot, Vec<u8>),
+    PruneKvUndo(WorkerId, BlockSlot),
+    KvRedo(WorkerId, Vec<u8>),
+    DeadLetter(DeadLetter),
+}
+
+/// Changes are buffered and written in a single transaction on commit, so
+/// the connection isn't held while workers are being handled.
+pub struct AtomicUpdate {
+    conn: Arc<Mutex<Connection>>,
+    changes: Option<Vec<Change>>,
+    log_seq: LogSeq,
+}
+impl AtomicUpdate {
+    fn push(&mut self, change: Change) -> Result<(), super::Error> {
+        let Some(changes) = self.changes.as_mut() else {
+            return Err(super::Error::Store(
+                "Transaction already commited".to_string(),
+            ));
+        };
+
+        changes.push(change);
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl AtomicUpdateTrait for AtomicUpdate {
+    async fn update_worker_cursor(&mut self, id: &str) -> Result<(), super::Error> {
+        self.push(Change::Cursor(id.to_owned()))
+    }
+
+    async fn save_kv_undo(
+        &mut self,
+        id: &str,
+        slot: BlockSlot,
+        block_hash: &[u8],
+        batch: &KvBatch,
+    ) -> Result<(), super::Error> {
+        self.push(Change::SaveKvUndo(
+            id.to_owned(),
+            slot,
+            block_hash.to_vec(),
+            batch.encode_to_vec(),
+        ))
+    }
+
+    async fn remove_kv_undo(
+        &mut self,
+        id: &str,
+        slot: BlockSlot,
+        block_hash: &[u8],
+    ) -> Result<(), super::Error> {
+        self.push(Change::RemoveKvUndo(
+            id.to_owned(),
+            slot,
+            block_hash.to_vec(),
+        ))
+    }
+
+    async fn prune_kv_undo(&mut self, id: &str, slot: BlockSlot) -> Result<(), super::Error> {
+        self.push(Change::PruneKvUndo(id.to_owned(), slot))
+    }
+
+    async fn set_kv_redo(&mut self, id: &str, batch: &KvBatch) -> Result<(), super::Error> {
+        self.push(Change::KvRedo(id.to_owned(), batch.encode_to_vec()))
+    }
+
+    async fn add_dead_letter(&mut self, letter: &DeadLetter) -> Result<(), super::Error> {
+        self.push(Change::DeadLetter(letter.clone()))
+    }
+
+    async fn commit(&mut self) -> Result<(), super::Error> {
+        let Some(changes) = self.changes.take() else {
+            return Err(super::Error::Store(
+                "Transaction already commited".to_string(),
+            ));
+        };
+
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+
+        for change in changes {
+            match change {
+                Change::Cursor(id) => tx.execute(
+                    "INSERT OR REPLACE INTO cursors (worker, seq) VALUES (?1, ?2)",
+                    params![id, self.log_seq],
+                )?,
+                Change::SaveKvUndo(id, slot, hash, batch) => tx.execute(
+                    "INSERT OR REPLACE INTO kv_undo (worker, slot, block_hash, batch) VALUES (?1, ?2, ?3, ?4)",
+                    params![id, slot, hash, batch],
+                )?,
+                Change::RemoveKvUndo(id, slot, hash) => tx.execute(
+                    "DELETE FROM kv_undo WHERE worker = ?1 AND slot = ?2 AND block_hash = ?3",
+                    params![id, slot, hash],
+                )?,
+                Change::PruneKvUndo(id, slot) => tx.execute(
+                    "DELETE FROM kv_undo WHERE worker = ?1 AND slot < ?2",
+                    params![id, slot],
+                )?,
+                Change::KvRedo(id, batch) => tx.execute(
+                    "INSERT OR REPLACE INTO kv_redo (worker, batch) VALUES (?1, ?2)",
+                    params![id, batch],
+                )?,
+                Change::DeadLetter(letter) => tx.execute(
+                    "INSERT INTO dead_letters (worker, channel, message, letter) VALUES (?1, ?2, ?3, ?4)",
+                    params![
+                        letter.worker,
+                        letter.channel,
+                        letter.message,
+                        letter.encode_to_vec()
+                    ],
+                )?,
+            };
+        }
+
+        tx.commit()?;
+
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+pub struct Store {
+    conn: Arc<Mutex<Connection>>,
+    log_seq: LogSeq,
+}
+
+impl Store {
+    pub fn in_memory() -> Result<Self, super::Error> {
+        Self::from_connection(Connection::open_in_memory()?)
+    }
+
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, super::Error> {
+        let conn = Connection::open(path)?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+
+        Self::from_connection(conn)
+    }
+
+    fn from_connection(mut conn: Connection) -> Result<Self, super::Error> {
+        migrate(&mut conn, MIGRATIONS).map_err(Error::Store)?;
+
+        let log_seq = Self::load_log_seq(&conn)?.unwrap_or_default();
+
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+            log_seq,
+        })
+    }
+
+    pub fn into_ephemeral(&mut self) -> Result<Self, super::Error> {
+        let conn = copy_to_memory(&self.conn.lock().unwrap())?;
+
+        Self::from_connection(conn)
+    }
+
+    fn load_log_seq(conn: &Connection) -> Result<Option<LogSeq>, Error> {
+        let seq = conn.query_row("SELECT MAX(seq) FROM wal", [], |row| row.get(0))?;
+        Ok(seq)
+    }
+
+    fn get_entry(&self, seq: LogSeq) -> Result<Option<LogEntry>, Error> {
+        let conn = self.conn.lock().unwrap();
+
+        let entry: Option<Vec<u8>> = conn
+            .query_row("SELECT entry FROM wal WHERE seq = ?1", [seq], |row| {
+                row.get(0)
+            })
+            .optional()?;
+
+        entry.map(|x| decode(&x)).transpose()
+    }
+}
+
+fn decode<T: Message + Default>(bytes: &[u8]) -> Result<T, Error> {
+    T::decode(bytes).map_err(|err| Error::Store(err.to_string()))
+}
+
+#[async_trait::async_trait]
+impl StoreTrait for Store {
+    async fn find_chain_point(&self, seq: LogSeq) -> Result<Option<ChainPoint>, Error> {
+        let entry = self.get_entry(seq)?;
+        let block = Block::from_bytes(&entry.unwrap().next_block);
+
+        Ok(Some(block.chain_point()))
+    }
+
+    async fn write_ahead(
+        &mut self,
+        undo_blocks: &[Block],
+        next_block: &Block,
+    ) -> Result<LogSeq, Error> {
+        let entry = LogEntry {
+            next_block: next_block.to_bytes(),
+            undo_blocks: undo_blocks.iter().map(|x| x.to_bytes()).collect(),
+        };
+
+        self.conn.lock().unwrap().execute(
+            "INSERT INTO wal (seq, slot, hash, entry) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                self.log_seq + 1,
+                next_block.slot(),
+                next_block.hash(),
+                entry.encode_to_vec()
+            ],
+        )?;
+
+        self.log_seq += 1;
+        Ok(self.log_seq)
+    }
+
+    async fn get_worker_cursor(&self, id: &str) -> Result<Option<LogSeq>, super::Error> {
+        let conn = self.conn.lock().unwrap();
+
+        let cursor = conn
+            .query_row("SELECT seq FROM cursors WHERE worker = ?1", [id], |row| {
+                row.get(0)
+            })
+            .optional()?;
+
+        Ok(cursor)
+    }
+
+    async fn start_atomic_update(
+        &self,
+        log_seq: LogSeq,
+    ) -> Result<super::AtomicUpdate, super::Error> {
+        Ok(super::AtomicUpdate::Sqlite(AtomicUpdate {
+            conn: self.conn.clone(),
+            changes: Some(vec![]),
+            log_seq,
+        }))
+    }
+
+    async fn handle_reset(&self, point: ChainPoint) -> Result<Vec<Block>, super::Error> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT entry FROM wal ORDER BY seq DESC")?;
+        let mut rows = stmt.query([])?;
+
+        let mut undos = VecDeque::new();
+        while let Some(row) = rows.next()? {
+            let entry: LogEntry = decode(&row.get::<_, Vec<u8>>(0)?)?;
+            let decoded = Block::from_bytes(&entry.next_block);
+            if decoded.slot() <= point.slot() {
+                break;
+            } else {
+                undos.push_front(decoded);
+            }
+        }
+        Ok(undos.into())
+    }
+
+    async fn last_log_seq(&self) -> Result<Option<LogSeq>, super::Error> {
+        Self::load_log_seq(&self.conn.lock().unwrap())
+    }
+
+    async fn read_log(
+        &self,
+        after: Option<LogSeq>,
+        limit: usize,
+    ) -> Result<Vec<(LogSeq, LogEntry)>, super::Error> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt =
+            conn.prepare("SELECT seq, entry FROM wal WHERE seq > ?1 ORDER BY seq LIMIT ?2")?;
+
+        // seq starts at 1, so 0 reads from the beginning
+        let rows = stmt.query_map(params![after.unwrap_or_default(), limit], |row| {
+            Ok((row.get(0)?, row.get::<_, Vec<u8>>(1)?))
+        })?;
+
+        rows.map(|row| {
+            let (seq, entry) = row?;
+            Ok((seq, decode(&entry)?))
+        })
+        .collect()
+    }
+
+    async fn add_dead_letters(&self, letters: &[DeadLetter]) -> Result<(), super::Error> {
+        if letters.is_empty() {
+            return Ok(());
+        }
+
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+
+        for letter in letters {
+            tx.execute(
+                "INSERT INTO dead_letters (worker, channel, message, letter) VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    letter.worker,
+                    letter.channel,
+                    letter.message,
+                    letter.encode_to_vec()
+                ],
+            )?;
+        }
+
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    async fn list_dead_letters(
+        &self,
+        worker: Option<&str>,
+    ) -> Result<Vec<(DeadLetterId, DeadLetter)>, super::Error> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, letter FROM dead_letters WHERE ?1 IS NULL OR worker = ?1 ORDER BY id",
+        )?;
+
+        let rows = stmt.query_map([worker], |row| Ok((row.get(0)?, row.get::<_, Vec<u8>>(1)?)))?;
+
+        rows.map(|row| {
+            let (id, letter) = row?;
+            Ok((id, decode(&letter)?))
+        })
+        .collect()
+    }
+
+    async fn get_dead_letter(&self, id: DeadLetterId) -> Result<Option<DeadLetter>, super::Error> {
+        let conn = self.conn.lock().unwrap();
+
+        let letter: Option<Vec<u8>> = conn
+            .query_row(
+                "SELECT letter FROM dead_letters WHERE id = ?1",
+                [id],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        letter.map(|x| decode(&x)).transpose()
+    }
+
+    async fn remove_dead_letter(&self, id: DeadLetterId) -> Result<(), super::Error> {
+        self.conn
+            .lock()
+            .unwrap()
+            .execute("DELETE FROM dead_letters WHERE id = ?1", [id])?;
+
+        Ok(())
+    }
+
+    async fn get_kv_undo(
+        &self,
+        id: &str,
+        slot: BlockSlot,
+        block_hash: &[u8],
+    ) -> Result<Option<KvBatch>, super::Error> {
+        let conn = self.conn.lock().unwrap();
+
+        let batch: Option<Vec<u8>> = conn
+            .query_row(
+                "SELECT batch FROM kv_undo WHERE worker = ?1 AND slot = ?2 AND block_hash = ?3",
+                params![id, slot, block_hash],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        batch.map(|x| decode(&x)).transpose()
+    }
+
+    async fn get_kv_redo(&self, id: &str) -> Result<Option<KvBatch>, super::Error> {
+        let conn = self.conn.lock().unwrap();
+
+        let batch: Option<Vec<u8>> = conn
+            .query_row("SELECT batch FROM kv_redo WHERE worker = ?1", [id], |row| {
+                row.get(0)
+            })
+            .optional()?;
+
+        batch.map(|x| decode(&x)).transpose()
+    }
+
+    async fn clear_kv_redo(&self, ids: &[WorkerId]) -> Result<(), super::Error> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+
+        for id in ids {
+            tx.execute("DELETE FROM kv_redo WHERE worker = ?1", [id])?;
+        }
+
+        tx.commit()?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::KvWrite;
+
+    fn letter(worker: &str) -> DeadLetter {
+        DeadLetter {
+            worker: worker.into(),
+            channel: 1,
+            event: b"{}".to_vec(),
+            code: None,
+            message: "boom".into(),
+            block: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn commits_worker_progress() {
+        let store = Store::in_memory().unwrap();
+
+        let batch = KvBatch {
+            writes: vec![KvWrite {
+                key: "a".into(),
+                value: Some(vec![1]),
+            }],
+        };
+
+        let mut update = store.start_atomic_update(7).await.unwrap();
+        update.update_worker_cursor("w").await.unwrap();
+        update.save_kv_undo("w", 10, b"h", &batch).await.unwrap();
+        update.set_kv_redo("w", &batch).await.unwrap();
+        update.add_dead_letter(&letter("w")).await.unwrap();
+
+        // nothing is visible until the commit
+        assert_eq!(store.get_worker_cursor("w").await.unwrap(), None);
+        assert!(store.list_dead_letters(None).await.unwrap().is_empty());
+
+        update.commit().await.unwrap();
+        assert!(update.commit().await.is_err());
+
+        assert_eq!(store.get_worker_cursor("w").await.unwrap(), Some(7));
+        assert!(store.get_kv_undo("w", 10, b"h").await.unwrap().is_some());
+        assert!(store.get_kv_redo("w").await.unwrap().is_some());
+        assert_eq!(store.list_dead_letters(Some("w")).await.unwrap().len(), 1);
+
+        store.clear_kv_redo(&["w".into()]).await.unwrap();
+        assert!(store.get_kv_redo("w").await.unwrap().is_none());
+
+        // migrations already applied are skipped
+        let store = store.clone().into_ephemeral().unwrap();
+        assert_eq!(store.get_worker_cursor("w").await.unwrap(), Some(7));
+        assert_eq!(store.last_log_seq().await.unwrap(), None);
+        assert!(store.read_log(None, 10).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn keeps_dead_letters() {
+        let store = Store::in_memory().unwrap();
+
+        store
+            .add_dead_letters(&[letter("a"), letter("b"), letter("a")])
+            .await
+            .unwrap();
+
+        let ids = store
+            .list_dead_letters(Some("a"))
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![1, 3]);
+
+        store.remove_dead_letter(3).await.unwrap();
+        assert!(store.get_dead_letter(3).await.unwrap().is_none());
+        assert_eq!(store.get_dead_letter(2).await.unwrap().unwrap().worker, "b");
+
+        // ids aren't reused
+        store.add_dead_letters(&[letter("c")]).await.unwrap();
+        assert_eq!(store.list_dead_letters(None).await.unwrap().len(), 3);
+        assert!(store.get_dead_letter(4).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn prunes_kv_undo() {
+        let store = Store::in_memory().unwrap();
+        let batch = KvBatch::default();
+
+        let mut update = store.start_atomic_update(0).await.unwrap();
+        update.save_kv_undo("a", 10, b"h10", &batch).await.unwrap();
+        update.save_kv_undo("a", 20, b"h20", &batch).await.unwrap();
+        update.save_kv_undo("b", 10, b"h10", &batch).await.unwrap();
+        update.prune_kv_undo("a", 20).await.unwrap();
+        update.commit().await.unwrap();
+
+        assert!(store.get_kv_undo("a", 10, b"h10").await.unwrap().is_none());
+        assert!(store.get_kv_undo("a", 20, b"h20").await.unwrap().is_some());
+        assert!(store.get_kv_undo("b", 10, b"h10").await.unwrap().is_some());
+    }
+}
@@ -60,7 +60,7 @@ async fn faucet_claim() {
     });
 
     runtime
-        .register_worker_from_file("faucet", "tests/faucet.wasm", config)
+        .register_worker_from_file("faucet", "tests/faucet.wasm", config, Default::default())
         .await
         .unwrap();
 
@@ -0,0 +1,76 @@
+;; Core module of a worker following the block channel, built into a
+;; component by the tests. Its behavior is driven by the `mode` KV key:
+;;   - missing or 0: increments the `count` key and acknowledges
+;;   - 1: fails with a handle error
+;;   - 2: traps
+;; Every `init` increments the `init` key.
+(module
+  (import "balius:app/driver@0.1.0" "register-channel"
+    (func $register_channel (param i32 i32 i64 i32 i32 i32 i32 i32 i32 i32 i32 i32)))
+  (import "balius:app/kv@0.1.0" "get-value" (func $get_value (param i32 i32 i32)))
+  (import "balius:app/kv@0.1.0" "set-value" (func $set_value (param i32 i32 i32 i32 i32)))
+
+  (memory (export "memory") 16)
+  (global $heap (mut i32) (i32.const 4096))
+
+  (data (i32.const 16) "init")
+  (data (i32.const 32) "count")
+  (data (i32.const 48) "mode")
+  (data (i32.const 64) "boom")
+
+  ;; value of a one byte key, zero when missing
+  (func $read (param $key i32) (param $len i32) (result i32)
+    (call $get_value (local.get $key) (local.get $len) (i32.const 1024))
+    (if (result i32)
+      (i32.and
+        (i32.eqz (i32.load8_u (i32.const 1024)))
+        (i32.ne (i32.load (i32.const 1032)) (i32.const 0)))
+      (then (i32.load8_u (i32.load (i32.const 1028))))
+      (else (i32.const 0))))
+
+  (func $incr (param $key i32) (param $len i32)
+    (i32.store8 (i32.const 1008)
+      (i32.add (call $read (local.get $key) (local.get $len)) (i32.const 1)))
+    (call $set_value
+      (local.get $key) (local.get $len) (i32.const 1008) (i32.const 1) (i32.const 1040)))
+
+  (func (export "init") (param i32 i32)
+    ;; channel 0, event-pattern::block
+    (call $register_channel
+      (i32.const 0) (i32.const 13) (i64.const 0)
+      (i32.const 0) (i32.const 0) (i32.const 0) (i32.const 0) (i32.const 0)
+      (i32.const 0) (i32.const 0) (i32.const 0) (i32.const 0))
+    (call $incr (i32.const 16) (i32.const 4))
+    (global.set $heap (i32.const 4096)))
+
+  (func (export "handle")
+    (param i32 i32 i64 i32 i64 i64 i32 i32 i64 i64 i64 i64 i64) (result i32)
+    (local $mode i32)
+    (local.set $mode (call $read (i32.const 48) (i32.const 4)))
+    (if (i32.eq (local.get $mode) (i32.const 2))
+      (then unreachable))
+    (if (i32.eq (local.get $mode) (i32.const 1))
+      (then
+        ;; err(handle-error { message: "boom", code: 7 })
+        (i32.store8 (i32.const 1100) (i32.const 1))
+        (i32.store (i32.const 1104) (i32.const 64))
+        (i32.store (i32.const 1108) (i32.const 4))
+        (i32.store (i32.const 1112) (i32.const 7))
+        (return (i32.const 1100))))
+    (call $incr (i32.const 32) (i32.const 5))
+    ;; ok(response::acknowledge)
+    (i32.store8 (i32.const 1100) (i32.const 0))
+    (i32.store8 (i32.const 1104) (i32.const 0))
+    (i32.const 1100))
+
+  (func (export "cabi_post_handle") (param i32)
+    (global.set $heap (i32.const 4096)))
+
+  (func (export "cabi_realloc") (param i32 i32) (param $align i32) (param $size i32) (result i32)
+    (local $ptr i32)
+    (local.set $ptr
+      (i32.and
+        (i32.add (global.get $heap) (i32.sub (local.get $align) (i32.const 1)))
+        (i32.sub (i32.const 0) (local.get $align))))
+    (global.set $heap (i32.add (local.get $ptr) (local.get $size)))
+    (local.get $ptr)))
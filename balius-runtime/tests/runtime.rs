@@ -0,0 +1,565 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use balius_runtime::{
+    drivers,
+    kv::{mock::MockKv, KvProvider},
+    store::{redb::Store as RedbStore, KvWrite},
+    AtomicUpdateTrait, Block, Error, FailurePolicy, KvBatch, Runtime, SlotRange, Store, StoreTrait,
+    WorkerLimits, WorkerOptions,
+};
+use serde_json::json;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::{ReceiverStream, TcpListenerStream};
+use tokio_util::sync::CancellationToken;
+use utxorpc::spec::sync;
+use wit_component::{ComponentEncoder, StringEncoding};
+
+/// The counter worker of `fixtures/counter.wat`, as a component.
+fn counter_worker() -> Vec<u8> {
+    let mut resolve = wit_parser::Resolve::default();
+    let (pkg, _) = resolve
+        .push_path(concat!(env!("CARGO_MANIFEST_DIR"), "/../wit"))
+        .unwrap();
+    let world = resolve.select_world(pkg, Some("worker")).unwrap();
+
+    let mut module = wat::parse_file("tests/fixtures/counter.wat").unwrap();
+    wit_component::embed_component_metadata(&mut module, &resolve, world, StringEncoding::UTF8)
+        .unwrap();
+
+    ComponentEncoder::default()
+        .validate(true)
+        .module(&module)
+        .unwrap()
+        .encode()
+        .unwrap()
+}
+
+fn block(slot: u64) -> Block {
+    use utxorpc::spec::cardano;
+
+    Block::Cardano(cardano::Block {
+        header: Some(cardano::BlockHeader {
+            slot,
+            hash: slot.to_be_bytes().to_vec().into(),
+            height: slot,
+        }),
+        body: Some(Default::default()),
+        timestamp: 0,
+    })
+}
+
+fn runtime(kv: &MockKv) -> Runtime {
+    Runtime::builder(Store::Redb(RedbStore::in_memory().unwrap()))
+        .with_kv(kv.clone().into())
+        .build()
+        .unwrap()
+}
+
+async fn read(kv: &MockKv, worker: &str, key: &str) -> Option<u8> {
+    kv.clone()
+        .get_value(worker, key.to_owned())
+        .await
+        .ok()
+        .map(|x| x[0])
+}
+
+async fn set_mode(kv: &MockKv, worker: &str, mode: u8) {
+    kv.clone()
+        .set_value(worker, "mode".to_owned(), vec![mode])
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn limits_apply_from_registration() {
+    let kv = MockKv::default();
+    let runtime = runtime(&kv);
+
+    let options = WorkerOptions {
+        limits: WorkerLimits {
+            fuel: Some(1),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let result = runtime
+        .register_worker("counter", &counter_worker(), json!({}), options)
+        .await;
+
+    assert!(matches!(result, Err(Error::OutOfFuel(1))));
+}
+
+#[tokio::test]
+async fn trap_reloads_instance_running_init_again() {
+    let kv = MockKv::default();
+    let mut runtime = runtime(&kv);
+
+    runtime
+        .register_worker("counter", &counter_worker(), json!({}), Default::default())
+        .await
+        .unwrap();
+    assert_eq!(read(&kv, "counter", "init").await, Some(1));
+
+    set_mode(&kv, "counter", 2).await;
+    runtime.handle_chain(&Vec::new(), &block(1)).await.unwrap();
+
+    // the trapped event was skipped, and the instance replacing the trapped
+    // one ran `init` again
+    assert_eq!(read(&kv, "counter", "init").await, Some(2));
+    assert_eq!(read(&kv, "counter", "count").await, None);
+
+    set_mode(&kv, "counter", 0).await;
+    runtime.handle_chain(&Vec::new(), &block(2)).await.unwrap();
+
+    assert_eq!(read(&kv, "counter", "count").await, Some(1));
+}
+
+#[tokio::test]
+async fn pooled_instances_cant_write_to_kv() {
+    let kv = MockKv::default();
+    let runtime = runtime(&kv);
+
+    let options = WorkerOptions {
+        limits: WorkerLimits {
+            request_instances: Some(1),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    runtime
+        .register_worker("counter", &counter_worker(), json!({}), options)
+        .await
+        .unwrap();
+    assert_eq!(read(&kv, "counter", "init").await, Some(1));
+
+    // the worker has no request handler, but the pooled instance serving the
+    // request ran `init`, whose KV write was rejected
+    let result = runtime.handle_request("counter", "ping", vec![]).await;
+    assert!(matches!(result, Err(Error::NoTarget)));
+    assert_eq!(read(&kv, "counter", "init").await, Some(1));
+}
+
+/// Sync upstream serving `blocks`, recording the intersect points it's
+/// asked to follow the tip from.
+#[derive(Default)]
+struct MockSync {
+    blocks: Vec<Block>,
+    intersects: Arc<Mutex<Vec<Vec<sync::BlockRef>>>>,
+    tips: Mutex<Vec<mpsc::Sender<Result<sync::FollowTipResponse, tonic::Status>>>>,
+}
+
+#[tonic::async_trait]
+impl sync::sync_service_server::SyncService for MockSync {
+    type FollowTipStream = ReceiverStream<Result<sync::FollowTipResponse, tonic::Status>>;
+
+    async fn fetch_block(
+        &self,
+        _: tonic::Request<sync::FetchBlockRequest>,
+    ) -> Result<tonic::Response<sync::FetchBlockResponse>, tonic::Status> {
+        Err(tonic::Status::unimplemented("fetch-block"))
+    }
+
+    async fn dump_history(
+        &self,
+        request: tonic::Request<sync::DumpHistoryRequest>,
+    ) -> Result<tonic::Response<sync::DumpHistoryResponse>, tonic::Status> {
+        let request = request.into_inner();
+        let start = request.start_token.map(|x| x.slot).unwrap_or_default();
+
+        let block = self
+            .blocks
+            .iter()
+            .filter(|x| x.slot() >= start)
+            .take(request.max_items as usize)
+            .map(|Block::Cardano(x)| sync::AnyChainBlock {
+                native_bytes: Default::default(),
+                chain: Some(sync::any_chain_block::Chain::Cardano(x.clone())),
+            })
+            .collect();
+
+        Ok(tonic::Response::new(sync::DumpHistoryResponse {
+            block,
+            next_token: None,
+        }))
+    }
+
+    async fn follow_tip(
+        &self,
+        request: tonic::Request<sync::FollowTipRequest>,
+    ) -> Result<tonic::Response<Self::FollowTipStream>, tonic::Status> {
+        let intersect = request.into_inner().intersect;
+        self.intersects.lock().unwrap().push(intersect.clone());
+
+        let (tx, rx) = mpsc::channel(1);
+        let reset = sync::follow_tip_response::Action::Reset(intersect[0].clone());
+        tx.send(Ok(sync::FollowTipResponse {
+            action: Some(reset),
+            tip: None,
+        }))
+        .await
+        .unwrap();
+
+        // keep the tip open, with no new blocks
+        self.tips.lock().unwrap().push(tx);
+
+        Ok(tonic::Response::new(ReceiverStream::new(rx)))
+    }
+
+    async fn read_tip(
+        &self,
+        _: tonic::Request<sync::ReadTipRequest>,
+    ) -> Result<tonic::Response<sync::ReadTipResponse>, tonic::Status> {
+        Err(tonic::Status::unimplemented("read-tip"))
+    }
+}
+
+#[tokio::test]
+async fn chain_sync_starts_from_the_block_at_the_start_slot() {
+    let intersects = Arc::new(Mutex::new(vec![]));
+    let upstream = MockSync {
+        blocks: vec![block(8), block(12), block(15)],
+        intersects: intersects.clone(),
+        ..Default::default()
+    };
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let endpoint_url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(
+        tonic::transport::Server::builder()
+            .add_service(sync::sync_service_server::SyncServiceServer::new(upstream))
+            .serve_with_incoming(TcpListenerStream::new(listener)),
+    );
+
+    let kv = MockKv::default();
+    let runtime = runtime(&kv);
+
+    let options = WorkerOptions {
+        range: SlotRange {
+            since: Some(10),
+            until: None,
+        },
+        ..Default::default()
+    };
+
+    runtime
+        .register_worker("counter", &counter_worker(), json!({}), options)
+        .await
+        .unwrap();
+
+    let config = drivers::chainsync::Config {
+        endpoint_url,
+        headers: None,
+    };
+    let cancel = CancellationToken::new();
+    let driver = tokio::spawn(drivers::chainsync::run(
+        config,
+        runtime.clone(),
+        cancel.clone(),
+    ));
+
+    tokio::time::timeout(Duration::from_secs(10), async {
+        while read(&kv, "counter", "count").await.is_none() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("start block wasn't handled");
+
+    cancel.cancel();
+    driver.await.unwrap().unwrap();
+
+    // the tip is followed from the first block at or after the start slot,
+    // which was handled on its own
+    let Block::Cardano(start) = block(12);
+    let start = start.header.unwrap();
+    let intersects = intersects.lock().unwrap().clone();
+    assert_eq!(intersects.len(), 1);
+    assert_eq!(intersects[0].len(), 1);
+    assert_eq!(intersects[0][0].slot, start.slot);
+    assert_eq!(intersects[0][0].hash, start.hash);
+
+    assert_eq!(read(&kv, "counter", "count").await, Some(1));
+}
+
+async fn register_with_policy(runtime: &Runtime, id: &str, policy: FailurePolicy) {
+    let options = WorkerOptions {
+        policy,
+        ..Default::default()
+    };
+
+    runtime
+        .register_worker(id, &counter_worker(), json!({}), options)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn skip_policy_moves_past_failed_events() {
+    let kv = MockKv::default();
+    let mut runtime = runtime(&kv);
+    register_with_policy(&runtime, "counter", FailurePolicy::Skip).await;
+
+    set_mode(&kv, "counter", 1).await;
+    runtime.handle_chain(&Vec::new(), &block(1)).await.unwrap();
+
+    set_mode(&kv, "counter", 0).await;
+    runtime.handle_chain(&Vec::new(), &block(2)).await.unwrap();
+
+    assert_eq!(read(&kv, "counter", "count").await, Some(1));
+    assert!(runtime.lagging_workers().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn halt_policy_freezes_the_cursor_until_upgrade() {
+    let kv = MockKv::default();
+    let mut runtime = runtime(&kv);
+    register_with_policy(&runtime, "counter", FailurePolicy::Halt).await;
+
+    set_mode(&kv, "counter", 1).await;
+    runtime.handle_chain(&Vec::new(), &block(1)).await.unwrap();
+
+    set_mode(&kv, "counter", 0).await;
+    runtime.handle_chain(&Vec::new(), &block(2)).await.unwrap();
+
+    // halted workers don't catch up either
+    assert_eq!(read(&kv, "counter", "count").await, None);
+    assert!(runtime.lagging_workers().await.unwrap().is_empty());
+
+    // the upgrade resumes from the failed block
+    runtime
+        .upgrade_worker("counter", &counter_worker(), json!({}), Default::default())
+        .await
+        .unwrap();
+    assert_eq!(runtime.lagging_workers().await.unwrap(), vec!["counter"]);
+    assert!(runtime.catch_up_worker("counter", 10).await.unwrap());
+
+    assert_eq!(read(&kv, "counter", "count").await, Some(2));
+}
+
+#[tokio::test]
+async fn failing_worker_doesnt_hold_back_others() {
+    let kv = MockKv::default();
+    let mut runtime = runtime(&kv);
+
+    let retry = FailurePolicy::Retry {
+        max_attempts: 3,
+        backoff_ms: 60_000,
+    };
+    register_with_policy(&runtime, "failing", retry).await;
+    register_with_policy(&runtime, "healthy", FailurePolicy::Skip).await;
+
+    set_mode(&kv, "failing", 1).await;
+
+    // the retry waits outside of the live feed
+    tokio::time::timeout(
+        Duration::from_secs(5),
+        runtime.handle_chain(&Vec::new(), &block(1)),
+    )
+    .await
+    .expect("failing worker held back the chain")
+    .unwrap();
+
+    assert_eq!(read(&kv, "healthy", "count").await, Some(1));
+    assert_eq!(read(&kv, "failing", "count").await, None);
+
+    // not retried before the backoff is over
+    assert!(runtime.lagging_workers().await.unwrap().is_empty());
+    assert!(!runtime.catch_up_worker("failing", 10).await.unwrap());
+}
+
+#[tokio::test]
+async fn retry_policy_replays_failed_block_from_the_log() {
+    let kv = MockKv::default();
+    let mut runtime = runtime(&kv);
+
+    let retry = FailurePolicy::Retry {
+        max_attempts: 2,
+        backoff_ms: 0,
+    };
+    register_with_policy(&runtime, "counter", retry).await;
+
+    set_mode(&kv, "counter", 1).await;
+    runtime.handle_chain(&Vec::new(), &block(1)).await.unwrap();
+    runtime.handle_chain(&Vec::new(), &block(2)).await.unwrap();
+    assert_eq!(runtime.lagging_workers().await.unwrap(), vec!["counter"]);
+
+    // still failing, retried again later
+    assert!(!runtime.catch_up_worker("counter", 10).await.unwrap());
+
+    set_mode(&kv, "counter", 0).await;
+    assert!(runtime.catch_up_worker("counter", 10).await.unwrap());
+    assert_eq!(read(&kv, "counter", "count").await, Some(2));
+}
+
+#[tokio::test]
+async fn retry_policy_halts_once_attempts_run_out() {
+    let kv = MockKv::default();
+    let mut runtime = runtime(&kv);
+
+    let retry = FailurePolicy::Retry {
+        max_attempts: 1,
+        backoff_ms: 0,
+    };
+    register_with_policy(&runtime, "counter", retry).await;
+
+    set_mode(&kv, "counter", 1).await;
+    runtime.handle_chain(&Vec::new(), &block(1)).await.unwrap();
+
+    let result = runtime.catch_up_worker("counter", 10).await;
+    assert!(matches!(result, Err(Error::WorkerHalted(_))));
+
+    set_mode(&kv, "counter", 0).await;
+    runtime.handle_chain(&Vec::new(), &block(2)).await.unwrap();
+
+    assert_eq!(read(&kv, "counter", "count").await, None);
+    assert!(runtime.lagging_workers().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn dead_letters_are_kept_and_replayed() {
+    let kv = MockKv::default();
+    let mut runtime = runtime(&kv);
+    register_with_policy(&runtime, "counter", FailurePolicy::DeadLetter).await;
+
+    set_mode(&kv, "counter", 1).await;
+    runtime.handle_chain(&Vec::new(), &block(1)).await.unwrap();
+
+    // the cursor moved on, with the failed event kept aside
+    assert!(runtime.lagging_workers().await.unwrap().is_empty());
+
+    let letters = runtime.dead_letters(Some("counter")).await.unwrap();
+    assert_eq!(letters.len(), 1);
+
+    let (id, letter) = &letters[0];
+    assert_eq!(letter.worker, "counter");
+    assert_eq!(letter.channel, 0);
+    assert_eq!(letter.code, Some(7));
+    assert!(letter.message.contains("boom"));
+    assert_eq!(letter.block.as_ref().map(|x| x.slot), Some(1));
+
+    // still failing, the letter stays
+    assert!(runtime.replay_dead_letter(*id).await.is_err());
+    assert_eq!(runtime.dead_letters(None).await.unwrap().len(), 1);
+
+    set_mode(&kv, "counter", 0).await;
+    runtime.replay_dead_letter(*id).await.unwrap();
+
+    assert_eq!(read(&kv, "counter", "count").await, Some(1));
+    assert!(runtime.dead_letters(None).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn resumed_worker_catches_up_from_where_it_paused() {
+    let kv = MockKv::default();
+    let mut runtime = runtime(&kv);
+    register_with_policy(&runtime, "counter", FailurePolicy::Skip).await;
+
+    runtime.handle_chain(&Vec::new(), &block(1)).await.unwrap();
+    runtime.pause_worker("counter").await.unwrap();
+
+    runtime.handle_chain(&Vec::new(), &block(2)).await.unwrap();
+    runtime.handle_chain(&Vec::new(), &block(3)).await.unwrap();
+
+    let result = runtime.handle_request("counter", "ping", vec![]).await;
+    assert!(matches!(result, Err(Error::WorkerPaused(_))));
+
+    // paused workers stay put, even behind the head
+    assert_eq!(read(&kv, "counter", "count").await, Some(1));
+    assert!(runtime.lagging_workers().await.unwrap().is_empty());
+    assert!(!runtime.catch_up_worker("counter", 10).await.unwrap());
+
+    runtime.resume_worker("counter").await.unwrap();
+    assert_eq!(runtime.lagging_workers().await.unwrap(), vec!["counter"]);
+    assert!(runtime.catch_up_worker("counter", 10).await.unwrap());
+    assert_eq!(read(&kv, "counter", "count").await, Some(3));
+
+    // back on the live feed
+    runtime.handle_chain(&Vec::new(), &block(4)).await.unwrap();
+    assert_eq!(read(&kv, "counter", "count").await, Some(4));
+}
+
+#[tokio::test]
+async fn resuming_lifts_the_halt() {
+    let kv = MockKv::default();
+    let mut runtime = runtime(&kv);
+    register_with_policy(&runtime, "counter", FailurePolicy::Halt).await;
+
+    set_mode(&kv, "counter", 1).await;
+    runtime.handle_chain(&Vec::new(), &block(1)).await.unwrap();
+    assert!(runtime.lagging_workers().await.unwrap().is_empty());
+
+    set_mode(&kv, "counter", 0).await;
+    runtime.resume_worker("counter").await.unwrap();
+
+    // replayed from the failed block
+    assert!(runtime.catch_up_worker("counter", 10).await.unwrap());
+    assert_eq!(read(&kv, "counter", "count").await, Some(1));
+}
+
+#[tokio::test]
+async fn rollback_reverts_kv_writes_of_undone_blocks() {
+    let kv = MockKv::default();
+    let mut runtime = runtime(&kv);
+
+    runtime
+        .register_worker("counter", &counter_worker(), json!({}), Default::default())
+        .await
+        .unwrap();
+
+    let options = WorkerOptions {
+        kv_rollback: false,
+        ..Default::default()
+    };
+    runtime
+        .register_worker("own_state", &counter_worker(), json!({}), options)
+        .await
+        .unwrap();
+
+    runtime.handle_chain(&Vec::new(), &block(1)).await.unwrap();
+    runtime.handle_chain(&Vec::new(), &block(2)).await.unwrap();
+    assert_eq!(read(&kv, "counter", "count").await, Some(2));
+
+    runtime
+        .handle_chain(&vec![block(2)], &block(3))
+        .await
+        .unwrap();
+
+    // block 2 no longer counts, block 3 does. Without the rollback, every
+    // event handled counts, the undo of block 2 included.
+    assert_eq!(read(&kv, "counter", "count").await, Some(2));
+    assert_eq!(read(&kv, "own_state", "count").await, Some(4));
+}
+
+#[tokio::test]
+async fn registration_applies_writes_left_by_a_crash() {
+    let kv = MockKv::default();
+    let store = Store::Redb(RedbStore::in_memory().unwrap());
+
+    // a chain commit whose writes didn't reach the provider
+    let writes = KvBatch {
+        writes: vec![KvWrite {
+            key: "count".into(),
+            value: Some(vec![5]),
+        }],
+    };
+    let mut update = store.start_atomic_update(0).await.unwrap();
+    update.set_kv_redo("counter", &writes).await.unwrap();
+    update.commit().await.unwrap();
+
+    let runtime = Runtime::builder(store.clone())
+        .with_kv(kv.clone().into())
+        .build()
+        .unwrap();
+    runtime
+        .register_worker("counter", &counter_worker(), json!({}), Default::default())
+        .await
+        .unwrap();
+
+    assert_eq!(read(&kv, "counter", "count").await, Some(5));
+    assert!(store.get_kv_redo("counter").await.unwrap().is_none());
+}
@@ -16,7 +16,7 @@ async fn wallet_balance() {
 
     let wasm = std::fs::read("tests/wallet.wasm").unwrap();
     runtime
-        .register_worker("wallet", &wasm, config)
+        .register_worker("wallet", &wasm, config, Default::default())
         .await
         .unwrap();
 
@@ -35,7 +35,7 @@ async fn faucet_claim() {
 
     let wasm = std::fs::read("tests/faucet.wasm").unwrap();
     runtime
-        .register_worker("faucet", &wasm, config)
+        .register_worker("faucet", &wasm, config, Default::default())
         .await
         .unwrap();
 
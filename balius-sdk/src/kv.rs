@@ -0,0 +1,270 @@
+//! Access to the key-value storage of the worker.
+//!
+//! The free functions are thin wrappers over the `kv` interface of the host
+//! that report missing keys as `None` instead of an error. On top of them,
+//! [`Kv`] and [`KvMap`] store typed values, encoded with serde as JSON (the
+//! default) or CBOR.
+//!
+//! ```ignore
+//! use balius_sdk::kv::{Cbor, Kv, KvMap};
+//!
+//! let total = Kv::<u64>::update("total", |x| x.unwrap_or_default() + 1)?;
+//!
+//! let orders = KvMap::<Order, Cbor>::new("orders");
+//! orders.put(&order.id, &order)?;
+//! ```
+
+use std::marker::PhantomData;
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::wit::balius::app::kv as wit;
+use crate::{Error, WorkerResult};
+
+pub use wit::{KvEntry, KvPage, Payload};
+
+/// Number of entries fetched at once by [`entries`].
+const PAGE_SIZE: u32 = 100;
+
+/// The value of a key, `None` if it's missing.
+pub fn get(key: &str) -> WorkerResult<Option<Payload>> {
+    match wit::get_value(key) {
+        Ok(value) => Ok(Some(value)),
+        Err(wit::KvError::NotFound(_)) => Ok(None),
+        Err(err) => Err(Error::KV(err)),
+    }
+}
+
+pub fn set(key: &str, value: &[u8]) -> WorkerResult<()> {
+    wit::set_value(key, value).map_err(Error::KV)
+}
+
+/// Remove a key. Removing a missing key is not an error.
+pub fn delete(key: &str) -> WorkerResult<()> {
+    wit::delete_value(key).map_err(Error::KV)
+}
+
+/// Set the key only if its current value is `expected`, `None` meaning the
+/// key is missing. Returns whether the value was set.
+pub fn compare_and_swap(key: &str, expected: Option<&[u8]>, value: &[u8]) -> WorkerResult<bool> {
+    wit::compare_and_swap(key, expected, value).map_err(Error::KV)
+}
+
+/// The values of several keys, in the same order, `None` for missing keys.
+pub fn get_many(keys: &[String]) -> WorkerResult<Vec<Option<Payload>>> {
+    wit::get_many(keys).map_err(Error::KV)
+}
+
+pub fn set_many(entries: &[KvEntry]) -> WorkerResult<()> {
+    wit::set_many(entries).map_err(Error::KV)
+}
+
+/// The keys under a prefix.
+pub fn keys(prefix: &str) -> WorkerResult<Vec<String>> {
+    wit::list_values(prefix).map_err(Error::KV)
+}
+
+/// A page of the entries under a prefix, starting after the `after` key.
+pub fn list_entries(prefix: &str, after: Option<&str>, limit: u32) -> WorkerResult<KvPage> {
+    wit::list_entries(prefix, after, limit).map_err(Error::KV)
+}
+
+/// Iterate every entry under a prefix in key order, fetching them a page at
+/// a time.
+pub fn entries(prefix: &str) -> Entries {
+    Entries {
+        prefix: prefix.to_owned(),
+        after: None,
+        page: Vec::new().into_iter(),
+        done: false,
+    }
+}
+
+pub struct Entries {
+    prefix: String,
+    after: Option<String>,
+    page: std::vec::IntoIter<KvEntry>,
+    done: bool,
+}
+
+impl Iterator for Entries {
+    type Item = WorkerResult<KvEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(entry) = self.page.next() {
+                return Some(Ok(entry));
+            }
+
+            if self.done {
+                return None;
+            }
+
+            let page = match list_entries(&self.prefix, self.after.as_deref(), PAGE_SIZE) {
+                Ok(page) => page,
+                Err(err) => {
+                    self.done = true;
+                    return Some(Err(err));
+                }
+            };
+
+            self.done = page.next.is_none();
+            self.after = page.next;
+            self.page = page.entries.into_iter();
+        }
+    }
+}
+
+/// How typed values are turned into bytes.
+pub trait Codec {
+    fn encode<T: Serialize>(value: &T) -> WorkerResult<Payload>;
+    fn decode<T: DeserializeOwned>(bytes: &[u8]) -> WorkerResult<T>;
+}
+
+fn codec_error(err: impl std::fmt::Display) -> Error {
+    Error::KV(wit::KvError::Internal(err.to_string()))
+}
+
+pub struct Json;
+
+impl Codec for Json {
+    fn encode<T: Serialize>(value: &T) -> WorkerResult<Payload> {
+        serde_json::to_vec(value).map_err(codec_error)
+    }
+
+    fn decode<T: DeserializeOwned>(bytes: &[u8]) -> WorkerResult<T> {
+        serde_json::from_slice(bytes).map_err(codec_error)
+    }
+}
+
+pub struct Cbor;
+
+impl Codec for Cbor {
+    fn encode<T: Serialize>(value: &T) -> WorkerResult<Payload> {
+        let mut bytes = vec![];
+        ciborium::into_writer(value, &mut bytes).map_err(codec_error)?;
+        Ok(bytes)
+    }
+
+    fn decode<T: DeserializeOwned>(bytes: &[u8]) -> WorkerResult<T> {
+        ciborium::from_reader(bytes).map_err(codec_error)
+    }
+}
+
+/// Typed access to single keys, eg: `Kv::<u64>::get("total")`.
+pub struct Kv<T, C = Json>(PhantomData<(T, C)>);
+
+impl<T, C> Kv<T, C>
+where
+    T: Serialize + DeserializeOwned,
+    C: Codec,
+{
+    pub fn get(key: &str) -> WorkerResult<Option<T>> {
+        get(key)?.map(|x| C::decode(&x)).transpose()
+    }
+
+    pub fn put(key: &str, value: &T) -> WorkerResult<()> {
+        set(key, &C::encode(value)?)
+    }
+
+    pub fn delete(key: &str) -> WorkerResult<()> {
+        delete(key)
+    }
+
+    /// Replace the value of a key with the result of `f`, which receives the
+    /// current value (if any). The write only happens if the stored value
+    /// didn't change in the meantime, otherwise `f` runs again on the new
+    /// value. Returns the value written.
+    pub fn update(key: &str, mut f: impl FnMut(Option<T>) -> T) -> WorkerResult<T> {
+        loop {
+            let current = get(key)?;
+
+            let old = current.as_deref().map(C::decode).transpose()?;
+            let new = f(old);
+
+            if compare_and_swap(key, current.as_deref(), &C::encode(&new)?)? {
+                return Ok(new);
+            }
+        }
+    }
+}
+
+/// A typed map whose keys live under a common prefix, so several of them
+/// can share the storage of a worker.
+pub struct KvMap<T, C = Json> {
+    prefix: String,
+    _value: PhantomData<(T, C)>,
+}
+
+impl<T, C> KvMap<T, C>
+where
+    T: Serialize + DeserializeOwned,
+    C: Codec,
+{
+    pub fn new(name: &str) -> Self {
+        Self {
+            prefix: format!("{name}/"),
+            _value: PhantomData,
+        }
+    }
+
+    /// A map nested under this one.
+    pub fn scoped<U, D>(&self, name: &str) -> KvMap<U, D> {
+        KvMap {
+            prefix: format!("{}{name}/", self.prefix),
+            _value: PhantomData,
+        }
+    }
+
+    fn key(&self, key: &str) -> String {
+        format!("{}{key}", self.prefix)
+    }
+
+    pub fn get(&self, key: &str) -> WorkerResult<Option<T>> {
+        Kv::<T, C>::get(&self.key(key))
+    }
+
+    pub fn put(&self, key: &str, value: &T) -> WorkerResult<()> {
+        Kv::<T, C>::put(&self.key(key), value)
+    }
+
+    pub fn delete(&self, key: &str) -> WorkerResult<()> {
+        delete(&self.key(key))
+    }
+
+    /// See [`Kv::update`].
+    pub fn update(&self, key: &str, f: impl FnMut(Option<T>) -> T) -> WorkerResult<T> {
+        Kv::<T, C>::update(&self.key(key), f)
+    }
+
+    /// The key without the prefix of the map, unless it belongs to a nested
+    /// map (see [`KvMap::scoped`]).
+    fn own_key<'a>(&self, key: &'a str) -> Option<&'a str> {
+        Some(&key[self.prefix.len()..]).filter(|x| !x.contains('/'))
+    }
+
+    /// Keys of the map, without its prefix. Keys of nested maps are left out.
+    pub fn keys(&self) -> WorkerResult<Vec<String>> {
+        let keys = keys(&self.prefix)?;
+
+        Ok(keys
+            .iter()
+            .filter_map(|x| self.own_key(x))
+            .map(str::to_owned)
+            .collect())
+    }
+
+    /// Iterate the entries of the map in key order, as `(key, value)` with
+    /// the key without its prefix. Entries of nested maps are left out.
+    pub fn iter(&self) -> impl Iterator<Item = WorkerResult<(String, T)>> + '_ {
+        entries(&self.prefix).filter_map(|entry| {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(err) => return Some(Err(err)),
+            };
+
+            let key = self.own_key(&entry.key)?.to_owned();
+            Some(C::decode(&entry.value).map(|value| (key, value)))
+        })
+    }
+}
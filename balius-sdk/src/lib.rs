@@ -29,5 +29,8 @@ pub mod http;
 /// A tracing implementation which sends logs to the server
 pub mod logging;
 
+/// Access to the key-value storage of the worker
+pub mod kv;
+
 pub use _internal::Worker;
 pub use qol::*;
@@ -31,6 +31,8 @@ pub enum Error {
     Submit(wit::balius::app::submit::SubmitError),
     #[error("http error: {0}")]
     Http(wit::balius::app::http::ErrorCode),
+    #[error("broadcast error: {0}")]
+    Broadcast(wit::balius::app::broadcast::BroadcastError),
 }
 
 impl From<Error> for wit::HandleError {
@@ -80,6 +82,10 @@ impl From<Error> for wit::HandleError {
                 code: 10,
                 message: err.to_string(),
             },
+            Error::Broadcast(err) => wit::HandleError {
+                code: 11,
+                message: format!("broadcast error {err}"),
+            },
         }
     }
 }
@@ -354,6 +360,430 @@ impl TryFrom<wit::Event> for Tx {
     }
 }
 
+pub struct MintDelta {
+    pub policy: Vec<u8>,
+    pub name: Vec<u8>,
+    /// Positive when minted, negative when burned
+    pub amount: i64,
+}
+
+pub struct Mint {
+    pub block_hash: Vec<u8>,
+    pub block_height: u64,
+    pub block_slot: u64,
+    pub hash: Vec<u8>,
+    /// Every asset minted or burned by the Tx
+    pub deltas: Vec<MintDelta>,
+    pub tx: balius_core::proto::v0::cardano::Tx,
+}
+
+impl TryFrom<wit::Event> for Mint {
+    type Error = Error;
+
+    fn try_from(value: wit::Event) -> Result<Self, Self::Error> {
+        use prost::Message;
+
+        let tx = match value {
+            wit::Event::Mint(x) => x,
+            wit::Event::MintUndo(x) => x,
+            _ => return Err(Error::EventMismatch("mint|mint-undo".to_owned())),
+        };
+
+        let body: balius_core::proto::v0::cardano::Tx =
+            Message::decode(tx.body.as_slice()).map_err(|_| Self::Error::BadUtxo)?;
+
+        let deltas = body
+            .mint
+            .iter()
+            .flat_map(|multiasset| {
+                multiasset.assets.iter().map(|asset| MintDelta {
+                    policy: multiasset.policy_id.to_vec(),
+                    name: asset.name.to_vec(),
+                    amount: asset.mint_coin,
+                })
+            })
+            .collect();
+
+        Ok(Self {
+            block_hash: tx.block.block_hash,
+            block_height: tx.block.block_height,
+            block_slot: tx.block.block_slot,
+            hash: tx.hash,
+            deltas,
+            tx: body,
+        })
+    }
+}
+
+pub struct Block {
+    pub block_hash: Vec<u8>,
+    pub block_height: u64,
+    pub block_slot: u64,
+    pub txs: Vec<balius_core::proto::v0::cardano::Tx>,
+}
+
+impl TryFrom<wit::Event> for Block {
+    type Error = Error;
+
+    fn try_from(value: wit::Event) -> Result<Self, Self::Error> {
+        use prost::Message;
+
+        let block = match value {
+            wit::Event::Block(x) => x,
+            wit::Event::BlockUndo(x) => x,
+            _ => return Err(Error::EventMismatch("block|block-undo".to_owned())),
+        };
+
+        let txs = block
+            .txs
+            .iter()
+            .map(|tx| Message::decode(tx.as_slice()).map_err(|_| Self::Error::BadTx))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            block_hash: block.block.block_hash,
+            block_height: block.block.block_height,
+            block_slot: block.block.block_slot,
+            txs,
+        })
+    }
+}
+
+pub struct BlockBoundary {
+    pub block_hash: Vec<u8>,
+    pub block_height: u64,
+    pub block_slot: u64,
+    pub tx_count: u32,
+    /// Whether the block is being rolled back
+    pub undo: bool,
+}
+
+impl TryFrom<wit::Event> for BlockBoundary {
+    type Error = Error;
+
+    fn try_from(value: wit::Event) -> Result<Self, Self::Error> {
+        let boundary = match value {
+            wit::Event::BlockBegin(x) => x,
+            wit::Event::BlockEnd(x) => x,
+            _ => return Err(Error::EventMismatch("block-begin|block-end".to_owned())),
+        };
+
+        Ok(Self {
+            block_hash: boundary.block.block_hash,
+            block_height: boundary.block.block_height,
+            block_slot: boundary.block.block_slot,
+            tx_count: boundary.tx_count,
+            undo: boundary.undo,
+        })
+    }
+}
+
+pub struct Metadata {
+    pub block_hash: Vec<u8>,
+    pub block_height: u64,
+    pub block_slot: u64,
+    pub hash: Vec<u8>,
+    pub tx: balius_core::proto::v0::cardano::Tx,
+}
+
+impl TryFrom<wit::Event> for Metadata {
+    type Error = Error;
+
+    fn try_from(value: wit::Event) -> Result<Self, Self::Error> {
+        use prost::Message;
+
+        let tx = match value {
+            wit::Event::Metadata(x) => x,
+            wit::Event::MetadataUndo(x) => x,
+            _ => return Err(Error::EventMismatch("metadata|metadata-undo".to_owned())),
+        };
+
+        let body = Message::decode(tx.body.as_slice()).map_err(|_| Self::Error::BadTx)?;
+
+        Ok(Self {
+            block_hash: tx.block.block_hash,
+            block_height: tx.block.block_height,
+            block_slot: tx.block.block_slot,
+            hash: tx.hash,
+            tx: body,
+        })
+    }
+}
+
+impl Metadata {
+    /// The metadatum attached to the Tx under `label`, if any.
+    pub fn get(&self, label: u64) -> Option<&balius_core::proto::v0::cardano::Metadatum> {
+        self.tx
+            .auxiliary
+            .as_ref()?
+            .metadata
+            .iter()
+            .find(|x| x.label == label)?
+            .value
+            .as_ref()
+    }
+
+    /// Decode the metadatum under `label` from its JSON representation (see
+    /// [`metadatum_to_json`]).
+    pub fn json<T>(&self, label: u64) -> WorkerResult<Option<T>>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let Some(metadatum) = self.get(label) else {
+            return Ok(None);
+        };
+
+        let value =
+            serde_json::from_value(metadatum_to_json(metadatum)).map_err(|_| Error::BadTx)?;
+
+        Ok(Some(value))
+    }
+
+    /// The metadatum under `label` as `PlutusData` (see
+    /// [`metadatum_to_plutus_data`]).
+    pub fn plutus_data(&self, label: u64) -> Option<balius_core::proto::v0::cardano::PlutusData> {
+        self.get(label).map(metadatum_to_plutus_data)
+    }
+}
+
+/// Map a metadatum to JSON. Bytes are hex-encoded and map keys that aren't
+/// text are rendered as strings (ints in decimal, bytes in hex).
+pub fn metadatum_to_json(
+    metadatum: &balius_core::proto::v0::cardano::Metadatum,
+) -> serde_json::Value {
+    use balius_core::proto::v0::cardano::metadatum::Metadatum as M;
+
+    match &metadatum.metadatum {
+        Some(M::Int(x)) => serde_json::Value::from(*x),
+        Some(M::Bytes(x)) => serde_json::Value::from(hex::encode(x)),
+        Some(M::Text(x)) => serde_json::Value::from(x.as_str()),
+        Some(M::Array(x)) => x.items.iter().map(metadatum_to_json).collect(),
+        Some(M::Map(x)) => x
+            .pairs
+            .iter()
+            .map(|pair| {
+                let key = match pair.key.as_ref().map(metadatum_to_json) {
+                    Some(serde_json::Value::String(x)) => x,
+                    Some(other) => other.to_string(),
+                    None => String::new(),
+                };
+                let value = pair
+                    .value
+                    .as_ref()
+                    .map(metadatum_to_json)
+                    .unwrap_or_default();
+                (key, value)
+            })
+            .collect::<serde_json::Map<_, _>>()
+            .into(),
+        None => serde_json::Value::Null,
+    }
+}
+
+/// Map a metadatum to `PlutusData`. Text is carried as its UTF-8 bytes.
+pub fn metadatum_to_plutus_data(
+    metadatum: &balius_core::proto::v0::cardano::Metadatum,
+) -> balius_core::proto::v0::cardano::PlutusData {
+    use balius_core::proto::v0::cardano as proto;
+    use proto::metadatum::Metadatum as M;
+    use proto::plutus_data::PlutusData as P;
+
+    let plutus_data = match &metadatum.metadatum {
+        Some(M::Int(x)) => Some(P::BigInt(proto::BigInt {
+            big_int: Some(proto::big_int::BigInt::Int(*x)),
+        })),
+        Some(M::Bytes(x)) => Some(P::BoundedBytes(x.clone())),
+        Some(M::Text(x)) => Some(P::BoundedBytes(x.clone().into_bytes().into())),
+        Some(M::Array(x)) => Some(P::Array(proto::PlutusDataArray {
+            items: x.items.iter().map(metadatum_to_plutus_data).collect(),
+        })),
+        Some(M::Map(x)) => Some(P::Map(proto::PlutusDataMap {
+            pairs: x
+                .pairs
+                .iter()
+                .map(|pair| proto::PlutusDataPair {
+                    key: pair.key.as_ref().map(metadatum_to_plutus_data),
+                    value: pair.value.as_ref().map(metadatum_to_plutus_data),
+                })
+                .collect(),
+        })),
+        None => None,
+    };
+
+    proto::PlutusData { plutus_data }
+}
+
+pub struct Certificate {
+    pub block_hash: Vec<u8>,
+    pub block_height: u64,
+    pub block_slot: u64,
+    pub tx_hash: Vec<u8>,
+    /// Position of the certificate within the Tx
+    pub index: u32,
+    pub certificate: balius_core::proto::v0::cardano::Certificate,
+}
+
+impl TryFrom<wit::Event> for Certificate {
+    type Error = Error;
+
+    fn try_from(value: wit::Event) -> Result<Self, Self::Error> {
+        use prost::Message;
+
+        let cert = match value {
+            wit::Event::Certificate(x) => x,
+            wit::Event::CertificateUndo(x) => x,
+            _ => {
+                return Err(Error::EventMismatch(
+                    "certificate|certificate-undo".to_owned(),
+                ))
+            }
+        };
+
+        let certificate =
+            Message::decode(cert.body.as_slice()).map_err(|_| Self::Error::BadUtxo)?;
+
+        Ok(Self {
+            block_hash: cert.block.block_hash,
+            block_height: cert.block.block_height,
+            block_slot: cert.block.block_slot,
+            tx_hash: cert.tx_hash,
+            index: cert.index,
+            certificate,
+        })
+    }
+}
+
+pub struct Proposal {
+    pub block_hash: Vec<u8>,
+    pub block_height: u64,
+    pub block_slot: u64,
+    pub tx_hash: Vec<u8>,
+    /// Position of the proposal within the Tx
+    pub index: u32,
+    pub proposal: balius_core::proto::v0::cardano::GovernanceActionProposal,
+}
+
+impl TryFrom<wit::Event> for Proposal {
+    type Error = Error;
+
+    fn try_from(value: wit::Event) -> Result<Self, Self::Error> {
+        use prost::Message;
+
+        let proposal = match value {
+            wit::Event::Proposal(x) => x,
+            wit::Event::ProposalUndo(x) => x,
+            _ => return Err(Error::EventMismatch("proposal|proposal-undo".to_owned())),
+        };
+
+        let body = Message::decode(proposal.body.as_slice()).map_err(|_| Self::Error::BadTx)?;
+
+        Ok(Self {
+            block_hash: proposal.block.block_hash,
+            block_height: proposal.block.block_height,
+            block_slot: proposal.block.block_slot,
+            tx_hash: proposal.tx_hash,
+            index: proposal.index,
+            proposal: body,
+        })
+    }
+}
+
+pub struct SpentUtxo {
+    pub block_hash: Vec<u8>,
+    pub block_height: u64,
+    pub block_slot: u64,
+    /// Hash of the Tx that spends the UTxO
+    pub spent_by: Vec<u8>,
+    pub tx_hash: Vec<u8>,
+    pub index: u64,
+    /// The spent output, if resolved by the chain source
+    pub utxo: Option<balius_core::proto::v0::cardano::TxOutput>,
+}
+
+impl TryFrom<wit::Event> for SpentUtxo {
+    type Error = Error;
+
+    fn try_from(value: wit::Event) -> Result<Self, Self::Error> {
+        use prost::Message;
+
+        let spent = match value {
+            wit::Event::UtxoSpent(x) => x,
+            wit::Event::UtxoSpentUndo(x) => x,
+            _ => {
+                return Err(Error::EventMismatch(
+                    "utxo-spent|utxo-spent-undo".to_owned(),
+                ))
+            }
+        };
+
+        let input: balius_core::proto::v0::cardano::TxInput =
+            Message::decode(spent.body.as_slice()).map_err(|_| Self::Error::BadUtxo)?;
+
+        Ok(SpentUtxo {
+            block_hash: spent.block.block_hash,
+            block_height: spent.block.block_height,
+            block_slot: spent.block.block_slot,
+            spent_by: spent.spent_by,
+            tx_hash: spent.ref_.tx_hash,
+            index: spent.ref_.txo_index as u64,
+            utxo: input.as_output,
+        })
+    }
+}
+
+pub struct Timer {
+    /// Unix timestamp (in milliseconds) at which the timer fired
+    pub timestamp: u64,
+}
+
+impl TryFrom<wit::Event> for Timer {
+    type Error = Error;
+
+    fn try_from(value: wit::Event) -> Result<Self, Self::Error> {
+        match value {
+            wit::Event::Timer(timestamp) => Ok(Self { timestamp }),
+            _ => Err(Error::EventMismatch("timer".to_owned())),
+        }
+    }
+}
+
+pub struct Message<T>(pub T);
+
+impl<T> TryFrom<wit::Event> for Message<T>
+where
+    T: serde::de::DeserializeOwned,
+{
+    type Error = Error;
+
+    fn try_from(value: wit::Event) -> Result<Self, Self::Error> {
+        let bytes = match value {
+            wit::Event::Message(x) => x,
+            _ => return Err(Error::EventMismatch("message".to_owned())),
+        };
+
+        let t = serde_json::from_slice(bytes.as_slice())
+            .map_err(|err| Error::Internal(format!("bad message payload: {err}")))?;
+        Ok(Message(t))
+    }
+}
+
+impl<T> std::ops::Deref for Message<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// Publish a JSON message to every worker subscribed to `topic`.
+pub fn publish<T>(topic: &str, payload: &T) -> WorkerResult<()>
+where
+    T: serde::Serialize,
+{
+    let bytes = serde_json::to_vec(payload)?;
+    wit::balius::app::broadcast::publish_msg(topic, &bytes).map_err(Error::Broadcast)
+}
+
 pub struct NewTx(pub Box<dyn crate::txbuilder::TxExpr>);
 
 impl TryInto<wit::Response> for NewTx {
@@ -367,27 +797,77 @@ impl TryInto<wit::Response> for NewTx {
     }
 }
 
+#[derive(Default)]
 pub struct UtxoMatcher {
-    address: Option<Vec<u8>>,
+    address: Option<wit::balius::app::driver::AddressPattern>,
+    token: Option<wit::balius::app::driver::TokenPattern>,
 }
 
 impl UtxoMatcher {
     pub fn all() -> Self {
-        Self { address: None }
+        Self::default()
     }
 
     pub fn by_address(address: Vec<u8>) -> Self {
         Self {
-            address: Some(address),
+            address: Some(wit::balius::app::driver::AddressPattern::Exact(address)),
+            ..Default::default()
+        }
+    }
+
+    /// Match outputs locked by the given payment key or script hash,
+    /// regardless of the stake part of the address.
+    pub fn by_payment_credential(hash: Vec<u8>) -> Self {
+        Self {
+            address: Some(wit::balius::app::driver::AddressPattern::PaymentCredential(
+                hash,
+            )),
+            ..Default::default()
+        }
+    }
+
+    /// Match outputs whose address delegates to the given stake key or
+    /// script hash.
+    pub fn by_stake_credential(hash: Vec<u8>) -> Self {
+        Self {
+            address: Some(wit::balius::app::driver::AddressPattern::StakeCredential(
+                hash,
+            )),
+            ..Default::default()
         }
     }
+
+    /// Match outputs holding any token of the given policy.
+    pub fn by_policy(policy: Vec<u8>) -> Self {
+        Self::all().with_policy(policy)
+    }
+
+    /// Match outputs holding the token with the given policy and name.
+    pub fn by_asset(policy: Vec<u8>, name: Vec<u8>) -> Self {
+        Self::all().with_asset(policy, name)
+    }
+
+    /// Narrow down the matcher to outputs holding any token of the policy.
+    pub fn with_policy(mut self, policy: Vec<u8>) -> Self {
+        self.token = Some(wit::balius::app::driver::TokenPattern { policy, name: None });
+        self
+    }
+
+    /// Narrow down the matcher to outputs holding the given token.
+    pub fn with_asset(mut self, policy: Vec<u8>, name: Vec<u8>) -> Self {
+        self.token = Some(wit::balius::app::driver::TokenPattern {
+            policy,
+            name: Some(name),
+        });
+        self
+    }
 }
 
 impl From<UtxoMatcher> for wit::balius::app::driver::UtxoPattern {
     fn from(value: UtxoMatcher) -> Self {
         Self {
             address: value.address,
-            token: None,
+            token: value.token,
         }
     }
 }
@@ -419,6 +899,33 @@ impl crate::_internal::Worker {
         self
     }
 
+    /// Register a handler triggered periodically. The interval is either a
+    /// cron expression (eg: `0 15 * * *`) or a simple duration (eg: `5m`);
+    /// the runtime refuses to load a worker with an invalid interval.
+    pub fn with_timer_handler(mut self, interval: &str, handler: impl Handler + 'static) -> Self {
+        self.channels.insert(
+            self.channels.len() as u32,
+            crate::_internal::Channel {
+                handler: Box::new(handler),
+                pattern: wit::balius::app::driver::EventPattern::Timer(interval.to_owned()),
+            },
+        );
+
+        self
+    }
+
+    pub fn with_message_handler(mut self, topic: &str, handler: impl Handler + 'static) -> Self {
+        self.channels.insert(
+            self.channels.len() as u32,
+            crate::_internal::Channel {
+                handler: Box::new(handler),
+                pattern: wit::balius::app::driver::EventPattern::Message(topic.to_owned()),
+            },
+        );
+
+        self
+    }
+
     pub fn with_utxo_handler(
         mut self,
         pattern: impl Into<wit::balius::app::driver::UtxoPattern>,
@@ -435,6 +942,203 @@ impl crate::_internal::Worker {
         self
     }
 
+    /// Register a handler for rolled-back UTxOs. Once set, rollbacks are no
+    /// longer delivered to the handlers registered with `with_utxo_handler`.
+    pub fn with_utxo_undo_handler(
+        mut self,
+        pattern: impl Into<wit::balius::app::driver::UtxoPattern>,
+        handler: impl Handler + 'static,
+    ) -> Self {
+        self.channels.insert(
+            self.channels.len() as u32,
+            crate::_internal::Channel {
+                handler: Box::new(handler),
+                pattern: wit::balius::app::driver::EventPattern::UtxoUndo(pattern.into()),
+            },
+        );
+
+        self
+    }
+
+    /// Register a handler for UTxOs consumed by a Tx. Matching by address or
+    /// token requires the chain source to resolve the spent outputs.
+    pub fn with_utxo_spent_handler(
+        mut self,
+        pattern: impl Into<wit::balius::app::driver::UtxoPattern>,
+        handler: impl Handler + 'static,
+    ) -> Self {
+        self.channels.insert(
+            self.channels.len() as u32,
+            crate::_internal::Channel {
+                handler: Box::new(handler),
+                pattern: wit::balius::app::driver::EventPattern::UtxoSpent(pattern.into()),
+            },
+        );
+
+        self
+    }
+
+    /// Register a handler for rolled-back spends. Once set, rollbacks are no
+    /// longer delivered to the handlers registered with
+    /// `with_utxo_spent_handler`.
+    pub fn with_utxo_spent_undo_handler(
+        mut self,
+        pattern: impl Into<wit::balius::app::driver::UtxoPattern>,
+        handler: impl Handler + 'static,
+    ) -> Self {
+        self.channels.insert(
+            self.channels.len() as u32,
+            crate::_internal::Channel {
+                handler: Box::new(handler),
+                pattern: wit::balius::app::driver::EventPattern::UtxoSpentUndo(pattern.into()),
+            },
+        );
+
+        self
+    }
+
+    /// Register a handler for Txs minting the matching tokens. Rollbacks of
+    /// those Txs are delivered to the same handler as `mint-undo` events.
+    pub fn with_mint_handler(
+        mut self,
+        pattern: wit::balius::app::driver::TokenPattern,
+        handler: impl Handler + 'static,
+    ) -> Self {
+        self.channels.insert(
+            self.channels.len() as u32,
+            crate::_internal::Channel {
+                handler: Box::new(handler),
+                pattern: wit::balius::app::driver::EventPattern::Mint(pattern),
+            },
+        );
+
+        self
+    }
+
+    /// Register a handler for Txs burning the matching tokens. Rollbacks of
+    /// those Txs are delivered to the same handler as `mint-undo` events.
+    pub fn with_burn_handler(
+        mut self,
+        pattern: wit::balius::app::driver::TokenPattern,
+        handler: impl Handler + 'static,
+    ) -> Self {
+        self.channels.insert(
+            self.channels.len() as u32,
+            crate::_internal::Channel {
+                handler: Box::new(handler),
+                pattern: wit::balius::app::driver::EventPattern::Burn(pattern),
+            },
+        );
+
+        self
+    }
+
+    /// Register a handler for certificates of the given kind. Rollbacks are
+    /// delivered to the same handler as `certificate-undo` events.
+    pub fn with_certificate_handler(
+        mut self,
+        kind: wit::balius::app::driver::CertificateKind,
+        handler: impl Handler + 'static,
+    ) -> Self {
+        self.channels.insert(
+            self.channels.len() as u32,
+            crate::_internal::Channel {
+                handler: Box::new(handler),
+                pattern: wit::balius::app::driver::EventPattern::Certificate(kind),
+            },
+        );
+
+        self
+    }
+
+    /// Register a handler for governance action proposals of the given kind.
+    /// Rollbacks are delivered to the same handler as `proposal-undo` events.
+    pub fn with_proposal_handler(
+        mut self,
+        kind: wit::balius::app::driver::GovernanceActionKind,
+        handler: impl Handler + 'static,
+    ) -> Self {
+        self.channels.insert(
+            self.channels.len() as u32,
+            crate::_internal::Channel {
+                handler: Box::new(handler),
+                pattern: wit::balius::app::driver::EventPattern::Proposal(kind),
+            },
+        );
+
+        self
+    }
+
+    /// Register a handler for Txs carrying metadata under the given label.
+    /// Rollbacks are delivered to the same handler as `metadata-undo` events.
+    pub fn with_metadata_handler(mut self, label: u64, handler: impl Handler + 'static) -> Self {
+        self.channels.insert(
+            self.channels.len() as u32,
+            crate::_internal::Channel {
+                handler: Box::new(handler),
+                pattern: wit::balius::app::driver::EventPattern::Metadata(label),
+            },
+        );
+
+        self
+    }
+
+    /// Register a handler receiving every block, after the events of its
+    /// Txs have been dispatched.
+    pub fn with_block_handler(mut self, handler: impl Handler + 'static) -> Self {
+        self.channels.insert(
+            self.channels.len() as u32,
+            crate::_internal::Channel {
+                handler: Box::new(handler),
+                pattern: wit::balius::app::driver::EventPattern::Block,
+            },
+        );
+
+        self
+    }
+
+    /// Register a handler for rolled-back blocks. Once set, rollbacks are no
+    /// longer delivered to the handlers registered with `with_block_handler`.
+    pub fn with_block_undo_handler(mut self, handler: impl Handler + 'static) -> Self {
+        self.channels.insert(
+            self.channels.len() as u32,
+            crate::_internal::Channel {
+                handler: Box::new(handler),
+                pattern: wit::balius::app::driver::EventPattern::BlockUndo,
+            },
+        );
+
+        self
+    }
+
+    /// Register a handler notified before any other event of a block, both
+    /// when applied and when rolled back.
+    pub fn with_block_begin_handler(mut self, handler: impl Handler + 'static) -> Self {
+        self.channels.insert(
+            self.channels.len() as u32,
+            crate::_internal::Channel {
+                handler: Box::new(handler),
+                pattern: wit::balius::app::driver::EventPattern::BlockBegin,
+            },
+        );
+
+        self
+    }
+
+    /// Register a handler notified after every other event of a block, both
+    /// when applied and when rolled back. Useful to flush per-block state.
+    pub fn with_block_end_handler(mut self, handler: impl Handler + 'static) -> Self {
+        self.channels.insert(
+            self.channels.len() as u32,
+            crate::_internal::Channel {
+                handler: Box::new(handler),
+                pattern: wit::balius::app::driver::EventPattern::BlockEnd,
+            },
+        );
+
+        self
+    }
+
     pub fn with_tx_handler(
         mut self,
         pattern: impl Into<wit::balius::app::driver::UtxoPattern>,
@@ -450,4 +1154,22 @@ impl crate::_internal::Worker {
 
         self
     }
+
+    /// Register a handler for rolled-back Txs. Once set, rollbacks are no
+    /// longer delivered to the handlers registered with `with_tx_handler`.
+    pub fn with_tx_undo_handler(
+        mut self,
+        pattern: impl Into<wit::balius::app::driver::UtxoPattern>,
+        handler: impl Handler + 'static,
+    ) -> Self {
+        self.channels.insert(
+            self.channels.len() as u32,
+            crate::_internal::Channel {
+                handler: Box::new(handler),
+                pattern: wit::balius::app::driver::EventPattern::TxUndo(pattern.into()),
+            },
+        );
+
+        self
+    }
 }
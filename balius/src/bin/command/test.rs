@@ -82,6 +82,7 @@ pub fn setup_tracing() -> miette::Result<()> {
 
 async fn run_project_with_config(
     project_name: &str,
+    target_dir: PathBuf,
     config_path: Option<PathBuf>,
     port: u16,
     utxo_url: String,
@@ -109,8 +110,10 @@ async fn run_project_with_config(
         .context("setting up ledger")?;
 
     let runtime = Runtime::builder(store)
+        .with_compile_cache(target_dir.join("balius-cache"))
         .with_ledger(ledger.into())
-        .with_kv(balius_runtime::kv::Kv::Mock)
+        .with_kv(balius_runtime::kv::Kv::Mock(Default::default()))
+        .with_broadcast(Default::default())
         .build()
         .into_diagnostic()
         .context("setting up runtime")?;
@@ -120,7 +123,7 @@ async fn run_project_with_config(
     let wasm_path = format!("{project_name}-c.wasm");
 
     runtime
-        .register_worker_from_file(project_name, &wasm_path, config)
+        .register_worker_from_file(project_name, &wasm_path, config, Default::default())
         .await
         .into_diagnostic()
         .context(format!("registering worker {}", &wasm_path))?;
@@ -147,10 +150,37 @@ async fn run_project_with_config(
         cancel.clone(),
     ));
 
-    let (jsonrpc, chainsync) = tokio::try_join!(jsonrpc_server, chainsync_driver).unwrap();
+    let timer_driver = tokio::spawn(balius_runtime::drivers::timer::run(
+        Default::default(),
+        runtime.clone(),
+        cancel.clone(),
+    ));
+
+    let broadcast_driver = tokio::spawn(balius_runtime::drivers::broadcast::run(
+        runtime.clone(),
+        cancel.clone(),
+    ));
+
+    let catchup_driver = tokio::spawn(balius_runtime::drivers::catchup::run(
+        Default::default(),
+        runtime.clone(),
+        cancel.clone(),
+    ));
+
+    let (jsonrpc, chainsync, timer, broadcast, catchup) = tokio::try_join!(
+        jsonrpc_server,
+        chainsync_driver,
+        timer_driver,
+        broadcast_driver,
+        catchup_driver
+    )
+    .unwrap();
 
     jsonrpc.unwrap();
     chainsync.unwrap();
+    timer.unwrap();
+    broadcast.unwrap();
+    catchup.unwrap();
 
     Ok(())
 }
@@ -161,13 +191,20 @@ pub async fn execute(
     utxo_url: String,
     utxo_api_key: String,
 ) {
-    let (_, package_name) = get_project_info();
+    let (target_dir, package_name) = get_project_info();
 
     // Convert config_path from String to PathBuf if provided
     let config_path_buf = config_path.map(PathBuf::from);
 
-    let result =
-        run_project_with_config(&package_name, config_path_buf, port, utxo_url, utxo_api_key).await;
+    let result = run_project_with_config(
+        &package_name,
+        target_dir,
+        config_path_buf,
+        port,
+        utxo_url,
+        utxo_api_key,
+    )
+    .await;
     if result.is_err() {
         info!("Error running project: {}", result.err().unwrap());
     }
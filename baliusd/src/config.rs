@@ -10,14 +10,30 @@ use balius_runtime::{
     drivers, ledgers,
     logging::file::FileLogger,
     sign::in_memory::{Ed25519Key, SignerKey},
+    SlotRange,
 };
 use pallas::crypto::key::ed25519;
 use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, DisplayFromStr};
 use tokio::sync::{Mutex, RwLock};
 
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum StoreType {
+    #[default]
+    Redb,
+    Sqlite,
+}
+
 #[derive(Deserialize, Clone, Debug)]
 pub struct StoreConfig {
+    #[serde(rename = "type", default)]
+    pub kind: StoreType,
+    pub path: PathBuf,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct CompileCacheConfig {
     pub path: PathBuf,
 }
 
@@ -37,6 +53,11 @@ pub struct RedbKvConfig {
     pub cache_size: Option<usize>,
 }
 
+#[derive(Deserialize, Clone, Debug)]
+pub struct SqliteKvConfig {
+    pub path: PathBuf,
+}
+
 #[derive(Deserialize, Clone, Debug)]
 pub struct MemoryKvConfig {
     pub keys: Option<Vec<MemoryKvKeyConfig>>,
@@ -56,6 +77,7 @@ pub struct MemoryKvKeyConfig {
 pub enum KvConfig {
     Memory(MemoryKvConfig),
     Redb(RedbKvConfig),
+    Sqlite(SqliteKvConfig),
 }
 
 #[derive(Deserialize, Clone, Debug)]
@@ -79,6 +101,23 @@ pub struct WorkerConfig {
     pub since_slot: Option<u64>,
     pub until_slot: Option<u64>,
     pub config: Option<PathBuf>,
+    pub limits: Option<balius_runtime::WorkerLimits>,
+    pub on_failure: Option<balius_runtime::FailurePolicy>,
+    pub kv_rollback: Option<bool>,
+}
+
+impl From<&WorkerConfig> for balius_runtime::WorkerOptions {
+    fn from(value: &WorkerConfig) -> Self {
+        Self {
+            limits: value.limits.clone().unwrap_or_default(),
+            range: SlotRange {
+                since: value.since_slot,
+                until: value.until_slot,
+            },
+            policy: value.on_failure.clone().unwrap_or_default(),
+            kv_rollback: value.kv_rollback.unwrap_or(true),
+        }
+    }
 }
 
 #[derive(Deserialize, Clone, Debug)]
@@ -157,6 +196,9 @@ pub struct Config {
     pub rpc: drivers::jsonrpc::Config,
     pub ledger: ledgers::u5c::Config,
     pub chainsync: drivers::chainsync::Config,
+    pub timer: Option<drivers::timer::Config>,
+    pub catchup: Option<drivers::catchup::Config>,
+    pub pooling: Option<balius_runtime::PoolingConfig>,
     pub workers: Vec<WorkerConfig>,
     pub logging: LoggingConfig,
     pub kv: Option<KvConfig>,
@@ -164,6 +206,7 @@ pub struct Config {
     pub metrics: Option<MetricsConfig>,
     pub signing: Option<SignerConfig>,
     pub store: Option<StoreConfig>,
+    pub compile_cache: Option<CompileCacheConfig>,
     pub http: Option<HttpConfig>,
     pub submit: Option<SubmitConfig>,
 }
@@ -189,7 +232,11 @@ impl From<&Config> for balius_runtime::kv::Kv {
                 balius_runtime::kv::redb::RedbKv::try_new(&cfg.path, cfg.cache_size)
                     .expect("Failed to open Redb KV store"),
             ))),
-            None => balius_runtime::kv::Kv::Mock,
+            Some(KvConfig::Sqlite(cfg)) => balius_runtime::kv::Kv::Sqlite(Arc::new(RwLock::new(
+                balius_runtime::kv::sqlite::SqliteKv::try_new(&cfg.path)
+                    .expect("Failed to open SQLite KV store"),
+            ))),
+            None => balius_runtime::kv::Kv::Mock(Default::default()),
         }
     }
 }
@@ -0,0 +1,170 @@
+//! Subcommands to inspect and replay the dead letters of workers.
+//!
+//! These open the store configured for the daemon, which is locked while the
+//! daemon is running, so it needs to be stopped first.
+
+use std::collections::BTreeSet;
+
+use balius_runtime::{DeadLetterId, StoreTrait as _};
+use clap::Subcommand;
+use miette::{Context as _, IntoDiagnostic as _};
+use tracing::warn;
+
+use crate::{boilerplate, build_runtime, config, open_store, register_worker};
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// List dead letters, oldest first
+    List {
+        /// Only list the dead letters of this worker
+        #[arg(long)]
+        worker: Option<String>,
+    },
+
+    /// Dispatch dead letters to their workers again, removing those handled
+    Replay {
+        /// Replay a single dead letter, every one if omitted
+        id: Option<DeadLetterId>,
+
+        /// Only replay the dead letters of this worker
+        #[arg(long)]
+        worker: Option<String>,
+    },
+
+    /// Drop a dead letter without replaying it
+    Discard { id: DeadLetterId },
+}
+
+fn load_config() -> miette::Result<config::Config> {
+    let config: config::Config = boilerplate::load_config(&None)
+        .into_diagnostic()
+        .context("loading config")?;
+
+    if config.store.is_none() {
+        miette::bail!("no store configured, dead letters are only kept in memory");
+    }
+
+    Ok(config)
+}
+
+pub async fn run(command: Command) -> miette::Result<()> {
+    let config = load_config()?;
+    boilerplate::setup_tracing(&config.logging).unwrap();
+
+    match command {
+        Command::List { worker } => list(&config, worker).await,
+        Command::Replay { id, worker } => replay(&config, id, worker).await,
+        Command::Discard { id } => discard(&config, id).await,
+    }
+}
+
+async fn list(config: &config::Config, worker: Option<String>) -> miette::Result<()> {
+    let store = open_store(config, false)?;
+
+    let letters = store
+        .list_dead_letters(worker.as_deref())
+        .await
+        .into_diagnostic()
+        .context("listing dead letters")?;
+
+    for (id, letter) in letters {
+        let slot = letter
+            .block
+            .map(|x| x.slot.to_string())
+            .unwrap_or("-".to_string());
+
+        println!(
+            "{id}\t{}\tchannel {}\tslot {slot}\t{}",
+            letter.worker, letter.channel, letter.message
+        );
+    }
+
+    Ok(())
+}
+
+async fn replay(
+    config: &config::Config,
+    id: Option<DeadLetterId>,
+    worker: Option<String>,
+) -> miette::Result<()> {
+    let store = open_store(config, false)?;
+
+    let letters = match id {
+        Some(id) => {
+            let letter = store
+                .get_dead_letter(id)
+                .await
+                .into_diagnostic()
+                .context("reading dead letter")?
+                .ok_or_else(|| miette::miette!("dead letter not found: {id}"))?;
+
+            vec![(id, letter)]
+        }
+        None => store
+            .list_dead_letters(worker.as_deref())
+            .await
+            .into_diagnostic()
+            .context("listing dead letters")?,
+    };
+
+    // only the workers of the letters are needed to replay them
+    let workers: BTreeSet<_> = letters.iter().map(|(_, x)| x.worker.clone()).collect();
+
+    let runtime = build_runtime(config, store, false).await?;
+
+    for name in workers {
+        let worker = config
+            .workers
+            .iter()
+            .find(|x| x.name == name)
+            .ok_or_else(|| miette::miette!("worker of dead letters not in config: {name}"))?;
+
+        register_worker(&runtime, worker.clone()).await?;
+    }
+
+    let mut failed = 0;
+
+    for (id, _) in letters.iter() {
+        match runtime.replay_dead_letter(*id).await {
+            Ok(()) => println!("{id}\treplayed"),
+            Err(err) => {
+                warn!(id, %err, "failed to replay dead letter");
+                println!("{id}\tfailed: {err}");
+                failed += 1;
+            }
+        }
+    }
+
+    if failed > 0 {
+        miette::bail!(
+            "{failed} of {} dead letters failed to replay",
+            letters.len()
+        );
+    }
+
+    Ok(())
+}
+
+async fn discard(config: &config::Config, id: DeadLetterId) -> miette::Result<()> {
+    let store = open_store(config, false)?;
+
+    if store
+        .get_dead_letter(id)
+        .await
+        .into_diagnostic()
+        .context("reading dead letter")?
+        .is_none()
+    {
+        miette::bail!("dead letter not found: {id}");
+    }
+
+    store
+        .remove_dead_letter(id)
+        .await
+        .into_diagnostic()
+        .context("removing dead letter")?;
+
+    println!("{id}\tdiscarded");
+
+    Ok(())
+}
@@ -1,7 +1,10 @@
 use std::path::PathBuf;
 
 use balius_runtime::{
-    drivers, ledgers, sign::in_memory::SignerKey, store::redb::Store as RedbStore, Runtime, Store,
+    drivers, ledgers,
+    sign::in_memory::SignerKey,
+    store::{redb::Store as RedbStore, sqlite::Store as SqliteStore},
+    Runtime, Store,
 };
 use boilerplate::{init_meter_provider, metrics_server};
 use clap::{Parser, Subcommand};
@@ -12,6 +15,8 @@ use tracing::info;
 
 mod boilerplate;
 mod config;
+mod dead_letters;
+mod watch;
 
 fn load_worker_config(config_path: Option<PathBuf>) -> miette::Result<serde_json::Value> {
     match config_path {
@@ -34,13 +39,26 @@ struct Cli {
     #[arg(short, long, action)]
     debug: bool,
 
+    /// Reload workers when their module or config file changes.
+    #[arg(short, long, action)]
+    watch: bool,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
 
 #[derive(Subcommand)]
 enum Commands {
-    GetPublicKey { worker: String, key: String },
+    GetPublicKey {
+        worker: String,
+        key: String,
+    },
+
+    /// Inspect and replay events that workers failed to handle
+    DeadLetters {
+        #[command(subcommand)]
+        command: dead_letters::Command,
+    },
 }
 
 #[tokio::main]
@@ -48,42 +66,67 @@ async fn main() -> miette::Result<()> {
     let cli = Cli::parse();
     match cli.command {
         Some(Commands::GetPublicKey { worker, key }) => get_public_key(worker, key).await,
-        None => daemon(cli.debug).await,
+        Some(Commands::DeadLetters { command }) => dead_letters::run(command).await,
+        None => daemon(cli.debug, cli.watch).await,
     }
 }
 
-async fn daemon(debug: bool) -> miette::Result<()> {
-    let config: config::Config = boilerplate::load_config(&None)
+fn open_store(config: &config::Config, debug: bool) -> miette::Result<Store> {
+    let store = match config.store.as_ref() {
+        Some(cfg) => match cfg.kind {
+            config::StoreType::Redb => RedbStore::open(cfg.path.clone(), None).map(Store::Redb),
+            config::StoreType::Sqlite => SqliteStore::open(&cfg.path).map(Store::Sqlite),
+        }
         .into_diagnostic()
-        .context("loading config")?;
-
-    let registry = Registry::new();
-    init_meter_provider(registry.clone())?;
-    boilerplate::setup_tracing(&config.logging).unwrap();
-
-    let mut store = match config.store.as_ref() {
-        Some(cfg) => RedbStore::open(cfg.path.clone(), None)
-            .into_diagnostic()
-            .context("opening store")?,
+        .context("opening store")?,
         None => RedbStore::in_memory()
+            .map(Store::Redb)
             .into_diagnostic()
             .context("opening in memory store")?,
     };
 
-    if debug {
-        info!("converting store into ephemeral for debug mode");
-        store = store
-            .into_ephemeral()
-            .into_diagnostic()
-            .context("converting store into ephemeral")?;
+    if !debug {
+        return Ok(store);
+    }
+
+    info!("converting store into ephemeral for debug mode");
+
+    let store = match store {
+        Store::Redb(mut x) => x.into_ephemeral().map(Store::Redb),
+        Store::Sqlite(mut x) => x.into_ephemeral().map(Store::Sqlite),
+        Store::Custom(_) => unreachable!("custom stores aren't configurable"),
+    };
+
+    store
+        .into_diagnostic()
+        .context("converting store into ephemeral")
+}
+
+/// Build the runtime described by the config and register its workers.
+async fn setup_runtime(config: &config::Config, debug: bool) -> miette::Result<Runtime> {
+    let store = open_store(config, debug)?;
+    let runtime = build_runtime(config, store, debug).await?;
+
+    for worker in config.workers.iter().cloned() {
+        register_worker(&runtime, worker).await?;
     }
 
+    Ok(runtime)
+}
+
+/// Build the runtime described by the config on top of `store`, without
+/// registering any worker.
+async fn build_runtime(
+    config: &config::Config,
+    store: Store,
+    debug: bool,
+) -> miette::Result<Runtime> {
     let ledger = ledgers::u5c::Ledger::new(&config.ledger)
         .await
         .into_diagnostic()
         .context("setting up ledger")?;
 
-    let mut kv: balius_runtime::kv::Kv = (&config).into();
+    let mut kv: balius_runtime::kv::Kv = config.into();
 
     if debug {
         info!("converting kv into ephemeral for debug mode");
@@ -94,28 +137,62 @@ async fn daemon(debug: bool) -> miette::Result<()> {
             .context("converting kv into ephemeral")?;
     }
 
-    let runtime = Runtime::builder(Store::Redb(store))
+    let mut builder = Runtime::builder(store);
+
+    if let Some(pooling) = &config.pooling {
+        builder = builder.with_pooling_allocator(pooling);
+    }
+
+    if let Some(cache) = &config.compile_cache {
+        builder = builder.with_compile_cache(&cache.path);
+    }
+
+    let runtime = builder
         .with_ledger(ledger.into())
         .with_kv(kv)
-        .with_logger((&config).into())
-        .with_signer((&config).into())
+        .with_logger(config.into())
+        .with_signer(config.into())
         .with_submit(config.clone().into_submit().await)
-        .with_http((&config).into())
+        .with_http(config.into())
+        .with_broadcast(Default::default())
         .build()
         .into_diagnostic()
         .context("setting up runtime")?;
 
-    for worker in config.workers {
-        let config = load_worker_config(worker.config)?;
+    Ok(runtime)
+}
 
-        runtime
-            .register_worker_from_file(&worker.name, worker.module, config)
-            .await
-            .into_diagnostic()
-            .context("registering worker")?;
+async fn register_worker(runtime: &Runtime, worker: config::WorkerConfig) -> miette::Result<()> {
+    let config = load_worker_config(worker.config.clone())?;
+    let options = (&worker).into();
 
-        info!(name = worker.name, "registered worker");
-    }
+    runtime
+        .register_worker_from_file(&worker.name, worker.module, config, options)
+        .await
+        .into_diagnostic()
+        .context("registering worker")?;
+
+    info!(name = worker.name, "registered worker");
+
+    Ok(())
+}
+
+async fn daemon(debug: bool, watch: bool) -> miette::Result<()> {
+    let config: config::Config = boilerplate::load_config(&None)
+        .into_diagnostic()
+        .context("loading config")?;
+
+    let registry = Registry::new();
+    init_meter_provider(registry.clone())?;
+    boilerplate::setup_tracing(&config.logging).unwrap();
+
+    let runtime = setup_runtime(&config, debug).await?;
+
+    let watched = if watch {
+        config.workers.clone()
+    } else {
+        vec![]
+    };
 
     let cancel = boilerplate::hook_exit_token();
 
@@ -137,11 +214,38 @@ async fn daemon(debug: bool) -> miette::Result<()> {
         cancel.clone(),
     ));
 
-    let (jsonrpc, chainsync, metrics_server) =
-        tokio::try_join!(jsonrpc_server, chainsync_driver, metrics_server).unwrap();
+    let timer_driver = tokio::spawn(drivers::timer::run(
+        config.timer.unwrap_or_default(),
+        runtime.clone(),
+        cancel.clone(),
+    ));
+
+    let broadcast_driver = tokio::spawn(drivers::broadcast::run(runtime.clone(), cancel.clone()));
+
+    let catchup_driver = tokio::spawn(drivers::catchup::run(
+        config.catchup.unwrap_or_default(),
+        runtime.clone(),
+        cancel.clone(),
+    ));
+
+    let watcher = tokio::spawn(watch::run(watched, runtime.clone(), cancel.clone()));
+
+    let (jsonrpc, chainsync, timer, broadcast, catchup, metrics_server, _) = tokio::try_join!(
+        jsonrpc_server,
+        chainsync_driver,
+        timer_driver,
+        broadcast_driver,
+        catchup_driver,
+        metrics_server,
+        watcher
+    )
+    .unwrap();
 
     jsonrpc.unwrap();
     chainsync.unwrap();
+    timer.unwrap();
+    broadcast.unwrap();
+    catchup.unwrap();
     metrics_server.unwrap();
 
     Ok(())
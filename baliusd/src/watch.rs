@@ -0,0 +1,92 @@
+//! Development mode that upgrades workers when their files change.
+//!
+//! The module and config file of every worker are polled for changes in
+//! their modification time. On change, the worker is upgraded in place (see
+//! `Runtime::upgrade_worker`); a broken build is logged and the previous
+//! version keeps running.
+
+use std::{path::Path, time::Duration, time::SystemTime};
+
+use balius_runtime::Runtime;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+use crate::{config::WorkerConfig, load_worker_config};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+struct Watched {
+    worker: WorkerConfig,
+    modified: Option<(SystemTime, Option<SystemTime>)>,
+}
+
+fn modified_at(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|x| x.modified()).ok()
+}
+
+impl Watched {
+    fn current(&self) -> Option<(SystemTime, Option<SystemTime>)> {
+        let module = modified_at(&self.worker.module)?;
+        let config = self.worker.config.as_deref().and_then(modified_at);
+
+        Some((module, config))
+    }
+
+    async fn upgrade(&self, runtime: &Runtime) -> miette::Result<()> {
+        let config = load_worker_config(self.worker.config.clone())?;
+
+        let wasm = std::fs::read(&self.worker.module)
+            .map_err(|err| miette::miette!("reading worker module: {err}"))?;
+
+        runtime
+            .upgrade_worker(&self.worker.name, &wasm, config, (&self.worker).into())
+            .await
+            .map_err(|err| miette::miette!("upgrading worker: {err}"))
+    }
+}
+
+pub async fn run(workers: Vec<WorkerConfig>, runtime: Runtime, cancel: CancellationToken) {
+    if workers.is_empty() {
+        return;
+    }
+
+    let mut watched = workers
+        .into_iter()
+        .map(|worker| {
+            let mut watched = Watched {
+                worker,
+                modified: None,
+            };
+            watched.modified = watched.current();
+            watched
+        })
+        .collect::<Vec<_>>();
+
+    info!("watching worker files for changes");
+
+    loop {
+        tokio::select! {
+            _ = cancel.cancelled() => {
+                warn!("worker watch cancelled");
+                break;
+            },
+            _ = tokio::time::sleep(POLL_INTERVAL) => {
+                for item in watched.iter_mut() {
+                    let current = item.current();
+
+                    // a missing file is usually a build in progress
+                    if current.is_none() || current == item.modified {
+                        continue;
+                    }
+
+                    item.modified = current;
+
+                    match item.upgrade(&runtime).await {
+                        Ok(()) => info!(name = item.worker.name, "reloaded worker"),
+                        Err(err) => warn!(name = item.worker.name, %err, "failed to reload worker"),
+                    }
+                }
+            }
+        }
+    }
+}